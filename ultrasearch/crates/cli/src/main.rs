@@ -2,11 +2,13 @@ use anyhow::Result;
 use clap::{Parser, Subcommand, ValueEnum};
 use console::style;
 use core_types::config::{default_config_path, load_or_create_config};
+use core_types::query::{ParseError, parse_query};
 #[cfg(not(windows))]
 use ipc::MetricsSnapshot;
 use ipc::{
-    QueryExpr, ReloadConfigRequest, RescanRequest, SearchMode, SearchRequest, SearchResponse,
-    StatusRequest, StatusResponse, TermExpr, TermModifier,
+    FacetField, FacetRequest, HitBatch, HitSource, MatchRange, QueryExpr, ReloadConfigRequest,
+    RescanRequest, SavedSearch, SavedSearchOp, SavedSearchRequest, SavedSearchResponse, SearchHit,
+    SearchMode, SearchRequest, SearchResponse, SortField, SortSpec, StatusRequest, StatusResponse,
 };
 use uuid::Uuid;
 
@@ -33,7 +35,7 @@ struct Cli {
 enum Commands {
     /// Run a search query over IPC.
     Search {
-        /// Query string (full-text or filename).
+        /// Query string, e.g. `ext:pdf "quarterly report" -draft size:>10mb`.
         query: String,
         /// Limit results.
         #[arg(short, long, default_value_t = 20)]
@@ -47,6 +49,25 @@ enum Commands {
         /// Optional timeout in milliseconds.
         #[arg(long)]
         timeout_ms: Option<u64>,
+        /// Only search below this folder (same as adding `in:<folder>`).
+        #[arg(long = "in", value_name = "FOLDER")]
+        in_folder: Option<String>,
+        /// Order the whole result set by a field instead of relevance.
+        #[arg(long, value_enum)]
+        sort: Option<SortArg>,
+        /// Sort descending (largest / newest / Z first).
+        #[arg(long, requires = "sort")]
+        desc: bool,
+        /// Count matches by a field (repeatable), e.g. `--facet ext --facet modified`.
+        #[arg(long = "facet", value_enum, value_name = "FIELD")]
+        facets: Vec<FacetArg>,
+        /// Show the query plan, the Tantivy queries and per-hit score breakdowns.
+        #[arg(long)]
+        explain: bool,
+        /// Continue from the `Next page:` cursor of a previous search with the
+        /// same query, mode and sort (replaces `--offset`).
+        #[arg(long, conflicts_with = "offset")]
+        cursor: Option<String>,
         /// Output as JSON.
         #[arg(long)]
         json: bool,
@@ -78,6 +99,12 @@ enum Commands {
         #[command(subcommand)]
         sub: ConfigCmd,
     },
+
+    /// Manage and run searches saved in the service.
+    Saved {
+        #[command(subcommand)]
+        sub: SavedCmd,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -102,6 +129,54 @@ enum ConfigCmd {
     },
 }
 
+#[derive(Subcommand, Debug)]
+enum SavedCmd {
+    /// List saved searches.
+    List {
+        /// Output as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Save a search under a name, replacing any search with that name.
+    Add {
+        name: String,
+        /// Query string, stored as typed.
+        query: String,
+        /// Search mode (auto/name/content/hybrid).
+        #[arg(short, long, value_enum, default_value_t = ModeArg::Auto)]
+        mode: ModeArg,
+        /// Order results by a field instead of relevance.
+        #[arg(long, value_enum)]
+        sort: Option<SortArg>,
+        /// Sort descending (largest / newest / Z first).
+        #[arg(long, requires = "sort")]
+        desc: bool,
+        /// Remember the match count of each run and report new matches.
+        #[arg(long)]
+        track: bool,
+        /// Output as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Run a saved search.
+    Run {
+        name: String,
+        /// Limit results.
+        #[arg(short, long, default_value_t = 20)]
+        limit: u32,
+        /// Output as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+    /// Delete a saved search.
+    Rm {
+        name: String,
+        /// Output as JSON.
+        #[arg(long)]
+        json: bool,
+    },
+}
+
 #[derive(Copy, Clone, Debug, ValueEnum)]
 enum ModeArg {
     Auto,
@@ -110,6 +185,22 @@ enum ModeArg {
     Hybrid,
 }
 
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum SortArg {
+    Name,
+    Size,
+    Modified,
+    Created,
+}
+
+#[derive(Copy, Clone, Debug, ValueEnum)]
+enum FacetArg {
+    Ext,
+    Volume,
+    Size,
+    Modified,
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
     dotenvy::dotenv().ok();
@@ -121,11 +212,49 @@ async fn main() -> Result<()> {
             offset,
             mode,
             timeout_ms,
+            ref in_folder,
+            sort,
+            desc,
+            ref facets,
+            explain,
+            ref cursor,
             json,
         } => {
-            let req = build_search_request(query, limit, offset, timeout_ms, mode);
-            let resp = pipe(&cli).search(req).await?;
-            output(resp, json, print_search_response)?;
+            let sort = sort.map(|field| sort_spec(field, desc));
+            let mut req = build_search_request(
+                query,
+                in_folder.as_deref(),
+                limit,
+                offset,
+                timeout_ms,
+                mode,
+                sort,
+            )?;
+            if !facets.is_empty() {
+                let fields: Vec<FacetField> = facets
+                    .iter()
+                    .map(|f| match f {
+                        FacetArg::Ext => FacetField::Ext,
+                        FacetArg::Volume => FacetField::Volume,
+                        FacetArg::Size => FacetField::Size,
+                        FacetArg::Modified => FacetField::Modified,
+                    })
+                    .collect();
+                req = req.with_facets(FacetRequest::new(fields));
+            }
+            req.explain = explain;
+            req.cursor = cursor.clone();
+            if json {
+                let resp = pipe(&cli).search(req).await?;
+                output(resp, json, print_search_response)?;
+            } else {
+                // Print each pass's hits as they arrive, then the summary.
+                let mut shown = 0;
+                let resp = pipe(&cli)
+                    .search_stream(req, |batch| print_hit_batch(batch, &mut shown))
+                    .await?;
+                print_search_summary(&resp)?;
+            }
         }
         Commands::Status { json } => {
             let req = StatusRequest { id: Uuid::new_v4() };
@@ -162,6 +291,44 @@ async fn main() -> Result<()> {
                 Ok(())
             })?;
         }
+        Commands::Saved { ref sub } => {
+            let (op, json) = match sub {
+                SavedCmd::List { json } => (SavedSearchOp::List, *json),
+                SavedCmd::Add {
+                    name,
+                    query,
+                    mode,
+                    sort,
+                    desc,
+                    track,
+                    json,
+                } => {
+                    parse_query(query)
+                        .map_err(|err| anyhow::anyhow!(render_parse_error(query, &err)))?;
+                    let mut saved = SavedSearch::new(name.clone(), query.clone());
+                    saved.mode = search_mode(*mode);
+                    saved.sort = sort.map(|field| sort_spec(field, *desc));
+                    saved.track = *track;
+                    (SavedSearchOp::Save(saved), *json)
+                }
+                SavedCmd::Run { name, limit, json } => (
+                    SavedSearchOp::Run {
+                        name: name.clone(),
+                        limit: *limit,
+                    },
+                    *json,
+                ),
+                SavedCmd::Rm { name, json } => {
+                    (SavedSearchOp::Remove { name: name.clone() }, *json)
+                }
+            };
+            let req = SavedSearchRequest {
+                id: Uuid::new_v4(),
+                op,
+            };
+            let resp = pipe(&cli).saved_searches(req).await?;
+            output(resp, json, print_saved_response)?;
+        }
         Commands::Config { sub } => match sub {
             ConfigCmd::Show { json } => {
                 let path = default_config_path();
@@ -245,32 +412,70 @@ fn pipe(_cli: &Cli) -> StubClient {
 
 fn build_search_request(
     query: &str,
+    in_folder: Option<&str>,
     limit: u32,
     offset: u32,
     timeout_ms: Option<u64>,
     mode: ModeArg,
-) -> SearchRequest {
-    let term = QueryExpr::Term(TermExpr {
-        field: None,
-        value: query.to_string(),
-        modifier: TermModifier::Term,
-    });
-
-    SearchRequest {
+    sort: Option<SortSpec>,
+) -> Result<SearchRequest> {
+    let mut expr =
+        parse_query(query).map_err(|err| anyhow::anyhow!(render_parse_error(query, &err)))?;
+    if let Some(folder) = in_folder {
+        expr = expr.and(QueryExpr::in_folder(folder));
+    }
+
+    Ok(SearchRequest {
         id: Uuid::new_v4(),
-        query: term,
+        query: expr,
         limit,
         offset,
-        mode: match mode {
-            ModeArg::Auto => SearchMode::Auto,
-            ModeArg::Name => SearchMode::NameOnly,
-            ModeArg::Content => SearchMode::Content,
-            ModeArg::Hybrid => SearchMode::Hybrid,
-        },
+        mode: search_mode(mode),
         timeout: timeout_ms.map(std::time::Duration::from_millis),
+        sort,
+        facets: None,
+        explain: false,
+        cursor: None,
+        stream: false,
+    })
+}
+
+fn search_mode(mode: ModeArg) -> SearchMode {
+    match mode {
+        ModeArg::Auto => SearchMode::Auto,
+        ModeArg::Name => SearchMode::NameOnly,
+        ModeArg::Content => SearchMode::Content,
+        ModeArg::Hybrid => SearchMode::Hybrid,
+    }
+}
+
+fn sort_spec(field: SortArg, desc: bool) -> SortSpec {
+    let field = match field {
+        SortArg::Name => SortField::Name,
+        SortArg::Size => SortField::Size,
+        SortArg::Modified => SortField::Modified,
+        SortArg::Created => SortField::Created,
+    };
+    if desc {
+        SortSpec::desc(field)
+    } else {
+        SortSpec::asc(field)
     }
 }
 
+/// Render a parse error with the query echoed and the offending span underlined.
+fn render_parse_error(query: &str, err: &ParseError) -> String {
+    let pad = query[..err.span.start].chars().count();
+    let width = query[err.span.start..err.span.end].chars().count().max(1);
+    format!(
+        "invalid query: {}\n  {}\n  {}{}",
+        err.message,
+        query,
+        " ".repeat(pad),
+        "^".repeat(width)
+    )
+}
+
 fn print_status_response(resp: &StatusResponse) -> Result<()> {
     println!("{}", style("Service Status:").green());
     println!("  Scheduler: {}", resp.scheduler_state);
@@ -292,6 +497,12 @@ fn print_status_response(resp: &StatusResponse) -> Result<()> {
         if let Some(drop) = metrics.content_dropped {
             println!("    Content Jobs Dropped: {}", drop);
         }
+        if let (Some(hits), Some(misses)) = (metrics.cache_hits, metrics.cache_misses) {
+            println!("    Result Cache: {} hits / {} misses", hits, misses);
+        }
+        if let Some(bytes) = metrics.name_ngram_bytes {
+            println!("    Name Trigrams: {} bytes", bytes);
+        }
     }
 
     println!(
@@ -308,16 +519,59 @@ fn print_status_response(resp: &StatusResponse) -> Result<()> {
 }
 
 fn print_search_response(resp: &SearchResponse) -> Result<()> {
-    println!("{}", style("Hits:").green());
-    for (i, hit) in resp.hits.iter().enumerate() {
+    if resp.error.is_none() {
+        println!("{}", style("Hits:").green());
+        print_hits(&resp.hits, 0);
+    }
+    print_search_summary(resp)
+}
+
+/// Print a streamed batch under its index pass, numbering on from `shown`.
+fn print_hit_batch(batch: &HitBatch, shown: &mut usize) {
+    if batch.hits.is_empty() {
+        return;
+    }
+    let source = match batch.source {
+        HitSource::Meta => "Name matches:",
+        HitSource::Content => "Content matches:",
+    };
+    println!("{}", style(source).green());
+    print_hits(&batch.hits, *shown);
+    *shown += batch.hits.len();
+}
+
+fn print_hits(hits: &[SearchHit], first: usize) {
+    for (i, hit) in hits.iter().enumerate() {
         println!(
-            "{:3}. {:<40} {:<6} score={:.3} path={}",
-            i + 1,
+            "{:3}. {:<40} {:<6} score={:.3}{} path={}",
+            first + i + 1,
             hit.name.as_deref().unwrap_or("<unknown>"),
             hit.ext.as_deref().unwrap_or(""),
             hit.score,
+            if hit.approximate { " ~" } else { "" },
             hit.path.as_deref().unwrap_or("")
         );
+        if let Some(snippet) = &hit.snippet {
+            println!("     {}", emphasize(snippet, &hit.highlights.snippet));
+        }
+        for explanation in &hit.explain {
+            println!(
+                "     {} score={:.3}",
+                style(format!("[{}]", explanation.index)).dim(),
+                explanation.score
+            );
+            for line in explanation.detail.lines() {
+                println!("       {line}");
+            }
+        }
+    }
+}
+
+/// Print everything about a search except its hits.
+fn print_search_summary(resp: &SearchResponse) -> Result<()> {
+    if let Some(err) = &resp.error {
+        println!("{} {}", style("Search failed:").red(), err);
+        return Ok(());
     }
     println!(
         "{}",
@@ -330,9 +584,101 @@ fn print_search_response(resp: &SearchResponse) -> Result<()> {
         ))
         .dim()
     );
+    if let Some(cursor) = &resp.next_cursor {
+        println!("{} --cursor {}", style("Next page:").dim(), cursor);
+    }
+    if !resp.suggestions.is_empty() {
+        let fixes: Vec<String> = resp
+            .suggestions
+            .iter()
+            .map(|s| format!("{} -> {}", s.term, s.suggestion))
+            .collect();
+        println!("{} {}", style("Did you mean:").yellow(), fixes.join(", "));
+    }
+    if let Some(explain) = &resp.explain {
+        println!("{} {:?}", style("Mode:").green(), explain.mode);
+        println!("{} {:?}", style("Plan:").green(), explain.plan);
+        for q in &explain.queries {
+            println!(
+                "{} {}",
+                style(format!("Query [{}]:", q.index)).green(),
+                q.query
+            );
+        }
+    }
+    for facet in &resp.facets {
+        let counts: Vec<String> = facet
+            .buckets
+            .iter()
+            .map(|b| {
+                let value = if b.value.is_empty() {
+                    "(none)"
+                } else {
+                    &b.value
+                };
+                format!("{} {}", b.count, value)
+            })
+            .collect();
+        println!(
+            "{} {}",
+            style(format!("{:?}:", facet.field)).green(),
+            counts.join(", ")
+        );
+    }
+    Ok(())
+}
+
+fn print_saved_response(resp: &SavedSearchResponse) -> Result<()> {
+    if let Some(err) = &resp.error {
+        println!("{} {}", style("Saved searches failed:").red(), err);
+        return Ok(());
+    }
+    if let Some(results) = &resp.results {
+        print_search_response(results)?;
+        if let Some(new) = resp.new_matches {
+            println!(
+                "{}",
+                style(format!("{new} new matches since last run")).yellow()
+            );
+        }
+        return Ok(());
+    }
+    println!("{}", style("Saved searches:").green());
+    for saved in &resp.searches {
+        let mut details = format!("{:?}", saved.mode);
+        if let Some(sort) = saved.sort {
+            details.push_str(&format!(", sort {:?} {:?}", sort.field, sort.direction));
+        }
+        if let Some(total) = saved.last_total {
+            details.push_str(&format!(", {total} matches last run"));
+        }
+        println!(
+            "  {:<20} {}  {}",
+            saved.name,
+            saved.query,
+            style(details).dim()
+        );
+    }
     Ok(())
 }
 
+/// Render `text` on one line with the matched ranges in bold.
+fn emphasize(text: &str, ranges: &[MatchRange]) -> String {
+    let mut out = String::new();
+    let mut at = 0;
+    for r in ranges {
+        let (start, end) = (r.start as usize, r.end as usize);
+        let (Some(before), Some(matched)) = (text.get(at..start), text.get(start..end)) else {
+            continue;
+        };
+        out.push_str(before);
+        out.push_str(&style(matched).bold().to_string());
+        at = end;
+    }
+    out.push_str(text.get(at..).unwrap_or_default());
+    out.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
 fn output<T, F>(value: T, json: bool, pretty: F) -> Result<()>
 where
     T: serde::Serialize,
@@ -357,6 +703,13 @@ impl StubClient {
     async fn search(&self, req: SearchRequest) -> Result<SearchResponse> {
         stub_search(req).await
     }
+    async fn search_stream(
+        &self,
+        req: SearchRequest,
+        _on_batch: impl FnMut(&HitBatch),
+    ) -> Result<SearchResponse> {
+        stub_search(req).await
+    }
     async fn reload_config(&self, _: ReloadConfigRequest) -> Result<ipc::ReloadConfigResponse> {
         Ok(ipc::ReloadConfigResponse {
             id: Uuid::new_v4(),
@@ -371,6 +724,15 @@ impl StubClient {
             message: Some("stub".into()),
         })
     }
+    async fn saved_searches(&self, req: SavedSearchRequest) -> Result<SavedSearchResponse> {
+        Ok(SavedSearchResponse {
+            id: req.id,
+            searches: Vec::new(),
+            results: None,
+            new_matches: None,
+            error: Some("stub".into()),
+        })
+    }
 }
 
 #[cfg(not(windows))]
@@ -386,6 +748,11 @@ async fn stub_search(req: SearchRequest) -> Result<SearchResponse> {
         truncated: false,
         took_ms: 0,
         served_by: Some("cli-linux-stub".into()),
+        error: None,
+        facets: Vec::new(),
+        explain: None,
+        next_cursor: None,
+        suggestions: Vec::new(),
     })
 }
 
@@ -409,6 +776,9 @@ async fn stub_status(req: StatusRequest) -> Result<StatusResponse> {
             active_workers: Some(0),
             content_enqueued: Some(0),
             content_dropped: Some(0),
+            cache_hits: Some(0),
+            cache_misses: Some(0),
+            name_ngram_bytes: None,
         }),
         served_by: Some("cli-linux-stub".into()),
     })
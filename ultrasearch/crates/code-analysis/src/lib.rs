@@ -0,0 +1,204 @@
+//! Identifier-aware analysis for source code and file names.
+//!
+//! `parseHttpRequest`, `parse_http_request`, `parse-http-request` and
+//! `ParseHTTPRequest` all become `parse`, `http`, `request` at consecutive
+//! positions, so `http request` finds each of them. The whole identifier is
+//! kept as well, with separators dropped (`parsehttprequest`), at the
+//! position of its first part.
+
+use tantivy::tokenizer::{
+    LowerCaser, RemoveLongFilter, TextAnalyzer, Token, TokenStream, Tokenizer, TokenizerManager,
+};
+
+pub const CODE_ANALYZER: &str = "code_analyzer";
+
+pub fn register_code_analyzers(manager: &TokenizerManager) {
+    let code_analyzer = TextAnalyzer::builder(IdentifierTokenizer::default())
+        .filter(RemoveLongFilter::limit(255))
+        .filter(LowerCaser)
+        .build();
+
+    manager.register(CODE_ANALYZER, code_analyzer);
+}
+
+/// Splits text into words (runs of alphanumerics joined by `_` or `-`) and
+/// words into identifier parts.
+#[derive(Clone, Default)]
+pub struct IdentifierTokenizer {
+    tokens: Vec<Token>,
+}
+
+pub struct IdentifierTokenStream<'a> {
+    tokens: &'a mut [Token],
+    /// Tokens handed out so far.
+    advanced: usize,
+}
+
+impl Tokenizer for IdentifierTokenizer {
+    type TokenStream<'a> = IdentifierTokenStream<'a>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> IdentifierTokenStream<'a> {
+        self.tokens.clear();
+        let mut position = 0;
+        for (start, word) in words(text) {
+            let parts = split_identifier(word);
+            if parts.len() > 1 {
+                let whole: String = parts.iter().map(|&(from, to)| &word[from..to]).collect();
+                self.tokens
+                    .push(token(whole, start, start + word.len(), position));
+            }
+            for (from, to) in parts {
+                self.tokens.push(token(
+                    word[from..to].to_string(),
+                    start + from,
+                    start + to,
+                    position,
+                ));
+                position += 1;
+            }
+        }
+        IdentifierTokenStream {
+            tokens: &mut self.tokens,
+            advanced: 0,
+        }
+    }
+}
+
+impl TokenStream for IdentifierTokenStream<'_> {
+    fn advance(&mut self) -> bool {
+        if self.advanced < self.tokens.len() {
+            self.advanced += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn token(&self) -> &Token {
+        &self.tokens[self.advanced - 1]
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.tokens[self.advanced - 1]
+    }
+}
+
+fn token(text: String, offset_from: usize, offset_to: usize, position: usize) -> Token {
+    Token {
+        offset_from,
+        offset_to,
+        position,
+        text,
+        position_length: 1,
+    }
+}
+
+fn is_connector(c: char) -> bool {
+    c == '_' || c == '-'
+}
+
+/// Words of `text` with their byte offsets; connectors at either end of a
+/// word are dropped.
+fn words(text: &str) -> Vec<(usize, &str)> {
+    let mut out = Vec::new();
+    let mut start = None;
+    // A trailing separator closes the last word.
+    for (at, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        let in_word = c.is_alphanumeric() || is_connector(c);
+        match start {
+            None if in_word => start = Some(at),
+            Some(from) if !in_word => {
+                start = None;
+                let word = &text[from..at];
+                let lead = word.len() - word.trim_start_matches(is_connector).len();
+                let trimmed = word.trim_matches(is_connector);
+                if !trimmed.is_empty() {
+                    out.push((from + lead, trimmed));
+                }
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+/// Byte ranges of the parts of `word`: split at connectors, before an
+/// uppercase letter that follows a lowercase letter or digit, and before the
+/// last capital of an acronym that runs into a word (`HTTPRequest`).
+fn split_identifier(word: &str) -> Vec<(usize, usize)> {
+    let chars: Vec<(usize, char)> = word.char_indices().collect();
+    let mut parts = Vec::new();
+    let mut start = None;
+    for (i, &(at, c)) in chars.iter().enumerate() {
+        if is_connector(c) {
+            if let Some(from) = start.take() {
+                parts.push((from, at));
+            }
+            continue;
+        }
+        let Some(from) = start else {
+            start = Some(at);
+            continue;
+        };
+        let prev = chars[i - 1].1;
+        let next = chars.get(i + 1).map(|&(_, c)| c);
+        let boundary = c.is_uppercase()
+            && (prev.is_lowercase()
+                || prev.is_numeric()
+                || (prev.is_uppercase() && next.is_some_and(char::is_lowercase)));
+        if boundary {
+            parts.push((from, at));
+            start = Some(at);
+        }
+    }
+    if let Some(from) = start {
+        parts.push((from, word.len()));
+    }
+    parts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tokens(text: &str) -> Vec<(String, usize)> {
+        let manager = TokenizerManager::default();
+        register_code_analyzers(&manager);
+        let mut analyzer = manager.get(CODE_ANALYZER).unwrap();
+        let mut stream = analyzer.token_stream(text);
+        let mut out = Vec::new();
+        while stream.advance() {
+            out.push((stream.token().text.clone(), stream.token().position));
+        }
+        out
+    }
+
+    #[test]
+    fn identifiers_split_into_parts() {
+        let expected = [
+            ("parsehttprequest".to_string(), 0),
+            ("parse".to_string(), 0),
+            ("http".to_string(), 1),
+            ("request".to_string(), 2),
+        ];
+        for ident in [
+            "parseHttpRequest",
+            "parse_http_request",
+            "parse-http-request",
+            "ParseHTTPRequest",
+        ] {
+            assert_eq!(tokens(ident), expected, "{ident}");
+        }
+        assert_eq!(
+            tokens("let _x = HTTP2Server::new();"),
+            [
+                ("let".to_string(), 0),
+                ("x".to_string(), 1),
+                ("http2server".to_string(), 2),
+                ("http2".to_string(), 2),
+                ("server".to_string(), 3),
+                ("new".to_string(), 4),
+            ]
+        );
+    }
+}
@@ -170,6 +170,7 @@ impl Extractor for IFilterExtractor {
                 truncated,
                 content_lang: None,
                 bytes_processed,
+                mime: ctx.mime_hint.map(str::to_string),
             })
         }
     }
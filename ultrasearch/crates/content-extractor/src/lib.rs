@@ -24,6 +24,8 @@ pub struct ExtractedContent {
     pub truncated: bool,
     pub content_lang: Option<String>,
     pub bytes_processed: usize,
+    /// MIME type reported by the backend, else `ExtractContext::mime_hint`.
+    pub mime: Option<String>,
 }
 
 /// Context passed to extractors (paths, limits, hints).
@@ -129,6 +131,7 @@ impl Extractor for NoopExtractor {
             truncated,
             content_lang: None,
             bytes_processed: used,
+            mime: ctx.mime_hint.map(str::to_string),
         })
     }
 }
@@ -178,6 +181,7 @@ impl Extractor for SimpleTextExtractor {
             truncated,
             content_lang: None,
             bytes_processed: used_bytes,
+            mime: ctx.mime_hint.map(str::to_string),
         })
     }
 }
@@ -272,9 +276,14 @@ impl Extractor for ExtractousExtractor {
         }
 
         let engine = self.build_engine(ctx);
-        let (text, _metadata) = engine
+        let (text, metadata) = engine
             .extract_file_to_string(ctx.path)
             .map_err(|e| ExtractError::Failed(e.to_string()))?;
+        let mime = metadata
+            .get("Content-Type")
+            .and_then(|types| types.first())
+            .cloned()
+            .or_else(|| ctx.mime_hint.map(str::to_string));
 
         let byte_len = text.len();
         let char_len = text.chars().count();
@@ -287,6 +296,7 @@ impl Extractor for ExtractousExtractor {
             truncated,
             bytes_processed: byte_len.min(ctx.max_bytes),
             text,
+            mime,
         })
     }
 }
@@ -311,6 +321,20 @@ mod tests {
         assert_eq!(out.bytes_processed, 0);
     }
 
+    #[test]
+    fn results_carry_the_mime_hint() {
+        let ctx = ExtractContext {
+            path: "dummy",
+            max_bytes: 1024,
+            max_chars: 1024,
+            ext_hint: None,
+            mime_hint: Some("text/x-log"),
+        };
+        let stack = ExtractorStack::new(vec![Box::new(NoopExtractor)]);
+        let out = stack.extract(DocKey::from_parts(1, 42), &ctx).unwrap();
+        assert_eq!(out.mime.as_deref(), Some("text/x-log"));
+    }
+
     #[test]
     fn enforce_limits_truncates_on_chars() {
         let s = "abcdef";
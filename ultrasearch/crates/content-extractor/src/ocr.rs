@@ -96,6 +96,7 @@ impl Extractor for OcrExtractor {
             truncated,
             content_lang: None,
             bytes_processed: used_bytes,
+            mime: ctx.mime_hint.map(str::to_string),
         })
     }
 }
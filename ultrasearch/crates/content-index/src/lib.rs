@@ -1,12 +1,17 @@
 //! Tantivy-based content index (full-text).
 //!
 //! Schema matches the plan: doc_key, volume, name/path/ext metadata, size,
-//! modified, optional content_lang, and the main `content` text field.
+//! modified/created, optional content_lang, and the main `content` text field.
+//! With doc-type analyzers, text of source files, logs and prose goes to
+//! `content_code`, `content_log` and `content_prose` instead, each with its
+//! own analyzer; queries search all of them.
 
 use std::path::Path;
 
-use anyhow::Result;
-use core_types::DocKey;
+use anyhow::{Context, Result};
+use core_types::config::FeaturesSection;
+use core_types::kind;
+use core_types::{DocKey, SchemaMismatch};
 pub use tantivy::IndexWriter;
 use tantivy::{Index, IndexSettings, ReloadPolicy, schema::document::TantivyDocument, schema::*};
 
@@ -24,6 +29,113 @@ pub struct ContentFields {
     pub modified: Field,
     pub content_lang: Field,
     pub content: Field,
+    pub created: Field,
+    /// Lowercased, untokenized file name used for glob matching and name sorting.
+    pub name_raw: Field,
+    /// File kind from `core_types::kind` (`document`, `image`, `code`, ...).
+    pub kind: Field,
+    /// Every ancestor folder `DocKey` (multi-valued), used by `in:` filters.
+    pub ancestors: Field,
+    /// Text of source files, split into identifier parts (`CODE_ANALYZER`).
+    pub content_code: Field,
+    /// Text of log files (`LOG_ANALYZER`).
+    pub content_log: Field,
+    /// Text of documents and notes, stemmed (`PROSE_ANALYZER`).
+    pub content_prose: Field,
+}
+
+/// Tantivy's built-in English stemming analyzer.
+pub const PROSE_ANALYZER: &str = "en_stem";
+
+impl ContentFields {
+    /// Field handles for an opened index, which must have every field of
+    /// `build_schema` under the same id. Indexes created by earlier versions
+    /// lack some and have to be rebuilt.
+    pub fn for_index(index: &Index) -> Result<Self> {
+        let (expected, fields) = build_schema();
+        let actual = index.schema();
+        for (field, entry) in expected.fields() {
+            let matches = actual.get_field(entry.name()).is_ok_and(|found| {
+                found == field
+                    && actual.get_field_entry(found).field_type().value_type()
+                        == entry.field_type().value_type()
+            });
+            if !matches {
+                return Err(SchemaMismatch {
+                    index: "content",
+                    field: entry.name().to_string(),
+                }
+                .into());
+            }
+        }
+        Ok(fields)
+    }
+
+    /// Field holding the text of documents of `kind`.
+    pub fn text_field(&self, kind: TextKind) -> Field {
+        match kind {
+            TextKind::Plain => self.content,
+            TextKind::Code => self.content_code,
+            TextKind::Log => self.content_log,
+            TextKind::Prose => self.content_prose,
+        }
+    }
+
+    /// Every field that may hold a document's text.
+    pub fn text_fields(&self) -> [Field; 4] {
+        [
+            self.content,
+            self.content_code,
+            self.content_log,
+            self.content_prose,
+        ]
+    }
+}
+
+/// How a document's text is analyzed, and so which field holds it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TextKind {
+    /// The default tokenizer, in `content`.
+    #[default]
+    Plain,
+    /// Identifier splitting, in `content_code`.
+    Code,
+    /// The log analyzer, in `content_log`.
+    Log,
+    /// English stemming, in `content_prose`.
+    Prose,
+}
+
+impl TextKind {
+    /// Analysis suited to a file with extension `ext` (lowercase) and, when
+    /// known, MIME type `mime`.
+    pub fn classify(ext: Option<&str>, mime: Option<&str>) -> Self {
+        if ext == Some("log") || mime == Some("text/x-log") {
+            return TextKind::Log;
+        }
+        match kind::classify(ext, mime).as_str() {
+            kind::CODE => TextKind::Code,
+            kind::DOCUMENT | kind::PRESENTATION => TextKind::Prose,
+            kind::TEXT if matches!(ext, Some("txt" | "md" | "rst")) => TextKind::Prose,
+            _ => TextKind::Plain,
+        }
+    }
+
+    /// Analysis to index a file's text with: `classify` under
+    /// `doc_type_analyzers`, and the log analyzer for every text file under
+    /// `log_dataset_mode`.
+    pub fn for_file(ext: Option<&str>, mime: Option<&str>, features: &FeaturesSection) -> Self {
+        let classified = TextKind::classify(ext, mime);
+        if features.log_dataset_mode
+            && (classified == TextKind::Log || kind::classify(ext, mime) == kind::TEXT)
+        {
+            TextKind::Log
+        } else if features.doc_type_analyzers {
+            classified
+        } else {
+            TextKind::Plain
+        }
+    }
 }
 
 pub fn build_schema() -> (Schema, ContentFields) {
@@ -39,7 +151,19 @@ pub fn build_schema() -> (Schema, ContentFields) {
     let content_lang = builder.add_text_field("content_lang", STRING | STORED);
 
     // Use default tokenizer for content, but allow overrides via per-field options later if needed.
-    let content = builder.add_text_field("content", TEXT);
+    // Stored (bounded by `extract.max_chars_per_file`) so hits can carry snippets.
+    let content = builder.add_text_field("content", TEXT | STORED);
+    // Added later, after `content` so the original fields keep their ids.
+    // Indexes created without them are rejected by `ContentFields::for_index`.
+    let created = builder.add_i64_field("created", FAST | STORED);
+    let name_raw = builder.add_text_field("name_raw", STRING | FAST);
+    let kind = builder.add_text_field("kind", STRING | FAST);
+    let ancestors = builder.add_u64_field("ancestors", INDEXED);
+    let content_code =
+        builder.add_text_field("content_code", analyzed_text(code_analysis::CODE_ANALYZER));
+    let content_log =
+        builder.add_text_field("content_log", analyzed_text(log_analysis::LOG_ANALYZER));
+    let content_prose = builder.add_text_field("content_prose", analyzed_text(PROSE_ANALYZER));
 
     let fields = ContentFields {
         doc_key,
@@ -51,11 +175,28 @@ pub fn build_schema() -> (Schema, ContentFields) {
         modified,
         content_lang,
         content,
+        created,
+        name_raw,
+        kind,
+        ancestors,
+        content_code,
+        content_log,
+        content_prose,
     };
 
     (builder.build(), fields)
 }
 
+/// Stored text with positions, tokenized by `analyzer`.
+fn analyzed_text(analyzer: &str) -> TextOptions {
+    let indexing = TextFieldIndexing::default()
+        .set_tokenizer(analyzer)
+        .set_index_option(IndexRecordOption::WithFreqsAndPositions);
+    TextOptions::default()
+        .set_indexing_options(indexing)
+        .set_stored()
+}
+
 #[derive(Debug)]
 pub struct ContentIndex {
     pub index: Index,
@@ -64,16 +205,20 @@ pub struct ContentIndex {
 
 fn setup_index(index: &Index) {
     log_analysis::register_log_analyzers(index.tokenizers());
+    code_analysis::register_code_analyzers(index.tokenizers());
 }
 
+/// Open the index at `path`, creating it if there is none. Fails when the
+/// existing index has an outdated schema (see `ContentFields::for_index`).
 pub fn open_or_create(path: &Path) -> Result<ContentIndex> {
-    let (schema, fields) = build_schema();
     let index = if path.join("meta.json").exists() {
         Index::open_in_dir(path)?
     } else {
-        Index::create_in_dir(path, schema)?
+        Index::create_in_dir(path, build_schema().0)?
     };
     setup_index(&index);
+    let fields = ContentFields::for_index(&index)
+        .with_context(|| format!("opening content index at {}", path.display()))?;
     Ok(ContentIndex { index, fields })
 }
 
@@ -126,8 +271,13 @@ pub struct ContentDoc {
     pub ext: Option<String>,
     pub size: u64,
     pub modified: i64,
+    pub created: i64,
     pub content_lang: Option<String>,
     pub content: String,
+    /// Ancestor folders, nearest first.
+    pub ancestors: Vec<DocKey>,
+    /// Decides which field `content` is indexed into.
+    pub text_kind: TextKind,
 }
 
 pub fn to_document(doc: &ContentDoc, fields: &ContentFields) -> TantivyDocument {
@@ -136,6 +286,7 @@ pub fn to_document(doc: &ContentDoc, fields: &ContentFields) -> TantivyDocument
     d.add_u64(fields.volume, doc.volume as u64);
     if let Some(name) = &doc.name {
         d.add_text(fields.name, name);
+        d.add_text(fields.name_raw, name.to_lowercase());
     }
     if let Some(path) = &doc.path {
         d.add_text(fields.path, path);
@@ -145,10 +296,18 @@ pub fn to_document(doc: &ContentDoc, fields: &ContentFields) -> TantivyDocument
     }
     d.add_u64(fields.size, doc.size);
     d.add_i64(fields.modified, doc.modified);
+    d.add_i64(fields.created, doc.created);
+    d.add_text(
+        fields.kind,
+        core_types::kind::classify(doc.ext.as_deref(), None),
+    );
+    for ancestor in &doc.ancestors {
+        d.add_u64(fields.ancestors, ancestor.0);
+    }
     if let Some(lang) = &doc.content_lang {
         d.add_text(fields.content_lang, lang);
     }
-    d.add_text(fields.content, &doc.content);
+    d.add_text(fields.text_field(doc.text_kind), &doc.content);
     d
 }
 
@@ -181,6 +340,13 @@ mod tests {
             fields.modified,
             fields.content_lang,
             fields.content,
+            fields.created,
+            fields.name_raw,
+            fields.kind,
+            fields.ancestors,
+            fields.content_code,
+            fields.content_log,
+            fields.content_prose,
         ] {
             assert!(!schema.get_field_entry(f).name().is_empty());
         }
@@ -197,8 +363,11 @@ mod tests {
             ext: Some("txt".into()),
             size: 10,
             modified: 123,
+            created: 100,
             content_lang: Some("en".into()),
             content: "hello world".into(),
+            ancestors: vec![DocKey::from_parts(1, 5)],
+            text_kind: TextKind::Plain,
         };
         let tantivy_doc = to_document(&doc, &fields);
         let mut vals = tantivy_doc.get_all(fields.doc_key);
@@ -206,6 +375,34 @@ mod tests {
         let owned: OwnedValue = first.into();
         assert!(matches!(owned, OwnedValue::U64(v) if v == doc.key.0));
         assert!(vals.next().is_none());
+        let kind: OwnedValue = tantivy_doc.get_first(fields.kind).unwrap().into();
+        assert_eq!(kind, OwnedValue::Str(core_types::kind::TEXT.into()));
+    }
+
+    #[test]
+    fn outdated_schemas_need_a_rebuild() {
+        // The schema of the first release, before `created`, `kind`, etc.
+        let mut builder = Schema::builder();
+        builder.add_u64_field("doc_key", FAST | STORED);
+        builder.add_u64_field("volume", FAST | STORED);
+        builder.add_text_field("name", TEXT | STORED);
+        builder.add_text_field("path", TEXT | STORED);
+        builder.add_text_field("ext", STRING | FAST);
+        builder.add_u64_field("size", FAST | STORED);
+        builder.add_i64_field("modified", FAST | STORED);
+        builder.add_text_field("content_lang", STRING | STORED);
+        builder.add_text_field("content", TEXT);
+        let dir = tempfile::tempdir().unwrap();
+        Index::create_in_dir(dir.path(), builder.build()).unwrap();
+
+        let err = open_or_create(dir.path()).unwrap_err();
+        assert!(format!("{err:#}").contains("rebuild required"), "{err:#}");
+        assert!(err.chain().any(|e| e.is::<SchemaMismatch>()));
+
+        let fresh = tempfile::tempdir().unwrap();
+        open_or_create(fresh.path()).unwrap();
+        // Reopening an index of the current schema works.
+        open_or_create(fresh.path()).unwrap();
     }
 
     #[test]
@@ -214,6 +411,126 @@ mod tests {
         let reader = open_reader(&idx).unwrap();
         assert_eq!(reader.searcher().num_docs(), 0);
     }
+
+    #[test]
+    fn text_kind_follows_feature_flags() {
+        let mut features = FeaturesSection::default();
+        let kind = |ext, features: &FeaturesSection| TextKind::for_file(Some(ext), None, features);
+        for ext in ["rs", "log", "docx", "txt", "json"] {
+            assert_eq!(kind(ext, &features), TextKind::Plain, "{ext}");
+        }
+
+        features.doc_type_analyzers = true;
+        assert_eq!(kind("rs", &features), TextKind::Code);
+        assert_eq!(kind("log", &features), TextKind::Log);
+        assert_eq!(kind("docx", &features), TextKind::Prose);
+        assert_eq!(kind("txt", &features), TextKind::Prose);
+        assert_eq!(kind("json", &features), TextKind::Plain);
+        assert_eq!(
+            TextKind::for_file(None, Some("text/x-log"), &features),
+            TextKind::Log
+        );
+
+        // Every text file is a log in a log dataset; code stays code.
+        features.log_dataset_mode = true;
+        assert_eq!(kind("txt", &features), TextKind::Log);
+        assert_eq!(kind("json", &features), TextKind::Log);
+        assert_eq!(kind("rs", &features), TextKind::Code);
+        features.doc_type_analyzers = false;
+        assert_eq!(kind("log", &features), TextKind::Log);
+        assert_eq!(kind("docx", &features), TextKind::Plain);
+    }
+
+    #[test]
+    fn each_text_kind_uses_its_analyzer() {
+        use tantivy::collector::Count;
+        use tantivy::query::QueryParser;
+
+        let idx = create_in_ram().unwrap();
+        let mut writer = idx.index.writer_with_num_threads(1, 15_000_000).unwrap();
+        let texts = [
+            (TextKind::Plain, "quarterly reports"),
+            (TextKind::Prose, "the reports were filed"),
+            (TextKind::Log, "ERROR status=500 path=/api/report"),
+            (TextKind::Code, "fn buildReport() {}"),
+        ];
+        for (i, (text_kind, text)) in texts.into_iter().enumerate() {
+            let doc = ContentDoc {
+                key: DocKey::from_parts(1, i as u64 + 1),
+                volume: 1,
+                name: None,
+                path: None,
+                ext: None,
+                size: 0,
+                modified: 0,
+                created: 0,
+                content_lang: None,
+                content: text.into(),
+                ancestors: Vec::new(),
+                text_kind,
+            };
+            add_content_doc(&mut writer, &idx.fields, &doc).unwrap();
+        }
+        writer.commit().unwrap();
+        let searcher = open_reader(&idx).unwrap().searcher();
+        let parser = QueryParser::for_index(&idx.index, idx.fields.text_fields().to_vec());
+        let count = |q: &str| {
+            searcher
+                .search(&parser.parse_query(q).unwrap(), &Count)
+                .unwrap()
+        };
+
+        // Stemmed prose matches the singular; the plain field does not.
+        assert_eq!(count("report"), 3);
+        assert_eq!(count("reports"), 2);
+        assert_eq!(count("status"), 1);
+        assert_eq!(count("build"), 1);
+    }
+
+    #[test]
+    fn code_content_matches_identifier_words() {
+        use tantivy::collector::Count;
+        use tantivy::query::QueryParser;
+
+        let idx = create_in_ram().unwrap();
+        let mut writer = idx.index.writer_with_num_threads(1, 15_000_000).unwrap();
+        let texts = [
+            "fn parse_http_request() {}",
+            "function parseHttpRequest() {}",
+            "class ParseHTTPRequest {}",
+            "http and request, far apart",
+        ];
+        for (i, text) in texts.iter().enumerate() {
+            let doc = ContentDoc {
+                key: DocKey::from_parts(1, i as u64 + 1),
+                volume: 1,
+                name: None,
+                path: None,
+                ext: Some("rs".into()),
+                size: 0,
+                modified: 0,
+                created: 0,
+                content_lang: None,
+                content: text.to_string(),
+                ancestors: Vec::new(),
+                text_kind: TextKind::Code,
+            };
+            writer.add_document(to_document(&doc, &idx.fields)).unwrap();
+        }
+        writer.commit().unwrap();
+        let searcher = idx.index.reader().unwrap().searcher();
+        let parser = QueryParser::for_index(&idx.index, vec![idx.fields.content_code]);
+        let count = |q: &str| {
+            searcher
+                .search(&parser.parse_query(q).unwrap(), &Count)
+                .unwrap()
+        };
+
+        assert_eq!(count("\"http request\""), 3);
+        assert_eq!(count("parse_http_request"), 3);
+        assert_eq!(count("ParseHttpRequest"), 3);
+        assert_eq!(count("+http +request"), 4);
+    }
 }
 
 #[test]
@@ -228,8 +545,11 @@ fn add_content_doc_appends() {
         ext: Some("txt".into()),
         size: 10,
         modified: 123,
+        created: 100,
         content_lang: Some("en".into()),
         content: "hello world".into(),
+        ancestors: vec![DocKey::from_parts(1, 5)],
+        text_kind: TextKind::Plain,
     };
     add_content_doc(&mut writer, &idx.fields, &doc).unwrap();
     writer.commit().unwrap();
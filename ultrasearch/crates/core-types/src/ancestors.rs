@@ -0,0 +1,90 @@
+//! Ancestor chains derived from `FileMeta::parent` links.
+//!
+//! Indexes store every ancestor `DocKey` of a document so `in:<folder>` is an
+//! exact term lookup that keeps working when a folder is renamed.
+
+use std::collections::HashMap;
+
+use crate::{DocKey, FileMeta};
+
+/// Guard against corrupt parent links forming very long or cyclic chains.
+const MAX_DEPTH: usize = 256;
+
+/// Parent lookup table built from a batch of `FileMeta` records.
+#[derive(Debug, Default, Clone)]
+pub struct AncestorIndex {
+    parents: HashMap<DocKey, DocKey>,
+}
+
+impl AncestorIndex {
+    pub fn from_metas<'a>(metas: impl IntoIterator<Item = &'a FileMeta>) -> Self {
+        let parents = metas
+            .into_iter()
+            .filter_map(|m| m.parent.map(|p| (m.key, p)))
+            .collect();
+        Self { parents }
+    }
+
+    /// Ancestors of `key`, nearest first, starting from its `parent`.
+    ///
+    /// The walk stops at the volume root (which is its own parent on NTFS),
+    /// at a parent outside this batch, or on a cycle.
+    pub fn ancestors(&self, key: DocKey, parent: Option<DocKey>) -> Vec<DocKey> {
+        let mut out = Vec::new();
+        let mut current = parent;
+        while let Some(p) = current {
+            if p == key || out.contains(&p) || out.len() >= MAX_DEPTH {
+                break;
+            }
+            out.push(p);
+            current = self.parents.get(&p).copied();
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::FileFlags;
+
+    fn meta(frn: u64, parent: Option<u64>) -> FileMeta {
+        FileMeta::new(
+            DocKey::from_parts(1, frn),
+            1,
+            parent.map(|p| DocKey::from_parts(1, p)),
+            format!("f{frn}"),
+            None,
+            0,
+            0,
+            0,
+            FileFlags::empty(),
+        )
+    }
+
+    #[test]
+    fn walks_to_root_and_stops_on_cycles() {
+        // 5 is the root (its own parent), 10 and 11 are nested folders.
+        let metas = vec![
+            meta(5, Some(5)),
+            meta(10, Some(5)),
+            meta(11, Some(10)),
+            meta(12, Some(11)),
+            meta(20, Some(21)),
+            meta(21, Some(20)),
+        ];
+        let idx = AncestorIndex::from_metas(&metas);
+        let frns = |m: &FileMeta| -> Vec<u64> {
+            idx.ancestors(m.key, m.parent)
+                .into_iter()
+                .map(DocKey::file_id)
+                .collect()
+        };
+
+        assert_eq!(frns(&metas[3]), vec![11, 10, 5]);
+        assert!(frns(&metas[0]).is_empty());
+        assert_eq!(frns(&metas[4]), vec![21]);
+        // Parent outside the batch is still recorded.
+        assert_eq!(frns(&meta(30, Some(99))), vec![99]);
+    }
+}
@@ -1,4 +1,5 @@
 use std::{
+    collections::BTreeMap,
     fs,
     path::{Path, PathBuf},
 };
@@ -31,6 +32,12 @@ pub struct AppConfig {
     pub extract: ExtractSection,
     #[serde(default)]
     pub semantic: SemanticSection,
+    /// Extra or overriding `kind:` mappings: kind name -> extensions and/or
+    /// MIME types, e.g. `cad = ["dwg", "dxf"]`.
+    #[serde(default)]
+    pub kinds: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    pub search: SearchSection,
 }
 
 /// Load config, creating a default config file if none exists at the target path.
@@ -64,6 +71,8 @@ impl Default for AppConfig {
             paths: PathsSection::default(),
             extract: ExtractSection::default(),
             semantic: SemanticSection::default(),
+            kinds: BTreeMap::new(),
+            search: SearchSection::default(),
             volumes: Vec::new(),
             content_index_volumes: Vec::new(),
         }
@@ -217,6 +226,10 @@ pub struct FeaturesSection {
     pub mem_opt_tuning: bool,
     #[serde(default)]
     pub auto_tuning: bool,
+    /// Index name trigrams so `*port*` finds `report.docx` without a full
+    /// term scan. Applies when the metadata index is (re)created.
+    #[serde(default = "default_name_ngram_index")]
+    pub name_ngram_index: bool,
 }
 
 #[allow(clippy::derivable_impls)]
@@ -232,10 +245,15 @@ impl Default for FeaturesSection {
             log_dataset_mode: false,
             mem_opt_tuning: false,
             auto_tuning: false,
+            name_ngram_index: default_name_ngram_index(),
         }
     }
 }
 
+fn default_name_ngram_index() -> bool {
+    true
+}
+
 /// Scheduler thresholds (base values; may be tuned adaptively).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SchedulerSection {
@@ -399,6 +417,57 @@ fn default_semantic_index_dir() -> String {
     "{data_dir}/index/semantic".into()
 }
 
+/// How hybrid search combines metadata and content hits.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum HybridMerge {
+    /// Reciprocal rank fusion: each source adds `weight / (rrf_k + rank)`.
+    #[default]
+    Rrf,
+    /// Highest raw score of either index (the original strategy).
+    Max,
+}
+
+/// Query-time ranking and caching knobs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchSection {
+    #[serde(default)]
+    pub hybrid_merge: HybridMerge,
+    /// RRF damping constant; larger values flatten the rank curve.
+    #[serde(default = "default_rrf_k")]
+    pub rrf_k: u32,
+    #[serde(default = "default_source_weight")]
+    pub meta_weight: f32,
+    #[serde(default = "default_source_weight")]
+    pub content_weight: f32,
+    /// Responses kept for repeated searches until the next index commit;
+    /// 0 disables the cache.
+    #[serde(default = "default_result_cache_entries")]
+    pub result_cache_entries: usize,
+}
+
+impl Default for SearchSection {
+    fn default() -> Self {
+        Self {
+            hybrid_merge: HybridMerge::default(),
+            rrf_k: default_rrf_k(),
+            meta_weight: default_source_weight(),
+            content_weight: default_source_weight(),
+            result_cache_entries: default_result_cache_entries(),
+        }
+    }
+}
+
+fn default_rrf_k() -> u32 {
+    60
+}
+fn default_source_weight() -> f32 {
+    1.0
+}
+fn default_result_cache_entries() -> usize {
+    256
+}
+
 static CONFIG: Lazy<RwLock<AppConfig>> = Lazy::new(|| RwLock::new(AppConfig::default()));
 
 /// Get a clone of the currently loaded configuration.
@@ -432,6 +501,7 @@ pub fn reload_config(path: Option<&Path>) -> Result<AppConfig> {
         apply_placeholders(&mut file_cfg);
         file_cfg.validate()?;
 
+        crate::kind::set_overrides(&file_cfg.kinds);
         *lock = file_cfg.clone();
         Ok(file_cfg)
     } else {
@@ -454,6 +524,23 @@ impl AppConfig {
                 "Feature 'semantic_search' requires a valid model configuration"
             ));
         }
+        if let Some(kind) = self
+            .kinds
+            .keys()
+            .find(|k| k.trim().is_empty() || k.contains(char::is_whitespace))
+        {
+            return Err(anyhow::anyhow!("Invalid kind name {kind:?} in [kinds]"));
+        }
+        for (name, weight) in [
+            ("meta_weight", self.search.meta_weight),
+            ("content_weight", self.search.content_weight),
+        ] {
+            if !weight.is_finite() || weight < 0.0 {
+                return Err(anyhow::anyhow!(
+                    "[search] {name} must be a non-negative number, got {weight}"
+                ));
+            }
+        }
         Ok(())
     }
 }
@@ -582,6 +669,8 @@ mod tests {
         base.semantic = override_cfg.semantic;
         base.volumes = override_cfg.volumes;
         base.content_index_volumes = override_cfg.content_index_volumes;
+        base.kinds = override_cfg.kinds;
+        base.search = override_cfg.search;
         base
     }
 
@@ -656,4 +745,42 @@ mod tests {
         let cfg: AppConfig = toml::from_str(toml_str).unwrap();
         assert_eq!(cfg.extract.max_chars_per_file, 12_345);
     }
+
+    #[test]
+    fn kinds_section_parses_and_validates() {
+        let toml_str = r#"
+            [kinds]
+            cad = ["dwg", "dxf"]
+            code = ["text/x-zig"]
+        "#;
+        let mut cfg: AppConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(cfg.kinds["cad"], ["dwg", "dxf"]);
+        assert!(cfg.validate().is_ok());
+
+        cfg.kinds.insert("bad kind".into(), vec!["x".into()]);
+        assert!(cfg.validate().is_err());
+    }
+
+    #[test]
+    fn search_section_defaults_to_rrf() {
+        let cfg: AppConfig = toml::from_str("").unwrap();
+        assert_eq!(cfg.search.hybrid_merge, HybridMerge::Rrf);
+        assert_eq!(cfg.search.rrf_k, 60);
+
+        let mut cfg: AppConfig = toml::from_str(
+            r#"
+            [search]
+            hybrid_merge = "max"
+            content_weight = 0.5
+        "#,
+        )
+        .unwrap();
+        assert_eq!(cfg.search.hybrid_merge, HybridMerge::Max);
+        assert_eq!(cfg.search.meta_weight, 1.0);
+        assert_eq!(cfg.search.content_weight, 0.5);
+        assert!(cfg.validate().is_ok());
+
+        cfg.search.meta_weight = -1.0;
+        assert!(cfg.validate().is_err());
+    }
 }
@@ -0,0 +1,220 @@
+//! File-kind taxonomy (`kind:image`, `kind:code`, ...).
+//!
+//! Kinds are derived from the extension, falling back to the MIME type when
+//! one is known. The built-in table can be extended or overridden through the
+//! `[kinds]` config section, which maps a kind name to extensions and/or MIME
+//! types; custom kind names are allowed. Indexes store the kind as a plain
+//! lowercase string.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::RwLock;
+
+use once_cell::sync::Lazy;
+
+pub const DOCUMENT: &str = "document";
+pub const SPREADSHEET: &str = "spreadsheet";
+pub const PRESENTATION: &str = "presentation";
+pub const IMAGE: &str = "image";
+pub const VIDEO: &str = "video";
+pub const AUDIO: &str = "audio";
+pub const ARCHIVE: &str = "archive";
+pub const CODE: &str = "code";
+pub const TEXT: &str = "text";
+pub const EXECUTABLE: &str = "executable";
+pub const FONT: &str = "font";
+pub const FOLDER: &str = "folder";
+pub const OTHER: &str = "other";
+
+/// Kinds known without any configuration.
+pub const BUILTIN_KINDS: &[&str] = &[
+    DOCUMENT,
+    SPREADSHEET,
+    PRESENTATION,
+    IMAGE,
+    VIDEO,
+    AUDIO,
+    ARCHIVE,
+    CODE,
+    TEXT,
+    EXECUTABLE,
+    FONT,
+    FOLDER,
+    OTHER,
+];
+
+fn builtin_for_ext(ext: &str) -> Option<&'static str> {
+    Some(match ext {
+        "pdf" | "doc" | "docx" | "odt" | "rtf" | "pages" | "epub" | "tex" => DOCUMENT,
+        "xls" | "xlsx" | "xlsm" | "ods" | "csv" | "tsv" | "numbers" => SPREADSHEET,
+        "ppt" | "pptx" | "odp" | "key" => PRESENTATION,
+        "jpg" | "jpeg" | "png" | "gif" | "bmp" | "tif" | "tiff" | "webp" | "heic" | "svg"
+        | "ico" | "raw" | "cr2" | "nef" | "psd" => IMAGE,
+        "mp4" | "mkv" | "avi" | "mov" | "wmv" | "webm" | "m4v" | "mpg" | "mpeg" => VIDEO,
+        "mp3" | "wav" | "flac" | "aac" | "ogg" | "m4a" | "wma" | "opus" => AUDIO,
+        "zip" | "7z" | "rar" | "tar" | "gz" | "tgz" | "bz2" | "xz" | "zst" | "cab" | "iso" => {
+            ARCHIVE
+        }
+        "rs" | "c" | "h" | "cc" | "cpp" | "hpp" | "cs" | "java" | "kt" | "go" | "py" | "rb"
+        | "js" | "jsx" | "ts" | "tsx" | "php" | "swift" | "scala" | "sh" | "ps1" | "bat"
+        | "cmd" | "sql" | "html" | "css" | "scss" | "lua" | "pl" | "r" | "m" | "vb" | "fs" => CODE,
+        "txt" | "md" | "log" | "ini" | "cfg" | "conf" | "toml" | "yaml" | "yml" | "json"
+        | "xml" => TEXT,
+        "exe" | "dll" | "msi" | "sys" | "com" | "scr" | "appx" | "msix" => EXECUTABLE,
+        "ttf" | "otf" | "woff" | "woff2" | "fon" => FONT,
+        _ => return None,
+    })
+}
+
+fn builtin_for_mime(mime: &str) -> Option<&'static str> {
+    let (top, sub) = mime.split_once('/')?;
+    Some(match (top, sub) {
+        ("image", _) => IMAGE,
+        ("video", _) => VIDEO,
+        ("audio", _) => AUDIO,
+        ("font", _) => FONT,
+        ("application", "pdf" | "msword" | "rtf" | "epub+zip") => DOCUMENT,
+        ("application", s) if s.contains("wordprocessingml") || s.contains("opendocument.text") => {
+            DOCUMENT
+        }
+        ("application", s) if s.contains("spreadsheetml") || s.contains("ms-excel") => SPREADSHEET,
+        ("application", s) if s.contains("presentationml") || s.contains("ms-powerpoint") => {
+            PRESENTATION
+        }
+        ("application", "zip" | "gzip" | "x-7z-compressed" | "x-rar-compressed" | "x-tar") => {
+            ARCHIVE
+        }
+        ("text", "x-c" | "x-rust" | "x-python" | "x-java-source" | "javascript") => CODE,
+        ("text", _) => TEXT,
+        _ => return None,
+    })
+}
+
+/// Extension/MIME → kind classifier with optional overrides.
+#[derive(Debug, Clone, Default)]
+pub struct KindClassifier {
+    by_ext: HashMap<String, String>,
+    by_mime: HashMap<String, String>,
+}
+
+impl KindClassifier {
+    /// Build a classifier from `kind -> [extension or MIME type]` overrides.
+    ///
+    /// Entries containing `/` are MIME types; everything else is an extension
+    /// (a leading `.` is ignored). Matching is case-insensitive.
+    pub fn from_overrides(overrides: &BTreeMap<String, Vec<String>>) -> Self {
+        let mut out = Self::default();
+        for (kind, patterns) in overrides {
+            let kind = kind.trim().to_ascii_lowercase();
+            for pattern in patterns {
+                let pattern = pattern.trim().to_ascii_lowercase();
+                if pattern.contains('/') {
+                    out.by_mime.insert(pattern, kind.clone());
+                } else {
+                    out.by_ext
+                        .insert(pattern.trim_start_matches('.').to_string(), kind.clone());
+                }
+            }
+        }
+        out
+    }
+
+    /// Classify by extension first, then MIME type; unknown files are `other`.
+    pub fn classify(&self, ext: Option<&str>, mime: Option<&str>) -> &str {
+        let ext = ext.map(|e| e.trim_start_matches('.').to_ascii_lowercase());
+        let mime = mime.map(|m| {
+            m.split(';')
+                .next()
+                .unwrap_or_default()
+                .trim()
+                .to_ascii_lowercase()
+        });
+
+        if let Some(kind) = ext.as_deref().and_then(|e| self.by_ext.get(e)) {
+            return kind;
+        }
+        if let Some(kind) = ext.as_deref().and_then(builtin_for_ext) {
+            return kind;
+        }
+        if let Some(kind) = mime.as_deref().and_then(|m| self.by_mime.get(m)) {
+            return kind;
+        }
+        mime.as_deref().and_then(builtin_for_mime).unwrap_or(OTHER)
+    }
+
+    /// Whether `kind` is a built-in or configured kind name.
+    pub fn is_known(&self, kind: &str) -> bool {
+        let kind = kind.to_ascii_lowercase();
+        BUILTIN_KINDS.contains(&kind.as_str())
+            || self.by_ext.values().any(|k| *k == kind)
+            || self.by_mime.values().any(|k| *k == kind)
+    }
+}
+
+static CLASSIFIER: Lazy<RwLock<KindClassifier>> =
+    Lazy::new(|| RwLock::new(KindClassifier::default()));
+
+/// Install overrides for the process-wide classifier (done on config load).
+pub fn set_overrides(overrides: &BTreeMap<String, Vec<String>>) {
+    let classifier = KindClassifier::from_overrides(overrides);
+    *CLASSIFIER.write().expect("kind classifier lock poisoned") = classifier;
+}
+
+/// Classify with the process-wide classifier.
+pub fn classify(ext: Option<&str>, mime: Option<&str>) -> String {
+    CLASSIFIER
+        .read()
+        .expect("kind classifier lock poisoned")
+        .classify(ext, mime)
+        .to_string()
+}
+
+/// Whether `kind` is known to the process-wide classifier.
+pub fn is_known(kind: &str) -> bool {
+    CLASSIFIER
+        .read()
+        .expect("kind classifier lock poisoned")
+        .is_known(kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builtin_table_covers_common_types() {
+        let c = KindClassifier::default();
+        assert_eq!(c.classify(Some("PDF"), None), DOCUMENT);
+        assert_eq!(c.classify(Some(".jpg"), None), IMAGE);
+        assert_eq!(c.classify(Some("rs"), None), CODE);
+        assert_eq!(c.classify(Some("7z"), None), ARCHIVE);
+        assert_eq!(c.classify(Some("mkv"), None), VIDEO);
+        assert_eq!(c.classify(None, Some("image/heif")), IMAGE);
+        assert_eq!(
+            c.classify(Some("bin"), Some("application/pdf; charset=binary")),
+            DOCUMENT
+        );
+        assert_eq!(c.classify(Some("bin"), None), OTHER);
+        assert_eq!(c.classify(None, None), OTHER);
+    }
+
+    #[test]
+    fn overrides_win_and_add_kinds() {
+        let overrides = BTreeMap::from([
+            (
+                "cad".to_string(),
+                vec!["DWG".to_string(), ".dxf".to_string()],
+            ),
+            ("code".to_string(), vec!["txt".to_string()]),
+            ("model".to_string(), vec!["application/x-onnx".to_string()]),
+        ]);
+        let c = KindClassifier::from_overrides(&overrides);
+        assert_eq!(c.classify(Some("dwg"), None), "cad");
+        assert_eq!(c.classify(Some("dxf"), None), "cad");
+        assert_eq!(c.classify(Some("txt"), None), CODE);
+        assert_eq!(c.classify(None, Some("application/x-onnx")), "model");
+        assert_eq!(c.classify(Some("png"), None), IMAGE);
+        assert!(c.is_known("CAD"));
+        assert!(c.is_known("image"));
+        assert!(!c.is_known("spaceship"));
+    }
+}
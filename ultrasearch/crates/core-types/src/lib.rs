@@ -40,6 +40,28 @@ impl DocKey {
     }
 }
 
+/// An on-disk index was created with a schema this version cannot use; it
+/// has to be rebuilt from a rescan.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaMismatch {
+    /// Which index, e.g. `metadata` or `content`.
+    pub index: &'static str,
+    /// First field that is missing or has another type or id.
+    pub field: String,
+}
+
+impl core::fmt::Display for SchemaMismatch {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{} index schema is out of date (field `{}`); rebuild required",
+            self.index, self.field
+        )
+    }
+}
+
+impl std::error::Error for SchemaMismatch {}
+
 impl core::fmt::Display for DocKey {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         let (v, id) = self.into_parts();
@@ -140,12 +162,29 @@ pub struct VolumeDescriptor {
     pub drive_letters: Vec<String>,
 }
 
+pub mod ancestors;
 pub mod config;
+pub mod kind;
+pub mod query;
 
 impl FileFlags {
     pub fn is_dir(self) -> bool {
         self.contains(Self::IS_DIR)
     }
+
+    /// Flag named by an `is:` query value, e.g. `dir`, `hidden` or `system`.
+    pub fn from_query_name(name: &str) -> Option<Self> {
+        Some(match name.to_ascii_lowercase().as_str() {
+            "dir" | "directory" | "folder" => Self::IS_DIR,
+            "hidden" => Self::HIDDEN,
+            "system" => Self::SYSTEM,
+            "archive" => Self::ARCHIVE,
+            "reparse" | "link" | "symlink" => Self::REPARSE,
+            "offline" => Self::OFFLINE,
+            "temp" | "temporary" => Self::TEMPORARY,
+            _ => return None,
+        })
+    }
 }
 
 #[cfg(test)]
@@ -177,6 +216,20 @@ mod tests {
         assert_eq!(fm.ext.as_deref(), Some("pdf"));
     }
 
+    #[test]
+    fn file_flags_from_query_name() {
+        assert_eq!(FileFlags::from_query_name("dir"), Some(FileFlags::IS_DIR));
+        assert_eq!(
+            FileFlags::from_query_name("Hidden"),
+            Some(FileFlags::HIDDEN)
+        );
+        assert_eq!(
+            FileFlags::from_query_name("symlink"),
+            Some(FileFlags::REPARSE)
+        );
+        assert_eq!(FileFlags::from_query_name("bogus"), None);
+    }
+
     #[test]
     fn doc_key_display_is_stable() {
         let dk = DocKey::from_parts(7, 0xabc);
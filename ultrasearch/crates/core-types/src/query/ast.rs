@@ -0,0 +1,122 @@
+//! Query AST shared by the parser, the IPC protocol and the service planner.
+//!
+//! The `ipc` crate re-exports these types so they travel over the wire
+//! unchanged; keep them serde-friendly and free of index-specific details.
+
+use serde::{Deserialize, Serialize};
+
+/// Fields that can be targeted explicitly in the query language.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum FieldKind {
+    Name,
+    Path,
+    Ext,
+    Content,
+    Size,
+    Modified,
+    Created,
+    Flags,
+    Volume,
+    Kind,
+    /// Subtree filter (`in:<folder>`); the value is a folder path or a
+    /// `DocKey` in its display form (`3:0x...`).
+    Folder,
+}
+
+impl FieldKind {
+    /// Fields whose values are numeric and only make sense as ranges.
+    pub const fn is_range(self) -> bool {
+        matches!(
+            self,
+            FieldKind::Size | FieldKind::Modified | FieldKind::Created
+        )
+    }
+}
+
+/// How a term should be interpreted.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum TermModifier {
+    Term,
+    Phrase,
+    Prefix,
+    Fuzzy(u8), // max edit distance
+    /// Shell-style `*`/`?` wildcards matched against the whole file name
+    /// (or against individual tokens when targeting `path`).
+    Glob,
+    /// Case-insensitive regular expression searched within the file name;
+    /// anchor with `^`/`$` to match the whole name.
+    Regex,
+    /// Case-insensitive substring of the file name (`*port*` matches
+    /// `report.docx`), or of individual tokens for other text fields.
+    Substring,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TermExpr {
+    pub field: Option<FieldKind>, // None => default (name + content)
+    pub value: String,
+    pub modifier: TermModifier,
+}
+
+/// Comparison applied by a `RangeExpr`.
+///
+/// For `Gt`/`Ge`/`Lt`/`Le` the bound is carried in `lo`; `Between` is
+/// inclusive on both ends and uses `lo..=hi`.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum RangeOp {
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Between,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum RangeValue {
+    I64 { lo: i64, hi: Option<i64> }, // timestamps
+    U64 { lo: u64, hi: Option<u64> }, // sizes
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct RangeExpr {
+    pub field: FieldKind,
+    pub op: RangeOp,
+    pub value: RangeValue,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum QueryExpr {
+    Term(TermExpr),
+    Range(RangeExpr),
+    Not(Box<QueryExpr>),
+    And(Vec<QueryExpr>),
+    Or(Vec<QueryExpr>),
+}
+
+impl Default for QueryExpr {
+    fn default() -> Self {
+        QueryExpr::And(Vec::new())
+    }
+}
+
+impl QueryExpr {
+    /// Restrict to documents anywhere below `folder`.
+    pub fn in_folder(folder: impl Into<String>) -> Self {
+        QueryExpr::Term(TermExpr {
+            field: Some(FieldKind::Folder),
+            value: folder.into(),
+            modifier: TermModifier::Term,
+        })
+    }
+
+    /// AND `filter` onto this expression, flattening an existing conjunction.
+    pub fn and(self, filter: QueryExpr) -> Self {
+        match self {
+            QueryExpr::And(mut items) => {
+                items.push(filter);
+                QueryExpr::And(items)
+            }
+            other => QueryExpr::And(vec![other, filter]),
+        }
+    }
+}
@@ -0,0 +1,7 @@
+//! Query AST and the textual query language shared by the CLI and UI.
+
+pub mod ast;
+pub mod parser;
+
+pub use ast::{FieldKind, QueryExpr, RangeExpr, RangeOp, RangeValue, TermExpr, TermModifier};
+pub use parser::{ParseError, Span, parse_query};
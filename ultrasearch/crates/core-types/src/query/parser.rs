@@ -0,0 +1,1053 @@
+//! Textual query language parser producing a [`QueryExpr`].
+//!
+//! Informal grammar:
+//!
+//! ```text
+//! query   := or
+//! or      := and ("OR" and)*
+//! and     := unary ("AND"? unary)*
+//! unary   := ("-" | "NOT") unary | primary
+//! primary := "(" or ")" | field ":" value | "\"phrase\"" | word
+//! value   := "\"phrase\"" | word | range | "(" or ")"
+//! ```
+//!
+//! Words ending in `*` become prefix terms, other words containing `*` or `?`
+//! become globs (`*.rs`, `invoice-20??.pdf`) and `word~N` becomes a fuzzy term.
+//! `regex:<pattern>` searches file names with a regular expression; the
+//! pattern runs to the next whitespace (or unbalanced `)`) or may be quoted.
+//! `size:`, `modified:` and `created:` take ranges such as `>10mb`,
+//! `<=2024-01-01`, `1kb..2mb` or a bare value (exact size / whole day).
+//! Unknown `foo:` prefixes are treated as plain text so `C:\src` still works.
+
+use std::fmt;
+
+use super::ast::{FieldKind, QueryExpr, RangeExpr, RangeOp, RangeValue, TermExpr, TermModifier};
+
+/// Edit distance used for `word~` without an explicit number.
+pub const DEFAULT_FUZZY_DISTANCE: u8 = 2;
+
+/// Byte range into the original query string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    const fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+}
+
+/// Parse failure with the offending span so clients can underline it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub message: String,
+    pub span: Span,
+}
+
+impl ParseError {
+    fn new(message: impl Into<String>, span: Span) -> Self {
+        Self {
+            message: message.into(),
+            span,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at {}..{}",
+            self.message, self.span.start, self.span.end
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Parse a textual query into a `QueryExpr`.
+///
+/// Blank input yields the empty conjunction (`QueryExpr::default()`).
+pub fn parse_query(input: &str) -> Result<QueryExpr, ParseError> {
+    let tokens = tokenize(input)?;
+    if tokens.is_empty() {
+        return Ok(QueryExpr::default());
+    }
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        end: input.len(),
+    };
+    let expr = parser.parse_or(None)?;
+    if let Some(tok) = parser.peek() {
+        // Only a stray ')' can stop the top-level disjunction early.
+        return Err(ParseError::new("unexpected ')'", tok.span));
+    }
+    Ok(expr)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Tok {
+    LParen,
+    RParen,
+    Not,
+    And,
+    Or,
+    Field(FieldKind),
+    Word(String),
+    Phrase(String),
+    Regex(String),
+}
+
+#[derive(Debug, Clone)]
+struct Token {
+    tok: Tok,
+    span: Span,
+}
+
+fn field_for(name: &str) -> Option<FieldKind> {
+    Some(match name.to_ascii_lowercase().as_str() {
+        "name" => FieldKind::Name,
+        "path" => FieldKind::Path,
+        "ext" => FieldKind::Ext,
+        "content" => FieldKind::Content,
+        "size" => FieldKind::Size,
+        "modified" => FieldKind::Modified,
+        "created" => FieldKind::Created,
+        "is" => FieldKind::Flags,
+        "vol" | "volume" => FieldKind::Volume,
+        "kind" => FieldKind::Kind,
+        "in" => FieldKind::Folder,
+        _ => return None,
+    })
+}
+
+const fn is_word_break(c: char) -> bool {
+    c.is_whitespace() || matches!(c, '(' | ')' | '"')
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, ParseError> {
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while let Some(c) = input[i..].chars().next() {
+        if c.is_whitespace() {
+            i += c.len_utf8();
+            continue;
+        }
+
+        match c {
+            '(' | ')' => {
+                let tok = if c == '(' { Tok::LParen } else { Tok::RParen };
+                tokens.push(Token {
+                    tok,
+                    span: Span::new(i, i + 1),
+                });
+                i += 1;
+            }
+            '"' => {
+                let (text, end) = lex_phrase(input, i)?;
+                tokens.push(Token {
+                    tok: Tok::Phrase(text),
+                    span: Span::new(i, end),
+                });
+                i = end;
+            }
+            '-' if input[i + 1..]
+                .chars()
+                .next()
+                .is_some_and(|n| !n.is_whitespace() && n != ')') =>
+            {
+                tokens.push(Token {
+                    tok: Tok::Not,
+                    span: Span::new(i, i + 1),
+                });
+                i += 1;
+            }
+            _ if input[i..]
+                .get(..REGEX_PREFIX.len())
+                .is_some_and(|p| p.eq_ignore_ascii_case(REGEX_PREFIX)) =>
+            {
+                let start = i;
+                let body = start + REGEX_PREFIX.len();
+                let (pattern, end) = if input[body..].starts_with('"') {
+                    lex_phrase(input, body)?
+                } else {
+                    let end = regex_end(input, body);
+                    (input[body..end].to_string(), end)
+                };
+                if pattern.is_empty() {
+                    return Err(ParseError::new(
+                        "regex: needs a pattern",
+                        Span::new(start, end),
+                    ));
+                }
+                tokens.push(Token {
+                    tok: Tok::Regex(pattern),
+                    span: Span::new(start, end),
+                });
+                i = end;
+            }
+            _ => {
+                let start = i;
+                let end = input[start..]
+                    .find(is_word_break)
+                    .map_or(input.len(), |off| start + off);
+                let word = &input[start..end];
+                i = end;
+
+                if let Some((head, rest)) = word.split_once(':')
+                    && let Some(field) = field_for(head)
+                {
+                    let colon = start + head.len() + 1;
+                    tokens.push(Token {
+                        tok: Tok::Field(field),
+                        span: Span::new(start, colon),
+                    });
+                    if !rest.is_empty() {
+                        tokens.push(Token {
+                            tok: Tok::Word(rest.to_string()),
+                            span: Span::new(colon, end),
+                        });
+                    }
+                    continue;
+                }
+
+                let tok = match word {
+                    "OR" | "||" => Tok::Or,
+                    "AND" | "&&" => Tok::And,
+                    "NOT" => Tok::Not,
+                    _ => Tok::Word(word.to_string()),
+                };
+                tokens.push(Token {
+                    tok,
+                    span: Span::new(start, end),
+                });
+            }
+        }
+    }
+
+    Ok(tokens)
+}
+
+const REGEX_PREFIX: &str = "regex:";
+
+/// End of an unquoted regex: the next whitespace or a `)` that closes a group
+/// opened outside the pattern. Backslash escapes are skipped.
+fn regex_end(input: &str, start: usize) -> usize {
+    let mut depth = 0usize;
+    let mut chars = input[start..].char_indices();
+    while let Some((off, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '(' => depth += 1,
+            ')' if depth == 0 => return start + off,
+            ')' => depth -= 1,
+            c if c.is_whitespace() => return start + off,
+            _ => {}
+        }
+    }
+    input.len()
+}
+
+/// Lex a double-quoted phrase starting at `start`; `\"` escapes a quote.
+fn lex_phrase(input: &str, start: usize) -> Result<(String, usize), ParseError> {
+    let mut text = String::new();
+    let mut chars = input[start + 1..].char_indices();
+    while let Some((off, c)) = chars.next() {
+        match c {
+            '"' => return Ok((text, start + 1 + off + 1)),
+            '\\' => match chars.next() {
+                Some((_, '"')) => text.push('"'),
+                Some((_, other)) => {
+                    text.push('\\');
+                    text.push(other);
+                }
+                None => text.push('\\'),
+            },
+            _ => text.push(c),
+        }
+    }
+    Err(ParseError::new(
+        "unterminated quote",
+        Span::new(start, input.len()),
+    ))
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn bump(&mut self) -> Option<Token> {
+        let tok = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        tok
+    }
+
+    /// Span of the current token, or an empty span at end of input.
+    fn here(&self) -> Span {
+        self.peek()
+            .map_or(Span::new(self.end, self.end), |t| t.span)
+    }
+
+    fn at_operand(&self) -> bool {
+        !matches!(
+            self.peek().map(|t| &t.tok),
+            None | Some(Tok::Or | Tok::And | Tok::RParen)
+        )
+    }
+
+    fn parse_or(&mut self, field: Option<FieldKind>) -> Result<QueryExpr, ParseError> {
+        let mut branches = vec![self.parse_and(field)?];
+        while matches!(self.peek().map(|t| &t.tok), Some(Tok::Or)) {
+            self.bump();
+            branches.push(self.parse_and(field)?);
+        }
+        Ok(collapse(branches, QueryExpr::Or))
+    }
+
+    fn parse_and(&mut self, field: Option<FieldKind>) -> Result<QueryExpr, ParseError> {
+        let mut items = Vec::new();
+        loop {
+            match self.peek().map(|t| &t.tok) {
+                None | Some(Tok::Or | Tok::RParen) => break,
+                Some(Tok::And) => {
+                    let span = self.here();
+                    if items.is_empty() {
+                        return Err(ParseError::new("AND needs a term on its left", span));
+                    }
+                    self.bump();
+                    if !self.at_operand() {
+                        return Err(ParseError::new("AND needs a term on its right", span));
+                    }
+                }
+                _ => items.push(self.parse_unary(field)?),
+            }
+        }
+        if items.is_empty() {
+            return Err(ParseError::new("expected a search term", self.here()));
+        }
+        Ok(collapse(items, QueryExpr::And))
+    }
+
+    fn parse_unary(&mut self, field: Option<FieldKind>) -> Result<QueryExpr, ParseError> {
+        if matches!(self.peek().map(|t| &t.tok), Some(Tok::Not)) {
+            let span = self.here();
+            self.bump();
+            if !self.at_operand() {
+                return Err(ParseError::new("negation needs a term", span));
+            }
+            return Ok(QueryExpr::Not(Box::new(self.parse_unary(field)?)));
+        }
+        self.parse_primary(field)
+    }
+
+    fn parse_primary(&mut self, field: Option<FieldKind>) -> Result<QueryExpr, ParseError> {
+        let Some(token) = self.bump() else {
+            return Err(ParseError::new("expected a search term", self.here()));
+        };
+        match token.tok {
+            Tok::LParen => self.parse_group(field, token.span),
+            Tok::Field(f) => self.parse_field_value(f, token.span),
+            Tok::Word(w) => word_expr(&w, token.span, field),
+            Tok::Phrase(p) => phrase_expr(p, token.span, field),
+            Tok::Regex(p) => Ok(term(Some(FieldKind::Name), p, TermModifier::Regex)),
+            Tok::RParen => Err(ParseError::new("unexpected ')'", token.span)),
+            Tok::Not | Tok::And | Tok::Or => {
+                Err(ParseError::new("expected a search term", token.span))
+            }
+        }
+    }
+
+    fn parse_group(
+        &mut self,
+        field: Option<FieldKind>,
+        open: Span,
+    ) -> Result<QueryExpr, ParseError> {
+        let inner = self.parse_or(field)?;
+        match self.bump() {
+            Some(Token {
+                tok: Tok::RParen, ..
+            }) => Ok(inner),
+            _ => Err(ParseError::new("unclosed '('", open)),
+        }
+    }
+
+    fn parse_field_value(&mut self, field: FieldKind, span: Span) -> Result<QueryExpr, ParseError> {
+        let missing = || ParseError::new("missing value after field", span);
+        // The value must follow the colon directly (`ext:pdf`, `name:"a b"`).
+        let Some(next) = self.peek().filter(|t| t.span.start == span.end) else {
+            return Err(missing());
+        };
+        let next_span = next.span;
+        match next.tok.clone() {
+            Tok::Word(w) => {
+                self.bump();
+                word_expr(&w, next_span, Some(field))
+            }
+            Tok::Phrase(p) => {
+                self.bump();
+                phrase_expr(p, next_span, Some(field))
+            }
+            Tok::LParen => {
+                self.bump();
+                self.parse_group(Some(field), next_span)
+            }
+            _ => Err(missing()),
+        }
+    }
+}
+
+fn collapse(mut items: Vec<QueryExpr>, ctor: fn(Vec<QueryExpr>) -> QueryExpr) -> QueryExpr {
+    if items.len() == 1 {
+        items.pop().expect("one item")
+    } else {
+        ctor(items)
+    }
+}
+
+fn term(field: Option<FieldKind>, value: String, modifier: TermModifier) -> QueryExpr {
+    let value = match field {
+        Some(FieldKind::Ext) => value.trim_start_matches('.').to_ascii_lowercase(),
+        _ => value,
+    };
+    QueryExpr::Term(TermExpr {
+        field,
+        value,
+        modifier,
+    })
+}
+
+fn word_expr(word: &str, span: Span, field: Option<FieldKind>) -> Result<QueryExpr, ParseError> {
+    if let Some(f) = field.filter(|f| f.is_range()) {
+        return parse_range(f, word, span);
+    }
+    // Folder paths are taken literally (`~` and `*` are valid in paths).
+    if field == Some(FieldKind::Folder) {
+        return Ok(term(field, word.to_string(), TermModifier::Term));
+    }
+
+    if let Some(inner) = word.strip_prefix('*').and_then(|w| w.strip_suffix('*'))
+        && !inner.is_empty()
+        && !inner.contains(['*', '?'])
+    {
+        return Ok(term(field, inner.to_string(), TermModifier::Substring));
+    }
+
+    if let Some(stem) = word.strip_suffix('*') {
+        if stem.is_empty() {
+            return Err(ParseError::new(
+                "wildcard needs at least one character",
+                span,
+            ));
+        }
+        if !stem.contains(['*', '?']) {
+            return Ok(term(field, stem.to_string(), TermModifier::Prefix));
+        }
+    }
+
+    if word.contains(['*', '?']) {
+        return Ok(term(field, word.to_string(), TermModifier::Glob));
+    }
+
+    if let Some((stem, dist)) = word.rsplit_once('~')
+        && !stem.is_empty()
+        && dist.chars().all(|c| c.is_ascii_digit())
+    {
+        let distance = if dist.is_empty() {
+            DEFAULT_FUZZY_DISTANCE
+        } else {
+            dist.parse()
+                .map_err(|_| ParseError::new("edit distance out of range", span))?
+        };
+        return Ok(term(field, stem.to_string(), TermModifier::Fuzzy(distance)));
+    }
+
+    Ok(term(field, word.to_string(), TermModifier::Term))
+}
+
+fn phrase_expr(
+    phrase: String,
+    span: Span,
+    field: Option<FieldKind>,
+) -> Result<QueryExpr, ParseError> {
+    if field.is_some_and(FieldKind::is_range) {
+        return Err(ParseError::new(
+            "range fields take a value like >10mb or 2024-01-01",
+            span,
+        ));
+    }
+    if phrase.trim().is_empty() {
+        return Err(ParseError::new("empty phrase", span));
+    }
+    Ok(term(field, phrase, TermModifier::Phrase))
+}
+
+#[derive(Clone, Copy)]
+enum Cmp {
+    Gt,
+    Ge,
+    Lt,
+    Le,
+    Eq,
+}
+
+fn parse_range(field: FieldKind, text: &str, span: Span) -> Result<QueryExpr, ParseError> {
+    let (cmp, rest) = if let Some(r) = text.strip_prefix(">=") {
+        (Cmp::Ge, r)
+    } else if let Some(r) = text.strip_prefix("<=") {
+        (Cmp::Le, r)
+    } else if let Some(r) = text.strip_prefix('>') {
+        (Cmp::Gt, r)
+    } else if let Some(r) = text.strip_prefix('<') {
+        (Cmp::Lt, r)
+    } else if let Some(r) = text.strip_prefix('=') {
+        (Cmp::Eq, r)
+    } else {
+        (Cmp::Eq, text)
+    };
+
+    let value = if matches!(cmp, Cmp::Eq)
+        && let Some((lo, hi)) = rest.split_once("..")
+    {
+        between(field, lo, hi, span)?
+    } else {
+        compare(field, cmp, rest, span)?
+    };
+
+    let op = match (&value, cmp) {
+        (_, Cmp::Gt) => RangeOp::Gt,
+        (_, Cmp::Ge) => RangeOp::Ge,
+        (_, Cmp::Lt) => RangeOp::Lt,
+        (_, Cmp::Le) => RangeOp::Le,
+        (RangeValue::U64 { hi: None, .. } | RangeValue::I64 { hi: None, .. }, Cmp::Eq) => {
+            // Open-ended `a..` / `..b` collapse to a single bound.
+            if rest.starts_with("..") {
+                RangeOp::Le
+            } else {
+                RangeOp::Ge
+            }
+        }
+        (_, Cmp::Eq) => RangeOp::Between,
+    };
+
+    Ok(QueryExpr::Range(RangeExpr { field, op, value }))
+}
+
+fn compare(field: FieldKind, cmp: Cmp, text: &str, span: Span) -> Result<RangeValue, ParseError> {
+    if field == FieldKind::Size {
+        let n = parse_size(text).ok_or_else(|| bad_size(span))?;
+        return Ok(match cmp {
+            Cmp::Eq => RangeValue::U64 { lo: n, hi: Some(n) },
+            _ => RangeValue::U64 { lo: n, hi: None },
+        });
+    }
+
+    let (start, end) = parse_date(text).ok_or_else(|| bad_date(span))?;
+    Ok(match cmp {
+        // "after that day" / "up to and including that day" use the day's last second.
+        Cmp::Gt | Cmp::Le => RangeValue::I64 { lo: end, hi: None },
+        Cmp::Ge | Cmp::Lt => RangeValue::I64 {
+            lo: start,
+            hi: None,
+        },
+        Cmp::Eq => RangeValue::I64 {
+            lo: start,
+            hi: Some(end),
+        },
+    })
+}
+
+fn between(field: FieldKind, lo: &str, hi: &str, span: Span) -> Result<RangeValue, ParseError> {
+    if lo.is_empty() && hi.is_empty() {
+        return Err(ParseError::new("range needs at least one bound", span));
+    }
+    if lo.is_empty() {
+        return compare(field, Cmp::Le, hi, span);
+    }
+    if hi.is_empty() {
+        return compare(field, Cmp::Ge, lo, span);
+    }
+
+    if field == FieldKind::Size {
+        let lo = parse_size(lo).ok_or_else(|| bad_size(span))?;
+        let hi = parse_size(hi).ok_or_else(|| bad_size(span))?;
+        if lo > hi {
+            return Err(ParseError::new("range start is after its end", span));
+        }
+        return Ok(RangeValue::U64 { lo, hi: Some(hi) });
+    }
+
+    let (lo, _) = parse_date(lo).ok_or_else(|| bad_date(span))?;
+    let (_, hi) = parse_date(hi).ok_or_else(|| bad_date(span))?;
+    if lo > hi {
+        return Err(ParseError::new("range start is after its end", span));
+    }
+    Ok(RangeValue::I64 { lo, hi: Some(hi) })
+}
+
+fn bad_size(span: Span) -> ParseError {
+    ParseError::new("invalid size (expected e.g. 500kb, 10mb, 1.5gb)", span)
+}
+
+fn bad_date(span: Span) -> ParseError {
+    ParseError::new(
+        "invalid date (expected YYYY, YYYY-MM, YYYY-MM-DD or YYYY-MM-DDTHH:MM[:SS])",
+        span,
+    )
+}
+
+/// Parse `10`, `500kb`, `1.5GB` … into bytes (binary multiples).
+fn parse_size(text: &str) -> Option<u64> {
+    let split = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(text.len());
+    let (num, unit) = text.split_at(split);
+    if num.is_empty() {
+        return None;
+    }
+    let mult: u64 = match unit.to_ascii_lowercase().as_str() {
+        "" | "b" => 1,
+        "k" | "kb" | "kib" => 1 << 10,
+        "m" | "mb" | "mib" => 1 << 20,
+        "g" | "gb" | "gib" => 1 << 30,
+        "t" | "tb" | "tib" => 1 << 40,
+        _ => return None,
+    };
+    if num.contains('.') {
+        let value: f64 = num.parse().ok()?;
+        let bytes = (value * mult as f64).round();
+        (bytes.is_finite() && bytes >= 0.0 && bytes <= u64::MAX as f64).then_some(bytes as u64)
+    } else {
+        num.parse::<u64>().ok()?.checked_mul(mult)
+    }
+}
+
+/// Parse a UTC date/time into the inclusive `(first_second, last_second)` it covers.
+fn parse_date(text: &str) -> Option<(i64, i64)> {
+    let (date, time) = match text.split_once(['T', 't']) {
+        Some((d, t)) => (d, Some(t)),
+        None => (text, None),
+    };
+
+    let mut parts = date.split('-');
+    let year: i64 = parse_fixed(parts.next()?, 4)?;
+    let month: Option<u32> = match parts.next() {
+        Some(m) => Some(parse_fixed(m, 2)?),
+        None => None,
+    };
+    let day: Option<u32> = match parts.next() {
+        Some(d) => Some(parse_fixed(d, 2)?),
+        None => None,
+    };
+    if parts.next().is_some() {
+        return None;
+    }
+
+    match (month, day, time) {
+        (None, None, None) => {
+            let start = days_from_civil(year, 1, 1) * 86_400;
+            let end = days_from_civil(year + 1, 1, 1) * 86_400 - 1;
+            Some((start, end))
+        }
+        (Some(m), None, None) => {
+            if !(1..=12).contains(&m) {
+                return None;
+            }
+            let (ny, nm) = if m == 12 {
+                (year + 1, 1)
+            } else {
+                (year, m + 1)
+            };
+            let start = days_from_civil(year, m, 1) * 86_400;
+            let end = days_from_civil(ny, nm, 1) * 86_400 - 1;
+            Some((start, end))
+        }
+        (Some(m), Some(d), time) => {
+            if !(1..=12).contains(&m) || d == 0 || d > days_in_month(year, m) {
+                return None;
+            }
+            let day_start = days_from_civil(year, m, d) * 86_400;
+            match time {
+                None => Some((day_start, day_start + 86_399)),
+                Some(t) => {
+                    let mut hms = t.split(':');
+                    let h: i64 = parse_fixed(hms.next()?, 2)?;
+                    let min: i64 = parse_fixed(hms.next()?, 2)?;
+                    let sec: Option<i64> = match hms.next() {
+                        Some(s) => Some(parse_fixed(s, 2)?),
+                        None => None,
+                    };
+                    if hms.next().is_some() || h > 23 || min > 59 || sec.unwrap_or(0) > 59 {
+                        return None;
+                    }
+                    let at = day_start + h * 3_600 + min * 60;
+                    Some(match sec {
+                        Some(s) => (at + s, at + s),
+                        None => (at, at + 59),
+                    })
+                }
+            }
+        }
+        _ => None,
+    }
+}
+
+fn parse_fixed<T: std::str::FromStr>(text: &str, width: usize) -> Option<T> {
+    (text.len() == width && text.chars().all(|c| c.is_ascii_digit()))
+        .then(|| text.parse().ok())
+        .flatten()
+}
+
+const fn is_leap(year: i64) -> bool {
+    (year % 4 == 0 && year % 100 != 0) || year % 400 == 0
+}
+
+const fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if is_leap(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Days since 1970-01-01 for a proleptic Gregorian date (Hinnant's algorithm).
+const fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let y = if month <= 2 { year - 1 } else { year };
+    let era = if y >= 0 { y } else { y - 399 } / 400;
+    let yoe = y - era * 400;
+    let m = month as i64;
+    let doy = (153 * (if m > 2 { m - 3 } else { m + 9 }) + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn t(field: Option<FieldKind>, value: &str, modifier: TermModifier) -> QueryExpr {
+        QueryExpr::Term(TermExpr {
+            field,
+            value: value.into(),
+            modifier,
+        })
+    }
+
+    fn range(field: FieldKind, op: RangeOp, value: RangeValue) -> QueryExpr {
+        QueryExpr::Range(RangeExpr { field, op, value })
+    }
+
+    #[test]
+    fn blank_input_is_empty_conjunction() {
+        assert_eq!(parse_query("   ").unwrap(), QueryExpr::default());
+    }
+
+    #[test]
+    fn single_word_is_default_field_term() {
+        assert_eq!(
+            parse_query("report").unwrap(),
+            t(None, "report", TermModifier::Term)
+        );
+    }
+
+    #[test]
+    fn parses_full_example() {
+        let q = parse_query(
+            r#"ext:pdf "quarterly report" -draft (name:foo OR path:bar) size:>10mb modified:>=2024-01-01 foo* fuzz~2"#,
+        )
+        .unwrap();
+        let expected = QueryExpr::And(vec![
+            t(Some(FieldKind::Ext), "pdf", TermModifier::Term),
+            t(None, "quarterly report", TermModifier::Phrase),
+            QueryExpr::Not(Box::new(t(None, "draft", TermModifier::Term))),
+            QueryExpr::Or(vec![
+                t(Some(FieldKind::Name), "foo", TermModifier::Term),
+                t(Some(FieldKind::Path), "bar", TermModifier::Term),
+            ]),
+            range(
+                FieldKind::Size,
+                RangeOp::Gt,
+                RangeValue::U64 {
+                    lo: 10 * 1024 * 1024,
+                    hi: None,
+                },
+            ),
+            range(
+                FieldKind::Modified,
+                RangeOp::Ge,
+                RangeValue::I64 {
+                    lo: 1_704_067_200,
+                    hi: None,
+                },
+            ),
+            t(None, "foo", TermModifier::Prefix),
+            t(None, "fuzz", TermModifier::Fuzzy(2)),
+        ]);
+        assert_eq!(q, expected);
+    }
+
+    #[test]
+    fn or_binds_looser_than_and() {
+        let q = parse_query("a b OR c").unwrap();
+        assert_eq!(
+            q,
+            QueryExpr::Or(vec![
+                QueryExpr::And(vec![
+                    t(None, "a", TermModifier::Term),
+                    t(None, "b", TermModifier::Term),
+                ]),
+                t(None, "c", TermModifier::Term),
+            ])
+        );
+        assert_eq!(parse_query("a AND b").unwrap(), parse_query("a b").unwrap());
+    }
+
+    #[test]
+    fn field_applies_to_group_and_phrase() {
+        let q = parse_query(r#"ext:(PDF OR .docx) name:"annual plan""#).unwrap();
+        assert_eq!(
+            q,
+            QueryExpr::And(vec![
+                QueryExpr::Or(vec![
+                    t(Some(FieldKind::Ext), "pdf", TermModifier::Term),
+                    t(Some(FieldKind::Ext), "docx", TermModifier::Term),
+                ]),
+                t(Some(FieldKind::Name), "annual plan", TermModifier::Phrase),
+            ])
+        );
+    }
+
+    #[test]
+    fn unknown_prefixes_and_paths_stay_plain_words() {
+        assert_eq!(
+            parse_query(r"C:\src\project").unwrap(),
+            t(None, r"C:\src\project", TermModifier::Term)
+        );
+        assert_eq!(
+            parse_query("foo:bar").unwrap(),
+            t(None, "foo:bar", TermModifier::Term)
+        );
+    }
+
+    #[test]
+    fn size_ranges_and_units() {
+        assert_eq!(
+            parse_query("size:1.5kb..2mb").unwrap(),
+            range(
+                FieldKind::Size,
+                RangeOp::Between,
+                RangeValue::U64 {
+                    lo: 1536,
+                    hi: Some(2 * 1024 * 1024),
+                },
+            )
+        );
+        assert_eq!(
+            parse_query("size:100").unwrap(),
+            range(
+                FieldKind::Size,
+                RangeOp::Between,
+                RangeValue::U64 {
+                    lo: 100,
+                    hi: Some(100),
+                },
+            )
+        );
+        assert_eq!(
+            parse_query("size:1g..").unwrap(),
+            range(
+                FieldKind::Size,
+                RangeOp::Ge,
+                RangeValue::U64 {
+                    lo: 1 << 30,
+                    hi: None,
+                },
+            )
+        );
+        assert_eq!(
+            parse_query("size:<=4k").unwrap(),
+            range(
+                FieldKind::Size,
+                RangeOp::Le,
+                RangeValue::U64 { lo: 4096, hi: None },
+            )
+        );
+    }
+
+    #[test]
+    fn date_ranges_cover_whole_units() {
+        let day = 1_704_067_200; // 2024-01-01T00:00:00Z
+        assert_eq!(
+            parse_query("modified:2024-01-01").unwrap(),
+            range(
+                FieldKind::Modified,
+                RangeOp::Between,
+                RangeValue::I64 {
+                    lo: day,
+                    hi: Some(day + 86_399),
+                },
+            )
+        );
+        assert_eq!(
+            parse_query("created:>2024-01-01").unwrap(),
+            range(
+                FieldKind::Created,
+                RangeOp::Gt,
+                RangeValue::I64 {
+                    lo: day + 86_399,
+                    hi: None,
+                },
+            )
+        );
+        assert_eq!(
+            parse_query("modified:2024-02").unwrap(),
+            range(
+                FieldKind::Modified,
+                RangeOp::Between,
+                RangeValue::I64 {
+                    lo: day + 31 * 86_400,
+                    hi: Some(day + 60 * 86_400 - 1),
+                },
+            )
+        );
+        assert_eq!(
+            parse_query("modified:<2024-01-01T12:30").unwrap(),
+            range(
+                FieldKind::Modified,
+                RangeOp::Lt,
+                RangeValue::I64 {
+                    lo: day + 12 * 3_600 + 30 * 60,
+                    hi: None,
+                },
+            )
+        );
+    }
+
+    #[test]
+    fn errors_carry_spans() {
+        let err = parse_query("(foo bar").unwrap_err();
+        assert_eq!(err.span, Span::new(0, 1));
+
+        let err = parse_query("foo ext: bar").unwrap_err();
+        assert_eq!(err.span, Span::new(4, 8));
+
+        let err = parse_query("size:>10zb").unwrap_err();
+        assert_eq!(err.span, Span::new(5, 10));
+
+        let err = parse_query(r#"name:"open"#).unwrap_err();
+        assert_eq!(err.span, Span::new(5, 10));
+
+        let err = parse_query("modified:2024-02-30").unwrap_err();
+        assert!(err.message.contains("invalid date"));
+
+        assert!(parse_query("foo OR").is_err());
+        assert!(parse_query("foo )").is_err());
+        assert!(parse_query("*").is_err());
+    }
+
+    #[test]
+    fn wildcards_become_prefix_substring_or_glob() {
+        assert_eq!(
+            parse_query("repo*").unwrap(),
+            t(None, "repo", TermModifier::Prefix)
+        );
+        assert_eq!(
+            parse_query("*.rs").unwrap(),
+            t(None, "*.rs", TermModifier::Glob)
+        );
+        assert_eq!(
+            parse_query("name:invoice-20??.pdf").unwrap(),
+            t(
+                Some(FieldKind::Name),
+                "invoice-20??.pdf",
+                TermModifier::Glob
+            )
+        );
+        assert_eq!(
+            parse_query("ext:J?G").unwrap(),
+            t(Some(FieldKind::Ext), "j?g", TermModifier::Glob)
+        );
+        assert_eq!(
+            parse_query("*port*").unwrap(),
+            t(None, "port", TermModifier::Substring)
+        );
+        assert_eq!(
+            parse_query("name:*2024-q*").unwrap(),
+            t(Some(FieldKind::Name), "2024-q", TermModifier::Substring)
+        );
+        assert_eq!(
+            parse_query("*a*b*").unwrap(),
+            t(None, "*a*b*", TermModifier::Glob)
+        );
+    }
+
+    #[test]
+    fn regex_patterns_keep_their_syntax() {
+        let re = |p: &str| t(Some(FieldKind::Name), p, TermModifier::Regex);
+        assert_eq!(
+            parse_query(r"regex:^IMG_\d{4}\.jpe?g$").unwrap(),
+            re(r"^IMG_\d{4}\.jpe?g$")
+        );
+        assert_eq!(
+            parse_query(r"(regex:^(a|b)\.txt ext:txt)").unwrap(),
+            QueryExpr::And(vec![
+                re(r"^(a|b)\.txt"),
+                t(Some(FieldKind::Ext), "txt", TermModifier::Term),
+            ])
+        );
+        assert_eq!(
+            parse_query(r#"-regex:"draft (v\d+)""#).unwrap(),
+            QueryExpr::Not(Box::new(re(r"draft (v\d+)")))
+        );
+        assert!(parse_query("regex: foo").is_err());
+    }
+
+    #[test]
+    fn in_folder_takes_paths_literally() {
+        assert_eq!(
+            parse_query(r"in:C:\Users\PROGRA~1 report").unwrap(),
+            QueryExpr::And(vec![
+                QueryExpr::in_folder(r"C:\Users\PROGRA~1"),
+                t(None, "report", TermModifier::Term),
+            ])
+        );
+        assert_eq!(
+            parse_query(r#"in:"D:\My Docs""#).unwrap(),
+            t(Some(FieldKind::Folder), r"D:\My Docs", TermModifier::Phrase)
+        );
+        assert_eq!(
+            parse_query("report")
+                .unwrap()
+                .and(QueryExpr::in_folder("3:0x5")),
+            QueryExpr::And(vec![
+                t(None, "report", TermModifier::Term),
+                QueryExpr::in_folder("3:0x5"),
+            ])
+        );
+    }
+
+    #[test]
+    fn negation_forms() {
+        let expected = QueryExpr::Not(Box::new(t(Some(FieldKind::Ext), "tmp", TermModifier::Term)));
+        assert_eq!(parse_query("-ext:tmp").unwrap(), expected);
+        assert_eq!(parse_query("NOT ext:tmp").unwrap(), expected);
+        // A dash inside a word is not negation.
+        assert_eq!(
+            parse_query("foo-bar").unwrap(),
+            t(None, "foo-bar", TermModifier::Term)
+        );
+    }
+}
@@ -10,8 +10,9 @@
 use anyhow::{Context, Result};
 use clap::Parser;
 use content_extractor::{ExtractContext, ExtractorStack};
-use content_index::{ContentIndex, IndexWriter, WriterConfig};
+use content_index::{ContentIndex, IndexWriter, TextKind, WriterConfig};
 use core_types::DocKey;
+use core_types::config::FeaturesSection;
 use dotenvy::dotenv;
 use serde::{Deserialize, Serialize};
 use std::path::PathBuf;
@@ -70,6 +71,9 @@ struct JobSpec {
     max_chars: Option<usize>,
     #[serde(default)]
     file_size: u64,
+    /// Packed `DocKey`s of the ancestor folders, nearest first.
+    #[serde(default)]
+    ancestors: Vec<u64>,
 }
 
 #[derive(Debug, Serialize)]
@@ -107,6 +111,16 @@ fn main() -> Result<()> {
 
     let mut args = Args::parse();
 
+    // Pick up `[kinds]` overrides so documents get the same kind as in the meta index,
+    // and the feature flags that decide how text is analyzed.
+    let features = match core_types::config::load_config(None) {
+        Ok(cfg) => cfg.features,
+        Err(err) => {
+            warn!("failed to load config, using built-in kinds: {err}");
+            FeaturesSection::default()
+        }
+    };
+
     // Allow env override for Extractous toggle.
     if let Ok(val) = env::var("ULTRASEARCH_ENABLE_EXTRACTOUS") {
         args.enable_extractous = matches!(val.as_str(), "1" | "true" | "TRUE");
@@ -142,7 +156,7 @@ fn main() -> Result<()> {
     if let Some(job_file) = args.job_file.clone() {
         let jobs = load_jobs(&job_file)?;
         for job in jobs {
-            if let Err(err) = process_job(&stack, &index, &mut writer, job, &args) {
+            if let Err(err) = process_job(&stack, &index, &mut writer, job, &args, &features) {
                 warn!("job failed: {err}");
             }
             pending += 1;
@@ -170,9 +184,10 @@ fn main() -> Result<()> {
             max_bytes: Some(args.max_bytes),
             max_chars: Some(args.max_chars),
             file_size: fs::metadata(path).map(|m| m.len()).unwrap_or(0),
+            ancestors: Vec::new(),
         };
 
-        process_job(&stack, &index, &mut writer, single, &args)?;
+        process_job(&stack, &index, &mut writer, single, &args, &features)?;
         pending += 1;
     }
 
@@ -246,6 +261,7 @@ fn process_job(
     writer: &mut IndexWriter,
     job: JobSpec,
     args: &Args,
+    features: &FeaturesSection,
 ) -> Result<()> {
     let doc_key = DocKey::from_parts(job.volume_id, job.file_id);
 
@@ -291,7 +307,7 @@ fn process_job(
             );
 
             // Index the document.
-            let content_doc = to_content_doc(&job, &meta, out)?;
+            let content_doc = to_content_doc(&job, &meta, out, features)?;
             let tdoc = content_index::to_document(&content_doc, &index.fields);
             writer.add_document(tdoc)?;
 
@@ -325,13 +341,16 @@ fn to_content_doc(
     job: &JobSpec,
     meta: &std::fs::Metadata,
     out: content_extractor::ExtractedContent,
+    features: &FeaturesSection,
 ) -> Result<content_index::ContentDoc> {
-    let modified = meta
-        .modified()
-        .ok()
-        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-        .map(|d| d.as_secs() as i64)
-        .unwrap_or_default();
+    let to_unix = |t: std::io::Result<std::time::SystemTime>| {
+        t.ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs() as i64)
+            .unwrap_or_default()
+    };
+    let modified = to_unix(meta.modified());
+    let created = to_unix(meta.created());
 
     let name = job
         .path
@@ -344,6 +363,7 @@ fn to_content_doc(
         .extension()
         .and_then(|s| s.to_str())
         .map(|s| s.to_ascii_lowercase());
+    let text_kind = TextKind::for_file(ext.as_deref(), out.mime.as_deref(), features);
 
     Ok(content_index::ContentDoc {
         key: out.key,
@@ -353,7 +373,10 @@ fn to_content_doc(
         ext,
         size: meta.len(),
         modified,
+        created,
         content_lang: out.content_lang.clone(),
         content: out.text,
+        ancestors: job.ancestors.iter().copied().map(DocKey).collect(),
+        text_kind,
     })
 }
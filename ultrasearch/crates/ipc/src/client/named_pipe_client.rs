@@ -1,15 +1,16 @@
 #![cfg(target_os = "windows")]
 
 use crate::{
-    ReloadConfigRequest, ReloadConfigResponse, RescanRequest, RescanResponse, SearchRequest,
-    SearchResponse, StatusRequest, StatusResponse, framing,
+    HitBatch, ReloadConfigRequest, ReloadConfigResponse, RescanRequest, RescanResponse,
+    SavedSearchRequest, SavedSearchResponse, SearchRequest, SearchResponse, StatusRequest,
+    StatusResponse, SuggestRequest, SuggestResponse, framing,
 };
 use anyhow::{Result, bail};
 use serde::{Serialize, de::DeserializeOwned};
 use std::sync::OnceLock;
 use std::sync::atomic::{AtomicUsize, Ordering};
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
-use tokio::net::windows::named_pipe::ClientOptions;
+use tokio::net::windows::named_pipe::{ClientOptions, NamedPipeClient};
 use tokio::time::{Duration, sleep};
 use tracing::warn;
 
@@ -84,6 +85,24 @@ impl PipeClient {
         self.request(&req).await
     }
 
+    /// Run a streamed search, handing each hit batch to `on_batch` as it
+    /// arrives, and return the assembled response.
+    ///
+    /// The request timeout applies to each frame rather than the whole
+    /// search. Only connecting is retried; once frames flow, errors surface.
+    pub async fn search_stream(
+        &self,
+        req: SearchRequest,
+        on_batch: impl FnMut(&HitBatch),
+    ) -> Result<SearchResponse> {
+        let payload = bincode::serialize(&req.streaming())?;
+        let framed = framing::encode_frame(&payload)?;
+        let mut conn = self.open().await?;
+        conn.write_all(&framed).await?;
+
+        framing::read_search_stream(&mut conn, self.request_timeout, on_batch).await
+    }
+
     pub async fn reload_config(&self, req: ReloadConfigRequest) -> Result<ReloadConfigResponse> {
         self.request(&req).await
     }
@@ -92,6 +111,30 @@ impl PipeClient {
         self.request(&req).await
     }
 
+    pub async fn saved_searches(&self, req: SavedSearchRequest) -> Result<SavedSearchResponse> {
+        self.request(&req).await
+    }
+
+    pub async fn suggest(&self, req: SuggestRequest) -> Result<SuggestResponse> {
+        self.request(&req).await
+    }
+
+    /// Connect, retrying while the pipe is missing or busy.
+    async fn open(&self) -> Result<NamedPipeClient> {
+        let mut attempt = 0;
+        loop {
+            match ClientOptions::new().open(&self.pipe_name) {
+                Ok(conn) => return Ok(conn),
+                // 2 = ERROR_FILE_NOT_FOUND, 231 = ERROR_PIPE_BUSY
+                Err(e) if attempt < self.retries && matches!(e.raw_os_error(), Some(2 | 231)) => {
+                    attempt += 1;
+                    sleep(self.backoff * attempt.min(10)).await;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+
     async fn request<Req, Resp>(&self, req: &Req) -> Result<Resp>
     where
         Req: Serialize,
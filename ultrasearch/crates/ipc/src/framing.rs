@@ -1,5 +1,9 @@
 //! Simple length-prefixed framing helpers for pipe/stream transport.
-use anyhow::{Result, bail};
+use anyhow::{Context, Result, bail};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::{HitBatch, SearchFrame, SearchResponse, SearchStream};
 
 pub const MAX_FRAME: usize = 256 * 1024;
 
@@ -34,9 +38,94 @@ pub fn decode_frame(buf: &[u8]) -> Result<(Vec<u8>, &[u8])> {
     Ok((payload, &buf[4 + len..]))
 }
 
+/// Read one length-prefixed frame from an async stream.
+pub async fn read_frame<R: AsyncRead + Unpin>(conn: &mut R) -> Result<Vec<u8>> {
+    let mut len_buf = [0u8; 4];
+    conn.read_exact(&mut len_buf).await?;
+    let len = u32::from_le_bytes(len_buf) as usize;
+    if len > MAX_FRAME {
+        bail!("frame too large: {} bytes", len);
+    }
+    let mut payload = vec![0u8; len];
+    conn.read_exact(&mut payload).await?;
+    Ok(payload)
+}
+
+/// Read streamed search frames until the summary arrives, handing each hit
+/// batch to `on_batch` as soon as it is decoded.
+///
+/// `frame_timeout` bounds the wait for each frame, not the whole search. A
+/// stream that ends before its summary is an error, but the batches already
+/// seen have been delivered by then.
+pub async fn read_search_stream<R: AsyncRead + Unpin>(
+    conn: &mut R,
+    frame_timeout: Duration,
+    mut on_batch: impl FnMut(&HitBatch),
+) -> Result<SearchResponse> {
+    let mut stream = SearchStream::default();
+    loop {
+        let payload = tokio::time::timeout(frame_timeout, read_frame(conn))
+            .await
+            .context("timed out waiting for a search frame")?
+            .context("search stream ended before its summary")?;
+        match bincode::deserialize::<SearchFrame>(&payload)? {
+            SearchFrame::Hits(batch) => {
+                on_batch(&batch);
+                stream.push(batch);
+            }
+            SearchFrame::Done(summary) => return Ok(stream.finish(*summary)),
+        }
+    }
+}
+
+/// Encode a streamed search frame, splitting a hit batch over as many
+/// frames as it takes to stay under `MAX_FRAME`.
+pub fn encode_search_frames(frame: &SearchFrame) -> Result<Vec<Vec<u8>>> {
+    let SearchFrame::Hits(batch) = frame else {
+        return Ok(vec![encode_frame(&bincode::serialize(frame)?)?]);
+    };
+    let empty = SearchFrame::Hits(HitBatch {
+        hits: Vec::new(),
+        ..batch.clone()
+    });
+    let overhead = bincode::serialized_size(&empty)? as usize;
+
+    let mut frames = Vec::new();
+    let mut chunk = Vec::new();
+    let mut size = overhead;
+    for hit in &batch.hits {
+        let hit_size = bincode::serialized_size(hit)? as usize;
+        if overhead + hit_size > MAX_FRAME {
+            bail!("hit {:?} does not fit in a frame", hit.key);
+        }
+        if size + hit_size > MAX_FRAME {
+            frames.push(chunk_frame(batch, std::mem::take(&mut chunk))?);
+            size = overhead;
+        }
+        chunk.push(hit.clone());
+        size += hit_size;
+    }
+    if !chunk.is_empty() || frames.is_empty() {
+        frames.push(chunk_frame(batch, chunk)?);
+    }
+    Ok(frames)
+}
+
+fn chunk_frame(batch: &HitBatch, hits: Vec<crate::SearchHit>) -> Result<Vec<u8>> {
+    let frame = SearchFrame::Hits(HitBatch {
+        id: batch.id,
+        source: batch.source,
+        hits,
+    });
+    encode_frame(&bincode::serialize(&frame)?)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{HitSource, SearchHit, SearchResponse, SearchStream, SearchSummary};
+    use core_types::DocKey;
+    use uuid::Uuid;
 
     #[test]
     fn roundtrip_ok() {
@@ -91,4 +180,164 @@ mod tests {
         let res = decode_frame(&[0, 0, 0]);
         assert!(res.is_err());
     }
+
+    fn hit(frn: u64, snippet: Option<&str>) -> SearchHit {
+        SearchHit {
+            key: DocKey::from_parts(1, frn),
+            score: 1.0,
+            name: Some(format!("f{frn}")),
+            path: None,
+            ext: None,
+            size: None,
+            modified: None,
+            snippet: snippet.map(str::to_string),
+            approximate: false,
+            highlights: Default::default(),
+            created: None,
+            explain: Vec::new(),
+        }
+    }
+
+    fn decode_all(frames: &[Vec<u8>]) -> Vec<SearchFrame> {
+        frames
+            .iter()
+            .map(|f| {
+                let (payload, rest) = decode_frame(f).unwrap();
+                assert!(rest.is_empty());
+                bincode::deserialize(&payload).unwrap()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn large_batches_split_and_reassemble() {
+        let id = Uuid::new_v4();
+        let text = "x".repeat(1024);
+        let meta: Vec<SearchHit> = (0..600).map(|i| hit(i, None)).collect();
+        let content: Vec<SearchHit> = (0..600).map(|i| hit(i, Some(&text))).collect();
+
+        let mut stream = SearchStream::default();
+        for (source, hits) in [(HitSource::Meta, meta), (HitSource::Content, content)] {
+            let frames =
+                encode_search_frames(&SearchFrame::Hits(HitBatch { id, source, hits })).unwrap();
+            if source == HitSource::Content {
+                assert!(frames.len() > 1);
+            }
+            for frame in decode_all(&frames) {
+                let SearchFrame::Hits(batch) = frame else {
+                    panic!("expected hits");
+                };
+                assert_eq!(batch.source, source);
+                stream.push(batch);
+            }
+        }
+
+        let mut response = SearchResponse {
+            id,
+            hits: vec![hit(599, None), hit(3, None)],
+            total: 600,
+            truncated: false,
+            took_ms: 1,
+            served_by: None,
+            error: None,
+            facets: Vec::new(),
+            explain: None,
+            next_cursor: None,
+            suggestions: Vec::new(),
+        };
+        response.hits[0].score = 2.0;
+        let summary = SearchSummary::from_response(response);
+        let frames = encode_search_frames(&SearchFrame::Done(Box::new(summary))).unwrap();
+        let [SearchFrame::Done(summary)] = &decode_all(&frames)[..] else {
+            panic!("expected one summary frame");
+        };
+        let done = stream.finish(summary.as_ref().clone());
+        let keys: Vec<(u64, f32)> = done
+            .hits
+            .iter()
+            .map(|h| (h.key.file_id(), h.score))
+            .collect();
+        assert_eq!(keys, [(599, 2.0), (3, 1.0)]);
+        assert_eq!(done.hits[1].snippet.as_deref(), Some(text.as_str()));
+        assert_eq!(done.total, 600);
+    }
+
+    fn summary(id: Uuid, hits: Vec<SearchHit>) -> SearchSummary {
+        SearchSummary::from_response(SearchResponse {
+            id,
+            total: hits.len() as u64,
+            hits,
+            truncated: false,
+            took_ms: 1,
+            served_by: None,
+            error: None,
+            facets: Vec::new(),
+            explain: None,
+            next_cursor: None,
+            suggestions: Vec::new(),
+        })
+    }
+
+    /// Write `bytes` to a duplex pipe a few bytes at a time, then hang up.
+    fn trickle(bytes: Vec<u8>) -> tokio::io::DuplexStream {
+        use tokio::io::AsyncWriteExt;
+        let (client, mut server) = tokio::io::duplex(64);
+        tokio::spawn(async move {
+            for chunk in bytes.chunks(7) {
+                server.write_all(chunk).await.unwrap();
+            }
+        });
+        client
+    }
+
+    fn hit_frames(id: Uuid, source: HitSource, hits: Vec<SearchHit>) -> Vec<u8> {
+        encode_search_frames(&SearchFrame::Hits(HitBatch { id, source, hits }))
+            .unwrap()
+            .concat()
+    }
+
+    #[tokio::test]
+    async fn reads_a_chunked_stream() {
+        let id = Uuid::new_v4();
+        let mut bytes = hit_frames(id, HitSource::Meta, vec![hit(1, None), hit(2, None)]);
+        bytes.extend(hit_frames(
+            id,
+            HitSource::Content,
+            vec![hit(2, Some("body"))],
+        ));
+        let done = SearchFrame::Done(Box::new(summary(id, vec![hit(2, None), hit(1, None)])));
+        bytes.extend(encode_search_frames(&done).unwrap().concat());
+
+        let mut sources = Vec::new();
+        let mut conn = trickle(bytes);
+        let resp = read_search_stream(&mut conn, Duration::from_secs(5), |batch| {
+            sources.push((batch.source, batch.hits.len()))
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(sources, [(HitSource::Meta, 2), (HitSource::Content, 1)]);
+        let keys: Vec<u64> = resp.hits.iter().map(|h| h.key.file_id()).collect();
+        assert_eq!(keys, [2, 1]);
+        assert_eq!(resp.hits[0].snippet.as_deref(), Some("body"));
+    }
+
+    #[tokio::test]
+    async fn truncated_stream_errors_after_delivering_batches() {
+        let id = Uuid::new_v4();
+        let mut bytes = hit_frames(id, HitSource::Meta, vec![hit(1, None)]);
+        let content = hit_frames(id, HitSource::Content, vec![hit(1, Some("body"))]);
+        bytes.extend_from_slice(&content[..content.len() / 2]);
+
+        let mut seen = Vec::new();
+        let mut conn = trickle(bytes);
+        let err = read_search_stream(&mut conn, Duration::from_secs(5), |batch| {
+            seen.extend(batch.hits.iter().map(|h| h.key.file_id()))
+        })
+        .await
+        .unwrap_err();
+
+        assert_eq!(seen, [1]);
+        assert!(err.to_string().contains("ended before its summary"));
+    }
 }
@@ -66,73 +66,11 @@ mod duration_ms {
     }
 }
 
-/// Fields that can be targeted explicitly in the query language.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
-pub enum FieldKind {
-    Name,
-    Path,
-    Ext,
-    Content,
-    Size,
-    Modified,
-    Created,
-    Flags,
-    Volume,
-    Kind,
-}
-
-/// How a term should be interpreted.
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
-pub enum TermModifier {
-    Term,
-    Phrase,
-    Prefix,
-    Fuzzy(u8), // max edit distance
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-pub struct TermExpr {
-    pub field: Option<FieldKind>, // None => default (name + content)
-    pub value: String,
-    pub modifier: TermModifier,
-}
-
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
-pub enum RangeOp {
-    Gt,
-    Ge,
-    Lt,
-    Le,
-    Between,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-pub enum RangeValue {
-    I64 { lo: i64, hi: Option<i64> }, // timestamps
-    U64 { lo: u64, hi: Option<u64> }, // sizes
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-pub struct RangeExpr {
-    pub field: FieldKind,
-    pub op: RangeOp,
-    pub value: RangeValue,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
-pub enum QueryExpr {
-    Term(TermExpr),
-    Range(RangeExpr),
-    Not(Box<QueryExpr>),
-    And(Vec<QueryExpr>),
-    Or(Vec<QueryExpr>),
-}
-
-impl Default for QueryExpr {
-    fn default() -> Self {
-        QueryExpr::And(Vec::new())
-    }
-}
+// The query AST lives in core-types so the textual parser can share it with
+// the service without a dependency cycle; re-export it as part of the protocol.
+pub use core_types::query::{
+    FieldKind, QueryExpr, RangeExpr, RangeOp, RangeValue, TermExpr, TermModifier,
+};
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
 pub enum SearchMode {
@@ -160,6 +98,114 @@ pub struct SearchRequest {
     pub timeout: Option<Duration>,
     #[serde(default)]
     pub offset: u32,
+    /// Result order; `None` ranks by relevance.
+    #[serde(default)]
+    pub sort: Option<SortSpec>,
+    /// Breakdowns to count over all matches; `None` skips faceting.
+    #[serde(default)]
+    pub facets: Option<FacetRequest>,
+    /// Return the query plan and per-hit score explanations.
+    #[serde(default)]
+    pub explain: bool,
+    /// `SearchResponse::next_cursor` of the previous page. Resumes right
+    /// after its last hit against the same index snapshot; `offset` is
+    /// ignored when set.
+    #[serde(default)]
+    pub cursor: Option<String>,
+    /// Answer with `SearchFrame`s (hits per index pass, then a summary)
+    /// instead of a single `SearchResponse`.
+    #[serde(default)]
+    pub stream: bool,
+}
+
+/// Field a `SortSpec` orders by.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum SortField {
+    /// Case-insensitive file name.
+    Name,
+    Size,
+    Modified,
+    Created,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+pub enum SortDirection {
+    #[default]
+    Asc,
+    Desc,
+}
+
+/// Server-side ordering of the whole result set; ties fall back to score.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SortSpec {
+    pub field: SortField,
+    #[serde(default)]
+    pub direction: SortDirection,
+}
+
+impl SortSpec {
+    pub const fn asc(field: SortField) -> Self {
+        Self {
+            field,
+            direction: SortDirection::Asc,
+        }
+    }
+
+    pub const fn desc(field: SortField) -> Self {
+        Self {
+            field,
+            direction: SortDirection::Desc,
+        }
+    }
+}
+
+/// Dimension a facet breaks the matches down by.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum FacetField {
+    /// Lowercase extension; files without one are counted under `""`.
+    Ext,
+    Volume,
+    /// Size classes: `empty`, `tiny` (< 16 KiB), `small` (< 1 MiB),
+    /// `medium` (< 128 MiB), `large` (< 1 GiB), `huge` (< 4 GiB), `gigantic`.
+    Size,
+    /// Modification month (`YYYY-MM`, UTC).
+    Modified,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FacetRequest {
+    pub fields: Vec<FacetField>,
+    /// Cap on `Ext` and `Volume` buckets (largest kept); 0 means no cap.
+    /// `Size` and `Modified` always return every non-empty bucket.
+    #[serde(default)]
+    pub max_buckets: u32,
+}
+
+impl FacetRequest {
+    pub fn new(fields: impl Into<Vec<FacetField>>) -> Self {
+        Self {
+            fields: fields.into(),
+            max_buckets: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FacetBucket {
+    pub value: String,
+    pub count: u64,
+    /// Query clause that narrows a search to this bucket (e.g. `ext:pdf`);
+    /// `None` when the bucket cannot be expressed in the query language.
+    #[serde(default)]
+    pub filter: Option<String>,
+}
+
+/// Counts for one `FacetField`. `Ext` and `Volume` buckets are ordered by
+/// count (largest first), `Size` and `Modified` by value.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FacetResult {
+    pub field: FacetField,
+    pub buckets: Vec<FacetBucket>,
 }
 
 fn default_limit() -> u32 {
@@ -175,6 +221,11 @@ impl Default for SearchRequest {
             mode: SearchMode::Auto,
             timeout: None,
             offset: 0,
+            sort: None,
+            facets: None,
+            explain: false,
+            cursor: None,
+            stream: false,
         }
     }
 }
@@ -211,6 +262,36 @@ impl SearchRequest {
         self.mode = mode;
         self
     }
+
+    /// Order results by a field instead of relevance.
+    pub fn with_sort(mut self, sort: SortSpec) -> Self {
+        self.sort = Some(sort);
+        self
+    }
+
+    /// Ask for the query plan and score explanations in the response.
+    pub fn with_explain(mut self) -> Self {
+        self.explain = true;
+        self
+    }
+
+    /// Request facet counts alongside the hits.
+    pub fn with_facets(mut self, facets: FacetRequest) -> Self {
+        self.facets = Some(facets);
+        self
+    }
+
+    /// Continue from a previous page's `next_cursor`.
+    pub fn with_cursor(mut self, cursor: impl Into<String>) -> Self {
+        self.cursor = Some(cursor.into());
+        self
+    }
+
+    /// Ask for a streamed response.
+    pub fn streaming(mut self) -> Self {
+        self.stream = true;
+        self
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -223,6 +304,45 @@ pub struct SearchHit {
     pub size: Option<u64>,
     pub modified: Option<i64>,
     pub snippet: Option<String>,
+    /// True when the hit only matched through fuzzy (edit-distance) terms.
+    #[serde(default)]
+    pub approximate: bool,
+    /// Where the query matched `name`, `path` and `snippet`.
+    #[serde(default)]
+    pub highlights: Highlights,
+    #[serde(default)]
+    pub created: Option<i64>,
+    /// Score breakdown per index the hit matched in; only filled for
+    /// `SearchRequest::explain`.
+    #[serde(default)]
+    pub explain: Vec<HitExplanation>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HitExplanation {
+    /// Index that scored the hit (`meta` or `content`).
+    pub index: String,
+    pub score: f32,
+    /// Tantivy's explanation tree, as JSON.
+    pub detail: String,
+}
+
+/// Half-open byte range (UTF-8 offsets) of a match within a string.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MatchRange {
+    pub start: u32,
+    pub end: u32,
+}
+
+/// Match ranges for the displayed text fields of a `SearchHit`.
+///
+/// Ranges are sorted and non-overlapping; empty when the field did not match
+/// or only matched through prefix, fuzzy, glob or regex terms.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Highlights {
+    pub name: Vec<MatchRange>,
+    pub path: Vec<MatchRange>,
+    pub snippet: Vec<MatchRange>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -234,6 +354,139 @@ pub struct SearchResponse {
     pub took_ms: u32,
     #[serde(default)]
     pub served_by: Option<String>,
+    /// Set when the query could not be executed (e.g. an unsupported filter).
+    #[serde(default)]
+    pub error: Option<String>,
+    /// One entry per requested `FacetField`, in request order.
+    #[serde(default)]
+    pub facets: Vec<FacetResult>,
+    /// Set when `SearchRequest::explain` was requested.
+    #[serde(default)]
+    pub explain: Option<SearchExplain>,
+    /// Opaque token for the page after this one; `None` on the last page.
+    #[serde(default)]
+    pub next_cursor: Option<String>,
+    /// Likely intended spellings when the query found little or nothing.
+    #[serde(default)]
+    pub suggestions: Vec<Suggestion>,
+}
+
+/// A more common indexed word close to one the query used.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Suggestion {
+    /// The word as the index saw it (lowercased).
+    pub term: String,
+    pub suggestion: String,
+    /// Edit distance between the two.
+    pub distance: u8,
+    /// Documents containing `suggestion`.
+    pub doc_freq: u64,
+}
+
+/// How a search was executed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchExplain {
+    /// Query AST after `QueryPlanner` optimization.
+    pub plan: QueryExpr,
+    /// Mode that ran, with `Auto` resolved.
+    pub mode: SearchMode,
+    /// Queries in the order they were executed.
+    pub queries: Vec<IndexQuery>,
+}
+
+/// Query run against one index.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexQuery {
+    /// `meta`, `content` or `names` (the name FST).
+    pub index: String,
+    /// Debug rendering of the Tantivy query (or the FST regex).
+    pub query: String,
+}
+
+/// Index pass a streamed batch of hits came from.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum HitSource {
+    Meta,
+    Content,
+}
+
+/// Hits of one index pass, in that index's rank order and with its scores.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HitBatch {
+    pub id: Uuid,
+    pub source: HitSource,
+    pub hits: Vec<SearchHit>,
+}
+
+/// End of a streamed search.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchSummary {
+    /// Everything but the hits, which were sent as `HitBatch`es.
+    pub response: SearchResponse,
+    /// The page in final order, with final scores. Hybrid searches stream
+    /// both passes before merging, so this may be a subset of the batches.
+    pub ranking: Vec<(DocKey, f32)>,
+}
+
+impl SearchSummary {
+    /// Split a complete response into its summary.
+    pub fn from_response(mut response: SearchResponse) -> Self {
+        let ranking = response.hits.iter().map(|h| (h.key, h.score)).collect();
+        response.hits.clear();
+        Self { response, ranking }
+    }
+}
+
+/// One frame of a streamed search: any number of `Hits` (metadata pass
+/// first), then exactly one `Done`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SearchFrame {
+    Hits(HitBatch),
+    Done(Box<SearchSummary>),
+}
+
+/// Rebuilds the `SearchResponse` a streamed search would have returned.
+#[derive(Debug, Default)]
+pub struct SearchStream {
+    hits: std::collections::HashMap<DocKey, SearchHit>,
+}
+
+impl SearchStream {
+    /// Take in a batch. A file found by both passes keeps the first hit,
+    /// filled in with the content pass's snippet and explanations.
+    pub fn push(&mut self, batch: HitBatch) {
+        for hit in batch.hits {
+            match self.hits.entry(hit.key) {
+                std::collections::hash_map::Entry::Occupied(mut seen) => {
+                    let seen = seen.get_mut();
+                    seen.approximate &= hit.approximate;
+                    seen.explain.extend(hit.explain);
+                    if seen.snippet.is_none() {
+                        seen.snippet = hit.snippet;
+                        seen.highlights.snippet = hit.highlights.snippet;
+                    }
+                }
+                std::collections::hash_map::Entry::Vacant(slot) => {
+                    slot.insert(hit);
+                }
+            }
+        }
+    }
+
+    /// The final response: the summary with its ranked hits filled in.
+    pub fn finish(mut self, summary: SearchSummary) -> SearchResponse {
+        let mut response = summary.response;
+        response.hits = summary
+            .ranking
+            .into_iter()
+            .filter_map(|(key, score)| {
+                let mut hit = self.hits.remove(&key)?;
+                hit.score = score;
+                Some(hit)
+            })
+            .collect();
+        response
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -265,6 +518,104 @@ pub struct RescanResponse {
     pub message: Option<String>,
 }
 
+/// A named search the service keeps across restarts.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SavedSearch {
+    pub name: String,
+    /// Query as typed; the service parses it on every run.
+    pub query: String,
+    #[serde(default)]
+    pub mode: SearchMode,
+    #[serde(default)]
+    pub sort: Option<SortSpec>,
+    /// Remember the match count of each run to report new matches.
+    #[serde(default)]
+    pub track: bool,
+    /// Match count of the last run, when tracked.
+    #[serde(default)]
+    pub last_total: Option<u64>,
+}
+
+impl SavedSearch {
+    pub fn new(name: impl Into<String>, query: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            query: query.into(),
+            mode: SearchMode::Auto,
+            sort: None,
+            track: false,
+            last_total: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum SavedSearchOp {
+    List,
+    /// Create, or replace the search with the same name.
+    Save(SavedSearch),
+    Run {
+        name: String,
+        limit: u32,
+    },
+    Remove {
+        name: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedSearchRequest {
+    pub id: Uuid,
+    pub op: SavedSearchOp,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedSearchResponse {
+    pub id: Uuid,
+    /// Every saved search after the operation, by name.
+    pub searches: Vec<SavedSearch>,
+    /// Results of `Run`.
+    pub results: Option<SearchResponse>,
+    /// Matches beyond the previous run's count, for tracked searches that
+    /// ran before.
+    pub new_matches: Option<u64>,
+    pub error: Option<String>,
+}
+
+/// File-name completions for what has been typed so far.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuggestRequest {
+    pub id: Uuid,
+    pub prefix: String,
+    pub limit: u32,
+}
+
+impl SuggestRequest {
+    pub fn new(prefix: impl Into<String>, limit: u32) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            prefix: prefix.into(),
+            limit,
+        }
+    }
+}
+
+/// A file name starting with the requested prefix.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Completion {
+    /// Name as the most recently modified of those files spells it.
+    pub name: String,
+    /// Indexed files with this name, matched case-insensitively.
+    pub files: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SuggestResponse {
+    pub id: Uuid,
+    /// Most recently modified first.
+    pub completions: Vec<Completion>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VolumeStatus {
     pub volume: u16,
@@ -302,6 +653,15 @@ pub struct MetricsSnapshot {
     pub content_enqueued: Option<u64>,
     /// Total content jobs dropped due to backpressure or missing scheduler (best-effort).
     pub content_dropped: Option<u64>,
+    /// Searches answered from the result cache since startup.
+    #[serde(default)]
+    pub cache_hits: Option<u64>,
+    /// Cacheable searches that had to run since startup.
+    #[serde(default)]
+    pub cache_misses: Option<u64>,
+    /// Size of the metadata index's name trigrams; `None` when disabled.
+    #[serde(default)]
+    pub name_ngram_bytes: Option<u64>,
 }
 
 #[cfg(test)]
@@ -336,6 +696,11 @@ mod tests {
                 value: "report".into(),
                 modifier: TermModifier::Prefix,
             }),
+            QueryExpr::Term(TermExpr {
+                field: None,
+                value: "*.rs".into(),
+                modifier: TermModifier::Glob,
+            }),
             QueryExpr::Range(RangeExpr {
                 field: FieldKind::Modified,
                 op: RangeOp::Ge,
@@ -353,6 +718,11 @@ mod tests {
             mode: SearchMode::Hybrid,
             timeout: None,
             offset: 0,
+            sort: None,
+            facets: None,
+            explain: false,
+            cursor: None,
+            stream: false,
         };
 
         let bytes = ser(&req);
@@ -376,11 +746,17 @@ mod tests {
             mode: SearchMode::Auto,
             timeout: Some(Duration::from_millis(250)),
             offset: 7,
+            sort: None,
+            facets: None,
+            explain: false,
+            cursor: Some("0a1b".into()),
+            stream: false,
         };
         let bytes = ser(&req);
         let back: SearchRequest = de(&bytes);
         assert_eq!(back.timeout, Some(Duration::from_millis(250)));
         assert_eq!(back.offset, 7);
+        assert_eq!(back.cursor.as_deref(), Some("0a1b"));
     }
 
     #[test]
@@ -413,6 +789,9 @@ mod tests {
             active_workers: Some(2),
             content_enqueued: Some(9),
             content_dropped: Some(1),
+            cache_hits: Some(4),
+            cache_misses: Some(3),
+            name_ngram_bytes: Some(4096),
         };
         let bytes = ser(&m);
         let back: MetricsSnapshot = de(&bytes);
@@ -420,6 +799,8 @@ mod tests {
         assert_eq!(back.active_workers, Some(2));
         assert_eq!(back.content_enqueued, Some(9));
         assert_eq!(back.content_dropped, Some(1));
+        assert_eq!(back.cache_hits, Some(4));
+        assert_eq!(back.cache_misses, Some(3));
     }
 
     #[test]
@@ -4,25 +4,48 @@ use std::path::Path;
 
 use anyhow::Result;
 use core_types::DocKey;
-use fst::{IntoStreamer, Map, MapBuilder, Streamer};
-use memmap2::Mmap;
+use fst::{Automaton, IntoStreamer, Map, MapBuilder, Streamer};
+use regex_automata::dfa::{Automaton as _, StartKind, dense};
+use regex_automata::util::{primitives::StateID, syntax};
+use regex_automata::{Anchored, Input};
 
-/// A memory-mapped FST index for fast prefix lookups.
+/// File name of the name FST inside the metadata index directory.
+pub const FST_FILE_NAME: &str = "names.fst";
+
+/// Upper bound on the compiled size of a `search_regex` pattern.
+const REGEX_SIZE_LIMIT: usize = 8 * 1024 * 1024;
+
+/// Keys read per `complete` call, so one-letter prefixes stay cheap.
+const COMPLETE_SCAN_LIMIT: usize = 20_000;
+
+/// A distinct file name offered by `FstIndex::complete`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NameCompletion {
+    pub name: String,
+    /// Files with this name.
+    pub files: u32,
+    /// Largest value stored with any of those files.
+    pub rank: u64,
+    /// The file that value belongs to; its stored name keeps the original case.
+    pub doc_key: DocKey,
+}
+
+/// An FST index for fast prefix lookups.
+///
+/// The FST is read into memory rather than mapped: Windows refuses to replace
+/// a mapped file, and ingest rewrites it while search handlers hold it open.
 ///
 /// Keys are encoded as `normalized_name + \0 + doc_key_be_bytes` to handle duplicates.
-/// The value associated with the FST key is unused (always 0) because the DocKey
-/// is embedded in the key itself to allow multiple files with the same name.
+/// The value is the rank written by `FstBuilder::insert_ranked` (modification
+/// time from `write_name_fst`, 0 via `insert_batch`); `complete` sorts by it.
 pub struct FstIndex {
-    map: Map<Mmap>,
+    map: Map<Vec<u8>>,
 }
 
 impl FstIndex {
     /// Open an FST index from a path.
     pub fn open(path: &Path) -> Result<Self> {
-        let file = File::open(path)?;
-        // SAFETY: We assume the file is immutable and safe to map.
-        let mmap = unsafe { Mmap::map(&file)? };
-        let map = Map::new(mmap)?;
+        let map = Map::new(std::fs::read(path)?)?;
         Ok(Self { map })
     }
 
@@ -31,6 +54,75 @@ impl FstIndex {
     /// `prefix` should be normalized (lowercased) if the index was built with normalized names.
     /// `limit` caps the number of results returned to prevent excessive memory usage.
     pub fn search<'a>(&'a self, prefix: &str, limit: usize) -> impl Iterator<Item = DocKey> + 'a {
+        let mut stream = self.prefix_range(prefix).into_stream();
+        let mut hits = Vec::new();
+
+        while let Some((k, _)) = stream.next() {
+            if hits.len() >= limit {
+                break;
+            }
+
+            // Double check prefix (range should handle it, but being safe against edge cases)
+            if !k.starts_with(prefix.as_bytes()) {
+                continue;
+            }
+
+            if let Some(key) = decode_doc_key(k) {
+                hits.push(key);
+            }
+        }
+
+        hits.into_iter()
+    }
+
+    /// Distinct names starting with `prefix`, best first: by rank (see
+    /// `FstBuilder::insert_ranked`), then by how many files share the name.
+    ///
+    /// `prefix` should be normalized like the indexed names. At most
+    /// `COMPLETE_SCAN_LIMIT` keys are read, so a very short prefix only
+    /// ranks the alphabetically first part of its range.
+    pub fn complete(&self, prefix: &str, limit: usize) -> Vec<NameCompletion> {
+        let mut stream = self.prefix_range(prefix).into_stream();
+        let mut names: Vec<NameCompletion> = Vec::new();
+        let mut scanned = 0;
+        while let Some((k, rank)) = stream.next() {
+            scanned += 1;
+            if scanned > COMPLETE_SCAN_LIMIT {
+                break;
+            }
+            let Some(doc_key) = decode_doc_key(k) else {
+                continue;
+            };
+            let name = &k[..k.len() - 9];
+            // Keys are sorted, so files sharing a name are adjacent.
+            match names.last_mut() {
+                Some(last) if last.name.as_bytes() == name => {
+                    last.files += 1;
+                    if rank > last.rank {
+                        last.rank = rank;
+                        last.doc_key = doc_key;
+                    }
+                }
+                _ => names.push(NameCompletion {
+                    name: String::from_utf8_lossy(name).into_owned(),
+                    files: 1,
+                    rank,
+                    doc_key,
+                }),
+            }
+        }
+        names.sort_by(|a, b| {
+            b.rank
+                .cmp(&a.rank)
+                .then(b.files.cmp(&a.files))
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        names.truncate(limit);
+        names
+    }
+
+    /// Keys whose name starts with `prefix`.
+    fn prefix_range(&self, prefix: &str) -> fst::map::StreamBuilder<'_> {
         let start = prefix.as_bytes().to_vec();
         let mut builder = self.map.range().ge(start);
 
@@ -49,37 +141,116 @@ impl FstIndex {
         if has_end {
             builder = builder.lt(end);
         }
+        builder
+    }
 
-        let mut stream = builder.into_stream();
+    /// Search for names matching a regular expression.
+    ///
+    /// Matching is case-insensitive and unanchored (`report` matches
+    /// `q3-report.pdf`); use `^`/`$` to pin the whole name. The automaton is
+    /// run against the FST directly, so only matching keys are visited.
+    /// Returns the first `limit` matches in name order and the number of
+    /// all matches.
+    pub fn search_regex(&self, pattern: &str, limit: usize) -> Result<(Vec<DocKey>, u64)> {
+        let automaton = NameRegex::new(pattern)?;
+        let mut stream = self.map.search(automaton).into_stream();
         let mut hits = Vec::new();
+        let mut total = 0;
 
         while let Some((k, _)) = stream.next() {
-            if hits.len() >= limit {
-                break;
+            if let Some(key) = decode_doc_key(k) {
+                if hits.len() < limit {
+                    hits.push(key);
+                }
+                total += 1;
             }
+        }
 
-            // Double check prefix (range should handle it, but being safe against edge cases)
-            if !k.starts_with(prefix.as_bytes()) {
-                continue;
-            }
+        Ok((hits, total))
+    }
+}
 
-            // Key format: name_bytes + \0 + 8 bytes DocKey (BE).
-            if k.len() < 9 {
-                continue;
-            }
+/// Key format: name_bytes + \0 + 8 bytes DocKey (BE).
+fn decode_doc_key(k: &[u8]) -> Option<DocKey> {
+    if k.len() < 9 {
+        return None;
+    }
 
-            let (rest, dk_bytes) = k.split_at(k.len() - 8);
-            if rest.last() != Some(&0) {
-                continue;
-            }
+    let (rest, dk_bytes) = k.split_at(k.len() - 8);
+    if rest.last() != Some(&0) {
+        return None;
+    }
 
-            if let Ok(bytes) = dk_bytes.try_into() {
-                let val = u64::from_be_bytes(bytes);
-                hits.push(DocKey(val));
-            }
+    dk_bytes
+        .try_into()
+        .ok()
+        .map(|b| DocKey(u64::from_be_bytes(b)))
+}
+
+/// FST automaton matching the name part of a key against a regex DFA and
+/// then accepting the `\0` + DocKey suffix unchanged.
+struct NameRegex {
+    dfa: dense::DFA<Vec<u32>>,
+    start: StateID,
+}
+
+#[derive(Clone, Copy)]
+enum NameRegexState {
+    Name(StateID),
+    Suffix(u8),
+    Dead,
+}
+
+impl NameRegex {
+    fn new(pattern: &str) -> Result<Self> {
+        // Wrap in `.*` on both sides for search semantics; `^`/`$` in the user
+        // pattern still only match at the ends of the name.
+        let dfa = dense::Builder::new()
+            .configure(
+                dense::Config::new()
+                    .start_kind(StartKind::Anchored)
+                    .dfa_size_limit(Some(REGEX_SIZE_LIMIT))
+                    .determinize_size_limit(Some(REGEX_SIZE_LIMIT)),
+            )
+            .syntax(syntax::Config::new().case_insensitive(true))
+            .build(&format!("(?s:.)*(?:{pattern})(?s:.)*"))?;
+        let start = dfa.start_state_forward(&Input::new("").anchored(Anchored::Yes))?;
+        Ok(Self { dfa, start })
+    }
+}
+
+impl Automaton for NameRegex {
+    type State = NameRegexState;
+
+    fn start(&self) -> Self::State {
+        NameRegexState::Name(self.start)
+    }
+
+    fn is_match(&self, state: &Self::State) -> bool {
+        matches!(state, NameRegexState::Suffix(8))
+    }
+
+    fn can_match(&self, state: &Self::State) -> bool {
+        match *state {
+            NameRegexState::Name(id) => !self.dfa.is_dead_state(id),
+            NameRegexState::Suffix(n) => n < 8,
+            NameRegexState::Dead => false,
         }
+    }
 
-        hits.into_iter()
+    fn accept(&self, state: &Self::State, byte: u8) -> Self::State {
+        match *state {
+            NameRegexState::Name(id) if byte == 0 => {
+                if self.dfa.is_match_state(self.dfa.next_eoi_state(id)) {
+                    NameRegexState::Suffix(0)
+                } else {
+                    NameRegexState::Dead
+                }
+            }
+            NameRegexState::Name(id) => NameRegexState::Name(self.dfa.next_state(id, byte)),
+            NameRegexState::Suffix(n) if n < 8 => NameRegexState::Suffix(n + 1),
+            _ => NameRegexState::Dead,
+        }
     }
 }
 
@@ -101,22 +272,35 @@ impl FstBuilder {
     /// `entries` is a list of `(normalized_name, doc_key)`.
     /// This function sorts them internally to satisfy FST insertion requirements.
     pub fn insert_batch(&mut self, entries: Vec<(String, DocKey)>) -> Result<()> {
+        self.insert_ranked(
+            entries
+                .into_iter()
+                .map(|(name, dk)| (name, dk, 0))
+                .collect(),
+        )
+    }
+
+    /// Insert `(normalized_name, doc_key, rank)` entries. `rank` orders
+    /// `FstIndex::complete` results, higher first; the modification time in
+    /// seconds makes recently touched files complete first.
+    pub fn insert_ranked(&mut self, entries: Vec<(String, DocKey, u64)>) -> Result<()> {
         // Transform to encoded keys: name + \0 + doc_key(BE)
-        let mut keys: Vec<Vec<u8>> = entries
+        let mut keys: Vec<(Vec<u8>, u64)> = entries
             .into_iter()
-            .map(|(name, dk)| {
+            .map(|(name, dk, rank)| {
                 let mut k = name.into_bytes();
                 k.push(0);
                 k.extend_from_slice(&dk.0.to_be_bytes());
-                k
+                (k, rank)
             })
             .collect();
 
-        keys.sort();
-        keys.dedup(); // Dedup exact matches just in case
+        // Dedup exact matches just in case, keeping the highest rank.
+        keys.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)));
+        keys.dedup_by(|a, b| a.0 == b.0);
 
-        for k in keys {
-            self.writer.insert(&k, 0)?;
+        for (k, rank) in keys {
+            self.writer.insert(&k, rank)?;
         }
         Ok(())
     }
@@ -179,4 +363,79 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn regex_search_matches_names() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join(FST_FILE_NAME);
+
+        let mut builder = FstBuilder::new(&path)?;
+        builder.insert_batch(vec![
+            ("img_0001.jpg".to_string(), DocKey(1)),
+            ("img_0002.jpeg".to_string(), DocKey(2)),
+            ("img_12.jpg".to_string(), DocKey(3)),
+            ("old_img_0003.jpg".to_string(), DocKey(4)),
+            ("notes.txt".to_string(), DocKey(5)),
+        ])?;
+        builder.finish()?;
+        let index = FstIndex::open(&path)?;
+
+        let keys = |pattern: &str, limit| -> Result<Vec<u64>> {
+            let mut hits: Vec<u64> = index
+                .search_regex(pattern, limit)?
+                .0
+                .into_iter()
+                .map(|k| k.0)
+                .collect();
+            hits.sort();
+            Ok(hits)
+        };
+
+        // Anchored and case-insensitive.
+        assert_eq!(keys(r"^IMG_\d{4}\.jpe?g$", 10)?, vec![1, 2]);
+        // Unanchored patterns match anywhere in the name.
+        assert_eq!(keys(r"img_\d{4}", 10)?, vec![1, 2, 4]);
+        assert_eq!(keys(r"\.txt$", 10)?, vec![5]);
+        assert!(keys(r"^zzz", 10)?.is_empty());
+        assert_eq!(keys("img", 2)?.len(), 2);
+        assert_eq!(index.search_regex("img", 2)?.1, 4);
+        assert!(index.search_regex("(unclosed", 10).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn completions_rank_by_value_then_file_count() -> Result<()> {
+        let dir = tempdir()?;
+        let path = dir.path().join(FST_FILE_NAME);
+
+        let mut builder = FstBuilder::new(&path)?;
+        builder.insert_ranked(vec![
+            ("report.docx".to_string(), DocKey(1), 100),
+            ("readme.md".to_string(), DocKey(2), 50),
+            ("readme.md".to_string(), DocKey(3), 70),
+            ("recipe.txt".to_string(), DocKey(4), 70),
+            ("notes.txt".to_string(), DocKey(5), 900),
+        ])?;
+        builder.finish()?;
+        let index = FstIndex::open(&path)?;
+
+        let completions = index.complete("re", 10);
+        let names: Vec<(&str, u32)> = completions
+            .iter()
+            .map(|c| (c.name.as_str(), c.files))
+            .collect();
+        assert_eq!(
+            names,
+            [("report.docx", 1), ("readme.md", 2), ("recipe.txt", 1)]
+        );
+        assert_eq!(completions[1].rank, 70);
+        assert_eq!(completions[1].doc_key, DocKey(3));
+        assert_eq!(index.complete("re", 1).len(), 1);
+        assert!(index.complete("x", 10).is_empty());
+        // Prefix search still lists every file.
+        assert_eq!(index.search("readme", 10).count(), 2);
+
+        Ok(())
+    }
 }
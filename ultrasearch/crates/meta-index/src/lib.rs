@@ -7,15 +7,19 @@
 
 use std::path::Path;
 
-use anyhow::Result;
-use core_types::{DocKey, FileMeta as CoreFileMeta};
-use tantivy::{Index, IndexWriter, schema::document::TantivyDocument, schema::*};
+use anyhow::{Context, Result};
+use code_analysis::{CODE_ANALYZER, register_code_analyzers};
+use core_types::{
+    DocKey, FileFlags, FileMeta as CoreFileMeta, SchemaMismatch, ancestors::AncestorIndex, kind,
+};
+use tantivy::{Index, IndexWriter, Searcher, schema::document::TantivyDocument, schema::*};
 
 #[cfg(test)]
 use tantivy::{IndexSettings, ReloadPolicy};
 
 pub mod cache;
 pub mod fst;
+pub mod ngram;
 pub mod state;
 pub mod tiers;
 
@@ -31,21 +35,108 @@ pub struct MetaFields {
     pub created: Field,
     pub modified: Field,
     pub flags: Field,
+    /// Lowercased, untokenized file name used for glob matching and name sorting.
+    pub name_raw: Field,
+    /// File kind from `core_types::kind` (`document`, `image`, `folder`, ...).
+    pub kind: Field,
+    /// Every ancestor folder `DocKey` (multi-valued), used by `in:` filters.
+    pub ancestors: Field,
+    /// Positioned name trigrams (see `ngram`) for substring matching; absent
+    /// from indexes created without them.
+    pub name_ngram: Option<Field>,
+}
+
+/// Schema field holding `MetaFields::name_ngram`.
+pub const NAME_NGRAM_FIELD: &str = "name_ngram";
+
+impl MetaFields {
+    /// Field handles for an opened index. Optional fields are taken from its
+    /// schema; every other field of `build_schema` must be there under the
+    /// same id, so indexes created by earlier versions have to be rebuilt.
+    pub fn for_index(index: &Index) -> Result<Self> {
+        let (expected, mut fields) = build_schema();
+        let actual = index.schema();
+        for (field, entry) in expected.fields() {
+            let found = actual.get_field(entry.name()).ok();
+            let matches = found.is_some_and(|found| {
+                found == field
+                    && actual.get_field_entry(found).field_type().value_type()
+                        == entry.field_type().value_type()
+            });
+            if !matches && !(found.is_none() && entry.name() == NAME_NGRAM_FIELD) {
+                return Err(SchemaMismatch {
+                    index: "metadata",
+                    field: entry.name().to_string(),
+                }
+                .into());
+            }
+        }
+        fields.name_ngram = actual.get_field(NAME_NGRAM_FIELD).ok();
+        Ok(fields)
+    }
+}
+
+/// Choices fixed when a metadata index is created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SchemaOptions {
+    /// Add the `name_ngram` field.
+    pub name_ngrams: bool,
+    /// Tokenize `name` with the identifier-splitting `CODE_ANALYZER`, so
+    /// `parseHttpRequest.ts` matches `http request`.
+    pub code_names: bool,
+}
+
+impl Default for SchemaOptions {
+    fn default() -> Self {
+        Self {
+            name_ngrams: true,
+            code_names: false,
+        }
+    }
 }
 
 /// Build the Tantivy schema and return both `Schema` and typed field handles.
 pub fn build_schema() -> (Schema, MetaFields) {
+    build_schema_with(SchemaOptions::default())
+}
+
+/// Like `build_schema`, with the optional parts chosen by `opts`.
+pub fn build_schema_with(opts: SchemaOptions) -> (Schema, MetaFields) {
     let mut builder = Schema::builder();
 
     let doc_key = builder.add_u64_field("doc_key", FAST | STORED);
     let volume = builder.add_u64_field("volume", FAST | STORED);
-    let name = builder.add_text_field("name", TEXT | STORED);
+    let name = if opts.code_names {
+        let indexing = TextFieldIndexing::default()
+            .set_tokenizer(CODE_ANALYZER)
+            .set_index_option(IndexRecordOption::WithFreqsAndPositions);
+        builder.add_text_field(
+            "name",
+            TextOptions::default()
+                .set_indexing_options(indexing)
+                .set_stored(),
+        )
+    } else {
+        builder.add_text_field("name", TEXT | STORED)
+    };
     let path = builder.add_text_field("path", TEXT | STORED);
     let ext = builder.add_text_field("ext", STRING | FAST);
     let size = builder.add_u64_field("size", FAST | STORED);
     let created = builder.add_i64_field("created", FAST | STORED);
     let modified = builder.add_i64_field("modified", FAST | STORED);
     let flags = builder.add_u64_field("flags", FAST | STORED);
+    let name_raw = builder.add_text_field("name_raw", STRING | FAST);
+    let kind = builder.add_text_field("kind", STRING | FAST);
+    let ancestors = builder.add_u64_field("ancestors", INDEXED | STORED);
+    let name_ngram = opts.name_ngrams.then(|| {
+        let indexing = TextFieldIndexing::default()
+            .set_tokenizer(ngram::NAME_NGRAM_TOKENIZER)
+            .set_index_option(IndexRecordOption::WithFreqsAndPositions);
+        builder.add_text_field(
+            NAME_NGRAM_FIELD,
+            TextOptions::default().set_indexing_options(indexing),
+        )
+    });
 
     let fields = MetaFields {
         doc_key,
@@ -57,11 +148,48 @@ pub fn build_schema() -> (Schema, MetaFields) {
         created,
         modified,
         flags,
+        name_raw,
+        kind,
+        ancestors,
+        name_ngram,
     };
 
     (builder.build(), fields)
 }
 
+/// Register the analyzers the schema refers to; needed whenever an index is
+/// created or opened.
+pub fn register_tokenizers(index: &Index) {
+    index.tokenizers().register(
+        ngram::NAME_NGRAM_TOKENIZER,
+        ngram::NameGramTokenizer::default(),
+    );
+    register_code_analyzers(index.tokenizers());
+}
+
+/// Bytes the segments of `searcher` spend on `field`: terms, postings,
+/// positions, norms and fast columns. Stored values are not split by field.
+pub fn field_bytes(searcher: &Searcher, field: Field) -> std::io::Result<u64> {
+    let usage = searcher.space_usage()?;
+    let mut bytes = 0;
+    for segment in usage.segments() {
+        for per_field in [
+            segment.termdict(),
+            segment.postings(),
+            segment.positions(),
+            segment.fieldnorms(),
+            segment.fast_fields(),
+        ] {
+            bytes += per_field
+                .fields()
+                .filter(|(f, _)| **f == field)
+                .map(|(_, usage)| usage.total().get_bytes())
+                .sum::<u64>();
+        }
+    }
+    Ok(bytes)
+}
+
 /// Lightweight document representation for ingest.
 #[derive(Debug, Clone)]
 pub struct MetaDoc {
@@ -74,6 +202,8 @@ pub struct MetaDoc {
     pub created: i64,
     pub modified: i64,
     pub flags: u64,
+    /// Ancestor folders, nearest first.
+    pub ancestors: Vec<DocKey>,
 }
 
 impl From<&CoreFileMeta> for MetaDoc {
@@ -88,6 +218,9 @@ impl From<&CoreFileMeta> for MetaDoc {
             created: f.created,
             modified: f.modified,
             flags: f.flags.bits() as u64,
+            // Only the direct parent is known from a single record; batch
+            // ingest fills in the full chain.
+            ancestors: f.parent.filter(|p| *p != f.key).into_iter().collect(),
         }
     }
 }
@@ -107,12 +240,24 @@ pub fn add_batch(
 }
 
 /// Add a batch of `core_types::FileMeta` records.
+///
+/// Ancestor chains are resolved through the parent links within the batch, so
+/// a whole-volume batch yields complete chains.
 pub fn add_file_meta_batch(
     writer: &mut IndexWriter,
     fields: &MetaFields,
     metas: impl IntoIterator<Item = CoreFileMeta>,
 ) -> Result<()> {
-    add_batch(writer, fields, metas.into_iter().map(|m| MetaDoc::from(&m)))
+    let metas: Vec<CoreFileMeta> = metas.into_iter().collect();
+    let chains = AncestorIndex::from_metas(&metas);
+    add_batch(
+        writer,
+        fields,
+        metas.iter().map(|m| MetaDoc {
+            ancestors: chains.ancestors(m.key, m.parent),
+            ..MetaDoc::from(m)
+        }),
+    )
 }
 
 /// Convenience handle bundling an index with its field set.
@@ -128,15 +273,62 @@ pub struct MetaIndex {
 /// “open or create” ergonomics without forcing the caller to probe the
 /// directory manually.
 pub fn open_or_create_index(path: &Path) -> Result<MetaIndex> {
-    let (schema, fields) = build_schema();
+    open_or_create_index_with(path, SchemaOptions::default())
+}
+
+/// Like `open_or_create_index`; `opts` only matter when the index is
+/// created; an existing index keeps the schema it was built with.
+pub fn open_or_create_index_with(path: &Path, opts: SchemaOptions) -> Result<MetaIndex> {
     let index = if path.join("meta.json").exists() {
         Index::open_in_dir(path)?
     } else {
-        Index::create_in_dir(path, schema)?
+        Index::create_in_dir(path, build_schema_with(opts).0)?
     };
+    register_tokenizers(&index);
+    let fields = MetaFields::for_index(&index)
+        .with_context(|| format!("opening metadata index at {}", path.display()))?;
     Ok(MetaIndex { index, fields })
 }
 
+/// Write the name FST (see `fst`) of every document `searcher` sees to
+/// `path`, ranked by modification time so recent files complete first. It
+/// is built from the fast columns, next to `path`, and renamed over it, so
+/// readers never open a half-written file.
+pub fn write_name_fst(searcher: &Searcher, fields: &MetaFields, path: &Path) -> Result<()> {
+    let schema = searcher.schema();
+    let (name_raw, doc_key, modified) = (
+        schema.get_field_name(fields.name_raw),
+        schema.get_field_name(fields.doc_key),
+        schema.get_field_name(fields.modified),
+    );
+    let mut entries = Vec::new();
+    let mut name = String::new();
+    for segment in searcher.segment_readers() {
+        let fast = segment.fast_fields();
+        let Some(names) = fast.str(name_raw)? else {
+            continue;
+        };
+        let keys = fast.u64(doc_key)?;
+        let times = fast.i64(modified)?;
+        for doc_id in segment.doc_ids_alive() {
+            let (Some(ord), Some(key)) = (names.ords().first(doc_id), keys.first(doc_id)) else {
+                continue;
+            };
+            name.clear();
+            names.ord_to_str(ord, &mut name)?;
+            let rank = times.first(doc_id).unwrap_or(0).max(0) as u64;
+            entries.push((name.clone(), DocKey(key), rank));
+        }
+    }
+    let tmp = path.with_extension("fst.tmp");
+    let mut builder = fst::FstBuilder::new(&tmp)?;
+    builder.insert_ranked(entries)?;
+    builder.finish()?;
+    std::fs::rename(&tmp, path)
+        .with_context(|| format!("replacing name FST at {}", path.display()))?;
+    Ok(())
+}
+
 /// Writer configuration used during initial builds and batch updates.
 #[derive(Debug, Clone)]
 pub struct WriterConfig {
@@ -174,6 +366,9 @@ pub fn to_document(doc: &MetaDoc, fields: &MetaFields) -> TantivyDocument {
     d.add_u64(fields.doc_key, doc.key.0);
     d.add_u64(fields.volume, doc.volume as u64);
     d.add_text(fields.name, &doc.name);
+    if let Some(name_ngram) = fields.name_ngram {
+        d.add_text(name_ngram, &doc.name);
+    }
     if let Some(path) = &doc.path {
         d.add_text(fields.path, path);
     }
@@ -184,6 +379,16 @@ pub fn to_document(doc: &MetaDoc, fields: &MetaFields) -> TantivyDocument {
     d.add_i64(fields.created, doc.created);
     d.add_i64(fields.modified, doc.modified);
     d.add_u64(fields.flags, doc.flags);
+    d.add_text(fields.name_raw, doc.name.to_lowercase());
+    let kind = if doc.flags & FileFlags::IS_DIR.bits() as u64 != 0 {
+        kind::FOLDER.to_string()
+    } else {
+        kind::classify(doc.ext.as_deref(), None)
+    };
+    d.add_text(fields.kind, kind);
+    for ancestor in &doc.ancestors {
+        d.add_u64(fields.ancestors, ancestor.0);
+    }
     d
 }
 
@@ -205,6 +410,7 @@ mod tests {
             created: 100,
             modified: 200,
             flags: 0b1010,
+            ancestors: vec![DocKey::from_parts(9, 7), DocKey::from_parts(9, 5)],
         };
 
         let tdoc = to_document(&doc, &fields);
@@ -215,6 +421,24 @@ mod tests {
         assert_eq!(get(fields.created).as_i64().unwrap(), doc.created);
         assert_eq!(get(fields.modified).as_i64().unwrap(), doc.modified);
         assert_eq!(get(fields.flags).as_u64().unwrap(), doc.flags);
+        assert_eq!(get(fields.name_raw).as_str().unwrap(), "sample.txt");
+        assert_eq!(get(fields.kind).as_str().unwrap(), kind::TEXT);
+        let ancestors: Vec<u64> = tdoc
+            .get_all(fields.ancestors)
+            .filter_map(|v| v.as_u64())
+            .collect();
+        assert_eq!(ancestors, vec![doc.ancestors[0].0, doc.ancestors[1].0]);
+
+        let dir = MetaDoc {
+            ext: None,
+            flags: FileFlags::IS_DIR.bits() as u64,
+            ..doc
+        };
+        let tdoc = to_document(&dir, &fields);
+        assert_eq!(
+            tdoc.get_first(fields.kind).unwrap().as_str().unwrap(),
+            kind::FOLDER
+        );
     }
 
     #[test]
@@ -222,6 +446,7 @@ mod tests {
         let dir = RamDirectory::create();
         let (schema, fields) = build_schema();
         let index = Index::create(dir, schema, IndexSettings::default())?;
+        register_tokenizers(&index);
         let mut writer = index.writer_with_num_threads(1, 50_000_000)?;
 
         let docs = vec![
@@ -235,6 +460,7 @@ mod tests {
                 created: 1_700_000_000,
                 modified: 1_700_000_100,
                 flags: 0,
+                ancestors: Vec::new(),
             },
             MetaDoc {
                 key: DocKey::from_parts(2, 20),
@@ -246,6 +472,7 @@ mod tests {
                 created: 1_700_000_200,
                 modified: 1_700_000_300,
                 flags: 0,
+                ancestors: Vec::new(),
             },
         ];
 
@@ -267,4 +494,109 @@ mod tests {
         assert!(doc_key == docs[0].key.0 || doc_key == docs[1].key.0);
         Ok(())
     }
+
+    #[test]
+    fn name_ngrams_are_fixed_when_the_index_is_created() -> Result<()> {
+        let doc = MetaDoc {
+            key: DocKey::from_parts(1, 10),
+            volume: 1,
+            name: "report.docx".into(),
+            path: None,
+            ext: Some("docx".into()),
+            size: 1,
+            created: 0,
+            modified: 0,
+            flags: 0,
+            ancestors: Vec::new(),
+        };
+        let gram_bytes = |meta: &MetaIndex| -> Result<Option<u64>> {
+            let mut writer = meta.index.writer_with_num_threads(1, 15_000_000)?;
+            add_batch(&mut writer, &meta.fields, [doc.clone()])?;
+            writer.commit()?;
+            let searcher = meta.index.reader()?.searcher();
+            Ok(match meta.fields.name_ngram {
+                Some(field) => Some(field_bytes(&searcher, field)?),
+                None => None,
+            })
+        };
+
+        let plain = tempfile::tempdir()?;
+        let no_grams = SchemaOptions {
+            name_ngrams: false,
+            ..SchemaOptions::default()
+        };
+        let meta = open_or_create_index_with(plain.path(), no_grams)?;
+        assert_eq!(gram_bytes(&meta)?, None);
+        // Reopening with n-grams enabled keeps the existing schema.
+        let meta = open_or_create_index_with(plain.path(), SchemaOptions::default())?;
+        assert_eq!(gram_bytes(&meta)?, None);
+
+        let grams = tempfile::tempdir()?;
+        let meta = open_or_create_index_with(grams.path(), SchemaOptions::default())?;
+        assert!(gram_bytes(&meta)?.is_some_and(|bytes| bytes > 0));
+        Ok(())
+    }
+
+    #[test]
+    fn code_names_split_identifiers() -> Result<()> {
+        let opts = SchemaOptions {
+            code_names: true,
+            ..SchemaOptions::default()
+        };
+        let (schema, fields) = build_schema_with(opts);
+        let index = Index::create_in_ram(schema);
+        register_tokenizers(&index);
+        let mut writer = index.writer_with_num_threads(1, 15_000_000)?;
+        let names = [
+            "parseHttpRequest.ts",
+            "parse_http_request.py",
+            "request_http.go",
+        ];
+        let docs = names.iter().enumerate().map(|(i, name)| MetaDoc {
+            key: DocKey::from_parts(1, i as u64 + 1),
+            volume: 1,
+            name: name.to_string(),
+            path: None,
+            ext: None,
+            size: 0,
+            created: 0,
+            modified: 0,
+            flags: 0,
+            ancestors: Vec::new(),
+        });
+        add_batch(&mut writer, &fields, docs)?;
+        writer.commit()?;
+
+        let searcher = index.reader()?.searcher();
+        let parser = tantivy::query::QueryParser::for_index(&index, vec![fields.name]);
+        let count = |q: &str| -> Result<usize> {
+            Ok(searcher.search(&parser.parse_query(q)?, &tantivy::collector::Count)?)
+        };
+        assert_eq!(count("\"http request\"")?, 2);
+        assert_eq!(count("parsehttprequest")?, 2);
+        assert_eq!(count("+http +request")?, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn outdated_schemas_need_a_rebuild() -> Result<()> {
+        // The schema of the first release, before `name_raw`, `kind`, etc.
+        let mut builder = Schema::builder();
+        builder.add_u64_field("doc_key", FAST | STORED);
+        builder.add_u64_field("volume", FAST | STORED);
+        builder.add_text_field("name", TEXT | STORED);
+        builder.add_text_field("path", TEXT | STORED);
+        builder.add_text_field("ext", STRING | FAST);
+        builder.add_u64_field("size", FAST | STORED);
+        builder.add_i64_field("created", FAST | STORED);
+        builder.add_i64_field("modified", FAST | STORED);
+        builder.add_u64_field("flags", FAST | STORED);
+        let dir = tempfile::tempdir()?;
+        Index::create_in_dir(dir.path(), builder.build())?;
+
+        let err = open_or_create_index(dir.path()).unwrap_err();
+        assert!(format!("{err:#}").contains("rebuild required"), "{err:#}");
+        assert!(err.chain().any(|e| e.is::<SchemaMismatch>()));
+        Ok(())
+    }
 }
@@ -0,0 +1,148 @@
+//! Character trigrams of file names, for substring matching.
+//!
+//! The word-tokenized `name` field cannot match `port` inside `report.docx`
+//! without scanning every term. The optional `name_ngram` field indexes each
+//! run of `GRAM_CHARS` characters of the lowercased name at its character
+//! offset, so a needle of that length or more is found as a phrase of its own
+//! trigrams. Shorter needles produce no grams and need another strategy.
+
+use tantivy::tokenizer::{Token, TokenStream, Tokenizer};
+
+/// Name under which `NameGramTokenizer` is registered on metadata indexes.
+pub const NAME_NGRAM_TOKENIZER: &str = "name_ngram";
+
+/// Characters per gram.
+pub const GRAM_CHARS: usize = 3;
+
+/// Emits the lowercased trigrams of a name, each at its character offset.
+#[derive(Clone, Default)]
+pub struct NameGramTokenizer {
+    token: Token,
+}
+
+pub struct NameGramStream<'a> {
+    token: &'a mut Token,
+    text: String,
+    /// Byte offset of every char of `text`, then its length.
+    bounds: Vec<usize>,
+    next: usize,
+}
+
+impl Tokenizer for NameGramTokenizer {
+    type TokenStream<'a> = NameGramStream<'a>;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> NameGramStream<'a> {
+        let text = text.to_lowercase();
+        let mut bounds: Vec<usize> = text.char_indices().map(|(i, _)| i).collect();
+        bounds.push(text.len());
+        self.token.reset();
+        NameGramStream {
+            token: &mut self.token,
+            text,
+            bounds,
+            next: 0,
+        }
+    }
+}
+
+impl TokenStream for NameGramStream<'_> {
+    fn advance(&mut self) -> bool {
+        let Some(&to) = self.bounds.get(self.next + GRAM_CHARS) else {
+            return false;
+        };
+        let from = self.bounds[self.next];
+        self.token.text.clear();
+        self.token.text.push_str(&self.text[from..to]);
+        self.token.offset_from = from;
+        self.token.offset_to = to;
+        self.token.position = self.next;
+        self.token.position_length = 1;
+        self.next += 1;
+        true
+    }
+
+    fn token(&self) -> &Token {
+        self.token
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        self.token
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MetaDoc, build_schema, register_tokenizers, to_document};
+    use core_types::DocKey;
+    use tantivy::collector::Count;
+    use tantivy::query::{PhraseQuery, Query, TermQuery};
+    use tantivy::schema::IndexRecordOption;
+    use tantivy::{Index, Term};
+
+    fn grams(text: &str) -> Vec<(String, usize)> {
+        let mut tokenizer = NameGramTokenizer::default();
+        let mut stream = tokenizer.token_stream(text);
+        let mut out = Vec::new();
+        while stream.advance() {
+            out.push((stream.token().text.clone(), stream.token().position));
+        }
+        out
+    }
+
+    #[test]
+    fn trigrams_are_lowercased_and_positioned() {
+        let expected: Vec<(String, usize)> = ["re.", "e.p", ".pö", "pör", "ört"]
+            .iter()
+            .enumerate()
+            .map(|(i, g)| (g.to_string(), i))
+            .collect();
+        assert_eq!(grams("Re.PÖRT"), expected);
+        assert_eq!(grams("abc"), [("abc".to_string(), 0)]);
+        assert!(grams("ab").is_empty());
+    }
+
+    #[test]
+    fn gram_phrases_match_substrings_only() {
+        let (schema, fields) = build_schema();
+        let field = fields.name_ngram.unwrap();
+        let index = Index::create_in_ram(schema);
+        register_tokenizers(&index);
+        let mut writer = index.writer_with_num_threads(1, 15_000_000).unwrap();
+        for (i, name) in ["Report.docx", "portal.txt", "trop.md"].iter().enumerate() {
+            let doc = MetaDoc {
+                key: DocKey::from_parts(1, i as u64 + 1),
+                volume: 1,
+                name: name.to_string(),
+                path: None,
+                ext: None,
+                size: 0,
+                created: 0,
+                modified: 0,
+                flags: 0,
+                ancestors: Vec::new(),
+            };
+            writer.add_document(to_document(&doc, &fields)).unwrap();
+        }
+        writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+
+        let count = |needle: &str| {
+            let terms: Vec<Term> = grams(needle)
+                .into_iter()
+                .map(|(g, _)| Term::from_field_text(field, &g))
+                .collect();
+            let query: Box<dyn Query> = match terms.len() {
+                1 => Box::new(TermQuery::new(terms[0].clone(), IndexRecordOption::Basic)),
+                _ => Box::new(PhraseQuery::new(terms)),
+            };
+            searcher.search(&query, &Count).unwrap()
+        };
+        assert_eq!(count("port"), 2);
+        assert_eq!(count("EPORT.D"), 1);
+        assert_eq!(count("rop"), 1);
+        // All grams must occur in one name, one after the other.
+        assert_eq!(count("prot"), 0);
+        assert_eq!(count("ortal.trop"), 0);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::{MetaDoc, MetaFields, MetaIndex, build_schema, to_document};
+use crate::{MetaDoc, MetaFields, MetaIndex, build_schema, register_tokenizers, to_document};
 use anyhow::Result;
 use std::path::Path;
 use tantivy::collector::TopDocs;
@@ -18,20 +18,27 @@ pub struct TieredMetaIndex {
 impl TieredMetaIndex {
     pub fn new(cold_path: &Path) -> Result<Self> {
         // 1. Open/Create Cold Index (Disk)
-        let (schema, fields) = build_schema();
         let cold_index = if cold_path.join("meta.json").exists() {
             Index::open_in_dir(cold_path)?
         } else {
-            Index::create_in_dir(cold_path, schema.clone())?
+            Index::create_in_dir(cold_path, build_schema().0)?
         };
+        register_tokenizers(&cold_index);
+        let fields = MetaFields::for_index(&cold_index)?;
         let cold = MetaIndex {
             index: cold_index,
             fields: fields.clone(),
         };
 
-        // 2. Create Delta Index (RAM)
+        // 2. Create Delta Index (RAM) with the cold schema, so compaction can
+        // copy documents across as they are.
         let ram_dir = tantivy::directory::RamDirectory::create();
-        let delta_index = Index::create(ram_dir, schema, tantivy::IndexSettings::default())?;
+        let delta_index = Index::create(
+            ram_dir,
+            cold.index.schema(),
+            tantivy::IndexSettings::default(),
+        )?;
+        register_tokenizers(&delta_index);
         let delta = MetaIndex {
             index: delta_index,
             fields,
@@ -140,6 +147,7 @@ pub fn doc_to_meta(doc: &TantivyDocument, fields: &MetaFields) -> Option<MetaDoc
     let mut modified = None;
     let mut flags = None;
     let mut volume = None;
+    let mut ancestors = Vec::new();
 
     for (field, value) in doc.iter_fields_and_values() {
         match field {
@@ -152,6 +160,9 @@ pub fn doc_to_meta(doc: &TantivyDocument, fields: &MetaFields) -> Option<MetaDoc
             f if f == fields.created => created = value.as_i64(),
             f if f == fields.modified => modified = value.as_i64(),
             f if f == fields.flags => flags = value.as_u64(),
+            f if f == fields.ancestors => {
+                ancestors.extend(value.as_u64().map(core_types::DocKey));
+            }
             _ => {}
         }
     }
@@ -169,6 +180,7 @@ pub fn doc_to_meta(doc: &TantivyDocument, fields: &MetaFields) -> Option<MetaDoc
             created: c,
             modified: m,
             flags: f,
+            ancestors,
         })
     } else {
         None
@@ -5,6 +5,7 @@ use std::{
 };
 
 use anyhow::Result;
+use core_types::SchemaMismatch;
 use core_types::config::AppConfig;
 use ipc::VolumeStatus;
 use tokio::sync::mpsc;
@@ -67,6 +68,7 @@ pub fn run_app_with_options(
     let mut cfg_owned = cfg.clone();
     super::ensure_default_volumes(&mut cfg_owned)?;
     ensure_data_paths_exist(&cfg_owned)?;
+    move_aside_stale_indexes(&cfg_owned)?;
 
     match opts.initial_metas {
         Some(metas) => ingest_seed_metadata(&cfg_owned, metas, &mut pending_jobs)?,
@@ -112,6 +114,9 @@ pub fn run_app_with_options(
     loop {
         match crate::search_handler::UnifiedSearchHandler::try_new(meta_path, content_path) {
             Ok(handler) => {
+                let handler = handler
+                    .with_ranking(cfg_owned.search.clone())
+                    .with_result_cache(cfg_owned.search.result_cache_entries);
                 set_search_handler(Box::new(handler));
                 break;
             }
@@ -193,6 +198,43 @@ fn ensure_data_paths_exist(cfg: &AppConfig) -> Result<()> {
     Ok(())
 }
 
+/// Move indexes created with an older schema aside and start them empty;
+/// the startup scan and the content jobs it queues fill them again.
+fn move_aside_stale_indexes(cfg: &AppConfig) -> Result<()> {
+    let meta_path = Path::new(&cfg.paths.meta_index);
+    reset_if_stale(
+        meta_path,
+        crate::meta_ingest::open_meta_index(meta_path).map(drop),
+    )?;
+    let content_path = Path::new(&cfg.paths.content_index);
+    reset_if_stale(
+        content_path,
+        content_index::open_or_create(content_path).map(drop),
+    )?;
+    Ok(())
+}
+
+/// If opening the index at `path` failed with a `SchemaMismatch`, move it
+/// to `<path>.stale` (replacing an older one) and leave an empty directory.
+/// Returns whether it did.
+fn reset_if_stale(path: &Path, opened: Result<()>) -> Result<bool> {
+    let Err(err) = opened else {
+        return Ok(false);
+    };
+    if !err.chain().any(|e| e.is::<SchemaMismatch>()) {
+        // Other failures surface where the index is used.
+        return Ok(false);
+    }
+    let stale = path.with_extension("stale");
+    if stale.exists() {
+        std::fs::remove_dir_all(&stale)?;
+    }
+    std::fs::rename(path, &stale)?;
+    std::fs::create_dir_all(path)?;
+    tracing::warn!("{err:#}; moved it to {:?} and rebuilding", stale);
+    Ok(true)
+}
+
 fn ingest_seed_metadata(
     cfg: &AppConfig,
     metas: Vec<core_types::FileMeta>,
@@ -244,3 +286,29 @@ fn unix_timestamp_secs() -> i64 {
         .map(|d| d.as_secs() as i64)
         .unwrap_or(0)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tantivy::Index;
+    use tantivy::schema::{FAST, STORED, Schema};
+
+    #[test]
+    fn stale_indexes_are_moved_aside() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("content");
+        std::fs::create_dir_all(&path).unwrap();
+        let mut builder = Schema::builder();
+        builder.add_u64_field("doc_key", FAST | STORED);
+        Index::create_in_dir(&path, builder.build()).unwrap();
+
+        let opened = content_index::open_or_create(&path).map(drop);
+        assert!(reset_if_stale(&path, opened).unwrap());
+        assert!(path.with_extension("stale").join("meta.json").exists());
+
+        // The empty directory opens as a current index and is kept.
+        let opened = content_index::open_or_create(&path).map(drop);
+        assert!(!reset_if_stale(&path, opened).unwrap());
+        assert!(path.join("meta.json").exists());
+    }
+}
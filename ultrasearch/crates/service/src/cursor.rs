@@ -0,0 +1,203 @@
+//! Opaque pagination cursors and the searcher generations they pin.
+//!
+//! A cursor records the index generations a search ran against and the
+//! position of the last hit it returned. The next page reruns the query on
+//! those same searchers and collects strictly after that position, so
+//! commits between pages neither shift nor repeat results. Searchers stay
+//! leased for a short while after their last page; an expired cursor has to
+//! restart the search.
+
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result, bail};
+use ipc::{SearchMode, SearchRequest};
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+use tantivy::Searcher;
+
+use crate::sort_collector::Position;
+
+/// How long a searcher stays pinned after the last page that used it.
+pub(crate) const LEASE: Duration = Duration::from_secs(120);
+/// Generations pinned per index; the least recently used is dropped first.
+const MAX_LEASES: usize = 8;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Cursor {
+    /// Hash of the query, mode and sort the cursor was issued for.
+    fingerprint: u64,
+    pub(crate) meta_generation: u64,
+    pub(crate) content_generation: Option<u64>,
+    /// Last hit of the previous page.
+    pub(crate) after: Position,
+    /// Hits returned by all previous pages.
+    pub(crate) served: u64,
+    /// Fused-list tier a hybrid search is paging through.
+    pub(crate) fusion: Option<Fusion>,
+}
+
+/// Where in its fused ranking a hybrid search is; see `search_hybrid`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct Fusion {
+    /// Hits read from each index; fused scores come from ranks in these.
+    pub(crate) depth: u64,
+    /// Files either index ranks above this were served by earlier tiers.
+    pub(crate) skip: u64,
+    /// Hits served before this tier.
+    pub(crate) tier_start: u64,
+}
+
+impl Cursor {
+    pub(crate) fn new(
+        req: &SearchRequest,
+        mode: SearchMode,
+        meta: &Searcher,
+        content: Option<&Searcher>,
+        after: Position,
+        served: u64,
+        fusion: Option<Fusion>,
+    ) -> Self {
+        Self {
+            fingerprint: fingerprint(req, mode),
+            meta_generation: meta.generation().generation_id(),
+            content_generation: content.map(|s| s.generation().generation_id()),
+            after,
+            served,
+            fusion,
+        }
+    }
+
+    pub(crate) fn encode(&self) -> String {
+        let bytes = bincode::serialize(self).unwrap_or_default();
+        bytes.iter().map(|b| format!("{b:02x}")).collect()
+    }
+
+    /// Parse `text` and check it was issued for this request.
+    pub(crate) fn decode(text: &str, req: &SearchRequest, mode: SearchMode) -> Result<Self> {
+        let bytes = (0..text.len())
+            .step_by(2)
+            .map(|i| {
+                text.get(i..i + 2)
+                    .and_then(|pair| u8::from_str_radix(pair, 16).ok())
+            })
+            .collect::<Option<Vec<u8>>>()
+            .context("invalid cursor")?;
+        let cursor: Cursor = bincode::deserialize(&bytes).context("invalid cursor")?;
+        if cursor.fingerprint != fingerprint(req, mode) {
+            bail!("cursor was issued for a different query, mode or sort");
+        }
+        Ok(cursor)
+    }
+}
+
+fn fingerprint(req: &SearchRequest, mode: SearchMode) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    bincode::serialize(&(&req.query, mode, req.sort))
+        .unwrap_or_default()
+        .hash(&mut hasher);
+    hasher.finish()
+}
+
+struct Lease {
+    searcher: Searcher,
+    expires: Instant,
+}
+
+/// Recently paged searchers of one index, by generation id.
+pub(crate) struct SearcherLeases {
+    ttl: Duration,
+    held: Mutex<Vec<Lease>>,
+}
+
+impl SearcherLeases {
+    pub(crate) fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            held: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Pin `searcher` (or renew its lease) so a cursor can return to it.
+    pub(crate) fn hold(&self, searcher: &Searcher) {
+        let id = searcher.generation().generation_id();
+        let expires = Instant::now() + self.ttl;
+        let mut held = self.held.lock();
+        held.retain(|l| l.searcher.generation().generation_id() != id);
+        held.push(Lease {
+            searcher: searcher.clone(),
+            expires,
+        });
+        if held.len() > MAX_LEASES {
+            held.sort_by_key(|l| std::cmp::Reverse(l.expires));
+            held.truncate(MAX_LEASES);
+        }
+    }
+
+    /// The pinned searcher for `generation`, unless its lease ran out.
+    pub(crate) fn get(&self, generation: u64) -> Option<Searcher> {
+        let now = Instant::now();
+        let mut held = self.held.lock();
+        held.retain(|l| l.expires > now);
+        held.iter()
+            .find(|l| l.searcher.generation().generation_id() == generation)
+            .map(|l| l.searcher.clone())
+    }
+}
+
+impl Default for SearcherLeases {
+    fn default() -> Self {
+        Self::new(LEASE)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core_types::DocKey;
+    use ipc::QueryExpr;
+    use tantivy::Index;
+    use tantivy::schema::Schema;
+
+    #[test]
+    fn cursor_roundtrips_and_leases_expire() {
+        let index = Index::create_in_ram(Schema::builder().build());
+        let searcher = index.reader().unwrap().searcher();
+        let req = SearchRequest::with_query(QueryExpr::default());
+        let cursor = Cursor::new(
+            &req,
+            SearchMode::NameOnly,
+            &searcher,
+            None,
+            Position {
+                value: None,
+                score: 1.5,
+                key: DocKey::from_parts(1, 7),
+            },
+            10,
+            Some(Fusion {
+                depth: 20,
+                skip: 10,
+                tier_start: 8,
+            }),
+        );
+
+        let text = cursor.encode();
+        assert_eq!(
+            Cursor::decode(&text, &req, SearchMode::NameOnly).unwrap(),
+            cursor
+        );
+        assert!(Cursor::decode(&text, &req, SearchMode::Content).is_err());
+        assert!(Cursor::decode("zz", &req, SearchMode::NameOnly).is_err());
+
+        let generation = searcher.generation().generation_id();
+        let leases = SearcherLeases::default();
+        assert!(leases.get(generation).is_none());
+        leases.hold(&searcher);
+        assert!(leases.get(generation).is_some());
+
+        let expired = SearcherLeases::new(Duration::ZERO);
+        expired.hold(&searcher);
+        assert!(expired.get(generation).is_none());
+    }
+}
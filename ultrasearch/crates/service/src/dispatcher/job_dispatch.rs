@@ -16,6 +16,9 @@ pub struct JobSpec {
     pub max_chars: Option<usize>,
     #[serde(default)]
     pub file_size: u64,
+    /// Packed `DocKey`s of the ancestor folders, nearest first.
+    #[serde(default)]
+    pub ancestors: Vec<u64>,
 }
 
 #[derive(Debug, Serialize)]
@@ -0,0 +1,362 @@
+//! Facet counts over every match of a search, gathered by a collector that
+//! runs in the same pass as the top hits.
+//!
+//! Both indexes expose `doc_key`, `ext`, `volume`, `size` and `modified` as
+//! fast fields, so one collector serves either. Hybrid searches count the
+//! metadata matches first and let the content pass skip their keys, so a file
+//! matching both indexes is counted once.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Arc;
+
+use ipc::{FacetBucket, FacetField, FacetRequest, FacetResult};
+use tantivy::collector::{Collector, SegmentCollector};
+use tantivy::columnar::StrColumn;
+use tantivy::fastfield::Column;
+use tantivy::{DocId, Score, SegmentOrdinal, SegmentReader};
+
+/// Size classes with their exclusive upper bounds (Explorer's buckets).
+const SIZE_CLASSES: [(&str, u64); 7] = [
+    ("empty", 1),
+    ("tiny", 16 << 10),
+    ("small", 1 << 20),
+    ("medium", 128 << 20),
+    ("large", 1 << 30),
+    ("huge", 4 << 30),
+    ("gigantic", u64::MAX),
+];
+
+/// Collects `FacetCounts` for the requested fields.
+#[derive(Debug, Clone)]
+pub(crate) struct FacetCollector {
+    fields: Vec<FacetField>,
+    /// Documents already counted by another pass.
+    skip: Option<Arc<HashSet<u64>>>,
+    /// Record the key of every counted document in `FacetCounts::keys`.
+    keep_keys: bool,
+}
+
+impl FacetCollector {
+    pub(crate) fn new(req: &FacetRequest) -> Self {
+        Self {
+            fields: req.fields.clone(),
+            skip: None,
+            keep_keys: false,
+        }
+    }
+
+    pub(crate) fn keeping_keys(mut self) -> Self {
+        self.keep_keys = true;
+        self
+    }
+
+    pub(crate) fn skipping(mut self, keys: Arc<HashSet<u64>>) -> Self {
+        self.skip = Some(keys);
+        self
+    }
+
+    fn wants(&self, field: FacetField) -> bool {
+        self.fields.contains(&field)
+    }
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct FacetCounts {
+    ext: HashMap<String, u64>,
+    volume: HashMap<u64, u64>,
+    size: [u64; SIZE_CLASSES.len()],
+    /// Keyed by months since year 0 (`year * 12 + month - 1`).
+    modified: BTreeMap<i64, u64>,
+    /// Keys of the counted documents, when the collector keeps them.
+    pub(crate) keys: Vec<u64>,
+}
+
+impl FacetCounts {
+    pub(crate) fn merge(&mut self, other: FacetCounts) {
+        for (ext, n) in other.ext {
+            *self.ext.entry(ext).or_default() += n;
+        }
+        for (vol, n) in other.volume {
+            *self.volume.entry(vol).or_default() += n;
+        }
+        for (mine, theirs) in self.size.iter_mut().zip(other.size) {
+            *mine += theirs;
+        }
+        for (month, n) in other.modified {
+            *self.modified.entry(month).or_default() += n;
+        }
+        self.keys.extend(other.keys);
+    }
+
+    /// Render the counts in the order and shape `req` asked for.
+    pub(crate) fn into_results(self, req: &FacetRequest) -> Vec<FacetResult> {
+        req.fields
+            .iter()
+            .map(|&field| {
+                let buckets = match field {
+                    FacetField::Ext => by_count(
+                        self.ext.iter().map(|(ext, &n)| {
+                            let filter = (!ext.is_empty()).then(|| format!("ext:{}", quoted(ext)));
+                            bucket(ext.clone(), n, filter)
+                        }),
+                        req.max_buckets,
+                    ),
+                    FacetField::Volume => by_count(
+                        self.volume.iter().map(|(vol, &n)| {
+                            bucket(vol.to_string(), n, Some(format!("vol:{vol}")))
+                        }),
+                        req.max_buckets,
+                    ),
+                    FacetField::Size => SIZE_CLASSES
+                        .iter()
+                        .enumerate()
+                        .filter(|&(i, _)| self.size[i] > 0)
+                        .map(|(i, (name, _))| {
+                            bucket(name.to_string(), self.size[i], Some(size_filter(i)))
+                        })
+                        .collect(),
+                    FacetField::Modified => self
+                        .modified
+                        .iter()
+                        .map(|(&month, &n)| {
+                            let label = format!(
+                                "{:04}-{:02}",
+                                month.div_euclid(12),
+                                month.rem_euclid(12) + 1
+                            );
+                            let filter = format!("modified:{label}");
+                            bucket(label, n, Some(filter))
+                        })
+                        .collect(),
+                };
+                FacetResult { field, buckets }
+            })
+            .collect()
+    }
+}
+
+fn bucket(value: String, count: u64, filter: Option<String>) -> FacetBucket {
+    FacetBucket {
+        value,
+        count,
+        filter,
+    }
+}
+
+/// Largest first, ties by value; keep at most `max` (0 = all).
+fn by_count(buckets: impl Iterator<Item = FacetBucket>, max: u32) -> Vec<FacetBucket> {
+    let mut out: Vec<FacetBucket> = buckets.collect();
+    out.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.value.cmp(&b.value)));
+    if max > 0 {
+        out.truncate(max as usize);
+    }
+    out
+}
+
+fn quoted(value: &str) -> String {
+    if value
+        .chars()
+        .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'))
+    {
+        value.to_string()
+    } else {
+        format!("\"{}\"", value.replace('"', ""))
+    }
+}
+
+fn size_class(size: u64) -> usize {
+    SIZE_CLASSES
+        .partition_point(|&(_, hi)| size >= hi)
+        .min(SIZE_CLASSES.len() - 1)
+}
+
+/// Query clauses selecting size class `i`.
+fn size_filter(i: usize) -> String {
+    let lo = if i == 0 { 0 } else { SIZE_CLASSES[i - 1].1 };
+    let hi = SIZE_CLASSES[i].1;
+    match i {
+        0 => "size:0".to_string(),
+        _ if i == SIZE_CLASSES.len() - 1 => format!("size:>={}", size_text(lo)),
+        _ => format!("size:>={} size:<{}", size_text(lo), size_text(hi)),
+    }
+}
+
+fn size_text(bytes: u64) -> String {
+    for (unit, shift) in [("gb", 30), ("mb", 20), ("kb", 10)] {
+        if bytes >= 1 << shift && bytes.is_multiple_of(1 << shift) {
+            return format!("{}{unit}", bytes >> shift);
+        }
+    }
+    bytes.to_string()
+}
+
+/// Months since year 0 of a Unix timestamp (UTC).
+fn month_key(ts: i64) -> i64 {
+    // Hinnant's civil_from_days.
+    let z = ts.div_euclid(86_400) + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    year * 12 + month - 1
+}
+
+pub(crate) struct FacetSegmentCollector {
+    doc_key: Option<Column<u64>>,
+    /// `Some` when ext is requested; the inner `None` means no doc in this
+    /// segment has an extension.
+    ext: Option<Option<StrColumn>>,
+    volume: Option<Column<u64>>,
+    size: Option<Column<u64>>,
+    modified: Option<Column<i64>>,
+    skip: Option<Arc<HashSet<u64>>>,
+    keep_keys: bool,
+    /// Per-segment term ordinal counts, resolved to strings at harvest.
+    ext_ords: HashMap<u64, u64>,
+    no_ext: u64,
+    counts: FacetCounts,
+}
+
+impl SegmentCollector for FacetSegmentCollector {
+    type Fruit = tantivy::Result<FacetCounts>;
+
+    fn collect(&mut self, doc: DocId, _score: Score) {
+        let key = self.doc_key.as_ref().and_then(|col| col.first(doc));
+        if let (Some(skip), Some(key)) = (&self.skip, key)
+            && skip.contains(&key)
+        {
+            return;
+        }
+        if self.keep_keys
+            && let Some(key) = key
+        {
+            self.counts.keys.push(key);
+        }
+
+        if let Some(ext) = &self.ext {
+            match ext.as_ref().and_then(|col| col.term_ords(doc).next()) {
+                Some(ord) => *self.ext_ords.entry(ord).or_default() += 1,
+                None => self.no_ext += 1,
+            }
+        }
+        if let Some(vol) = self.volume.as_ref().and_then(|col| col.first(doc)) {
+            *self.counts.volume.entry(vol).or_default() += 1;
+        }
+        if let Some(size) = self.size.as_ref().and_then(|col| col.first(doc)) {
+            self.counts.size[size_class(size)] += 1;
+        }
+        if let Some(ts) = self.modified.as_ref().and_then(|col| col.first(doc)) {
+            *self.counts.modified.entry(month_key(ts)).or_default() += 1;
+        }
+    }
+
+    fn harvest(mut self) -> Self::Fruit {
+        if let Some(Some(col)) = &self.ext {
+            let mut buf = String::new();
+            for (ord, n) in self.ext_ords {
+                buf.clear();
+                col.ord_to_str(ord, &mut buf)?;
+                *self.counts.ext.entry(buf.to_lowercase()).or_default() += n;
+            }
+        }
+        if self.no_ext > 0 {
+            *self.counts.ext.entry(String::new()).or_default() += self.no_ext;
+        }
+        Ok(self.counts)
+    }
+}
+
+impl Collector for FacetCollector {
+    type Fruit = FacetCounts;
+    type Child = FacetSegmentCollector;
+
+    fn for_segment(
+        &self,
+        _segment_ord: SegmentOrdinal,
+        segment: &SegmentReader,
+    ) -> tantivy::Result<Self::Child> {
+        let fast = segment.fast_fields();
+        let needs_key = self.keep_keys || self.skip.is_some();
+        Ok(FacetSegmentCollector {
+            doc_key: needs_key.then(|| fast.u64("doc_key")).transpose()?,
+            ext: self
+                .wants(FacetField::Ext)
+                .then(|| fast.str("ext"))
+                .transpose()?,
+            volume: self
+                .wants(FacetField::Volume)
+                .then(|| fast.u64("volume"))
+                .transpose()?,
+            size: self
+                .wants(FacetField::Size)
+                .then(|| fast.u64("size"))
+                .transpose()?,
+            modified: self
+                .wants(FacetField::Modified)
+                .then(|| fast.i64("modified"))
+                .transpose()?,
+            skip: self.skip.clone(),
+            keep_keys: self.keep_keys,
+            ext_ords: HashMap::new(),
+            no_ext: 0,
+            counts: FacetCounts::default(),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        false
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<tantivy::Result<FacetCounts>>,
+    ) -> tantivy::Result<FacetCounts> {
+        let mut out = FacetCounts::default();
+        for fruit in segment_fruits {
+            out.merge(fruit?);
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buckets_sizes_and_months() {
+        assert_eq!(size_class(0), 0);
+        assert_eq!(size_class(1), 1);
+        assert_eq!(size_class((16 << 10) - 1), 1);
+        assert_eq!(size_class(16 << 10), 2);
+        assert_eq!(size_class(5 << 30), 6);
+        assert_eq!(size_class(u64::MAX), 6);
+        assert_eq!(size_filter(0), "size:0");
+        assert_eq!(size_filter(1), "size:>=1 size:<16kb");
+        assert_eq!(size_filter(3), "size:>=1mb size:<128mb");
+        assert_eq!(size_filter(6), "size:>=4gb");
+
+        let mut counts = FacetCounts::default();
+        // 2024-02-29T23:59:59Z, 2024-03-01T00:00:00Z, 1969-12-31T23:59:59Z
+        for ts in [1_709_251_199, 1_709_251_200, 1_709_251_200, -1] {
+            *counts.modified.entry(month_key(ts)).or_default() += 1;
+        }
+        let req = FacetRequest::new([FacetField::Modified]);
+        let months: Vec<(String, u64)> = counts.into_results(&req)[0]
+            .buckets
+            .iter()
+            .map(|b| (b.value.clone(), b.count))
+            .collect();
+        assert_eq!(
+            months,
+            [
+                ("1969-12".to_string(), 1),
+                ("2024-02".to_string(), 1),
+                ("2024-03".to_string(), 2)
+            ]
+        );
+    }
+}
@@ -0,0 +1,116 @@
+//! Bitmask filter over a `u64` fast field (the metadata `flags` column).
+//!
+//! Tantivy only offers equality and range queries on fast fields, which
+//! cannot express "bit N is set", so this scans the column per segment.
+
+use tantivy::query::{ConstScorer, EnableScoring, Explanation, Query, Scorer, Weight};
+use tantivy::schema::Field;
+use tantivy::{DocId, DocSet, Score, SegmentReader, TERMINATED, TantivyError};
+
+/// Matches documents where any bit of `mask` is set (or, for
+/// [`FlagsQuery::none`], where none of them are).
+#[derive(Debug, Clone)]
+pub(crate) struct FlagsQuery {
+    field: Field,
+    mask: u64,
+    set: bool,
+}
+
+impl FlagsQuery {
+    pub(crate) fn any(field: Field, mask: u64) -> Self {
+        Self {
+            field,
+            mask,
+            set: true,
+        }
+    }
+
+    pub(crate) fn none(field: Field, mask: u64) -> Self {
+        Self {
+            field,
+            mask,
+            set: false,
+        }
+    }
+}
+
+impl Query for FlagsQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> tantivy::Result<Box<dyn Weight>> {
+        let field_name = enable_scoring
+            .schema()
+            .get_field_name(self.field)
+            .to_string();
+        Ok(Box::new(FlagsWeight {
+            field_name,
+            mask: self.mask,
+            set: self.set,
+        }))
+    }
+}
+
+struct FlagsWeight {
+    field_name: String,
+    mask: u64,
+    set: bool,
+}
+
+impl FlagsWeight {
+    fn matching_docs(&self, reader: &SegmentReader) -> tantivy::Result<Vec<DocId>> {
+        let column = reader.fast_fields().u64(&self.field_name)?;
+        Ok((0..reader.max_doc())
+            .filter(|&doc| {
+                column
+                    .first(doc)
+                    .is_some_and(|v| (v & self.mask != 0) == self.set)
+            })
+            .collect())
+    }
+}
+
+impl Weight for FlagsWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> tantivy::Result<Box<dyn Scorer>> {
+        let docs = self.matching_docs(reader)?;
+        Ok(Box::new(ConstScorer::new(SortedDocs::new(docs), boost)))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> tantivy::Result<Explanation> {
+        let column = reader.fast_fields().u64(&self.field_name)?;
+        if column
+            .first(doc)
+            .is_some_and(|v| (v & self.mask != 0) == self.set)
+        {
+            Ok(Explanation::new("FlagsQuery", 1.0))
+        } else {
+            Err(TantivyError::InvalidArgument(format!(
+                "Document #({doc}) does not match"
+            )))
+        }
+    }
+}
+
+/// `DocSet` over an ascending list of doc ids.
+struct SortedDocs {
+    docs: Vec<DocId>,
+    cursor: usize,
+}
+
+impl SortedDocs {
+    fn new(docs: Vec<DocId>) -> Self {
+        Self { docs, cursor: 0 }
+    }
+}
+
+impl DocSet for SortedDocs {
+    fn advance(&mut self) -> DocId {
+        self.cursor += 1;
+        self.doc()
+    }
+
+    fn doc(&self) -> DocId {
+        self.docs.get(self.cursor).copied().unwrap_or(TERMINATED)
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.docs.len().saturating_sub(self.cursor) as u32
+    }
+}
@@ -0,0 +1,297 @@
+//! Match highlighting and content snippets for search hits.
+//!
+//! Highlights are derived from the query AST rather than from the Tantivy
+//! query so prefix terms (`repo*`) light up too. Text is tokenized with the
+//! target field's analyzer, so ranges line up with how the field was indexed.
+//! Negated terms, fuzzy edits, globs and regexes are not highlighted.
+
+use ipc::{FieldKind, MatchRange, QueryExpr, TermModifier};
+use tantivy::Index;
+use tantivy::schema::Field;
+use tantivy::tokenizer::TextAnalyzer;
+
+/// Upper bound on snippet length, in bytes.
+const SNIPPET_MAX_BYTES: usize = 200;
+/// Context kept before the first match in a snippet, in bytes.
+const SNIPPET_LEAD_BYTES: usize = 40;
+
+#[derive(Debug, Clone)]
+struct HighlightTerm {
+    field: Option<FieldKind>,
+    value: String,
+    prefix: bool,
+}
+
+/// Positive text terms of a query, ready to be matched against hit fields.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Highlighter {
+    terms: Vec<HighlightTerm>,
+}
+
+impl Highlighter {
+    pub(crate) fn new(expr: &QueryExpr) -> Self {
+        let mut terms = Vec::new();
+        collect_terms(expr, &mut terms);
+        Self { terms }
+    }
+
+    /// Match ranges of the query within `text`, a value of `field` (`kind`).
+    pub(crate) fn ranges(
+        &self,
+        index: &Index,
+        field: Field,
+        kind: FieldKind,
+        text: &str,
+    ) -> Vec<MatchRange> {
+        let Some(mut analyzer) = self.analyzer_for(index, field, kind) else {
+            return Vec::new();
+        };
+        self.match_spans(&mut analyzer, kind, text)
+            .into_iter()
+            .map(to_range)
+            .collect()
+    }
+
+    /// Best fragment of `text` (a content value) with its match ranges.
+    ///
+    /// Falls back to the start of the text when nothing matched, e.g. for
+    /// queries that only filter.
+    pub(crate) fn snippet(
+        &self,
+        index: &Index,
+        field: Field,
+        text: &str,
+    ) -> Option<(String, Vec<MatchRange>)> {
+        let spans = match self.analyzer_for(index, field, FieldKind::Content) {
+            Some(mut analyzer) => self.match_spans(&mut analyzer, FieldKind::Content, text),
+            None => Vec::new(),
+        };
+        let (start, end) = snippet_window(text, &spans);
+        let fragment = &text[start..end];
+        if fragment.trim().is_empty() {
+            return None;
+        }
+        let ranges = spans
+            .into_iter()
+            .filter(|&(s, e)| s >= start && e <= end)
+            .map(|(s, e)| to_range((s - start, e - start)))
+            .collect();
+        Some((fragment.to_string(), ranges))
+    }
+
+    fn applies_to(term: &HighlightTerm, kind: FieldKind) -> bool {
+        match term.field {
+            Some(f) => f == kind,
+            // Unqualified terms search name, path and content.
+            None => matches!(kind, FieldKind::Name | FieldKind::Path | FieldKind::Content),
+        }
+    }
+
+    fn analyzer_for(&self, index: &Index, field: Field, kind: FieldKind) -> Option<TextAnalyzer> {
+        if !self.terms.iter().any(|t| Self::applies_to(t, kind)) {
+            return None;
+        }
+        index.tokenizer_for_field(field).ok()
+    }
+
+    /// Sorted, non-overlapping byte spans of tokens in `text` matching a term.
+    fn match_spans(
+        &self,
+        analyzer: &mut TextAnalyzer,
+        kind: FieldKind,
+        text: &str,
+    ) -> Vec<(usize, usize)> {
+        // (token, is_prefix) in the field's normalized form.
+        let mut wanted: Vec<(String, bool)> = Vec::new();
+        for term in self.terms.iter().filter(|t| Self::applies_to(t, kind)) {
+            let mut tokens = Vec::new();
+            let mut stream = analyzer.token_stream(&term.value);
+            while stream.advance() {
+                tokens.push(stream.token().text.clone());
+            }
+            let last = tokens.len().saturating_sub(1);
+            wanted.extend(
+                tokens
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, t)| (t, term.prefix && i == last)),
+            );
+        }
+        if wanted.is_empty() {
+            return Vec::new();
+        }
+
+        let mut spans: Vec<(usize, usize)> = Vec::new();
+        let mut stream = analyzer.token_stream(text);
+        while stream.advance() {
+            let token = stream.token();
+            let hit = wanted.iter().any(|(w, prefix)| {
+                if *prefix {
+                    token.text.starts_with(w.as_str())
+                } else {
+                    token.text == *w
+                }
+            });
+            if !hit {
+                continue;
+            }
+            let (from, to) = (token.offset_from, token.offset_to);
+            match spans.last_mut() {
+                Some(last) if from <= last.1 => last.1 = last.1.max(to),
+                _ => spans.push((from, to)),
+            }
+        }
+        spans
+    }
+}
+
+fn collect_terms(expr: &QueryExpr, out: &mut Vec<HighlightTerm>) {
+    match expr {
+        QueryExpr::Term(t) => {
+            let prefix = match t.modifier {
+                TermModifier::Term | TermModifier::Phrase | TermModifier::Fuzzy(_) => false,
+                TermModifier::Prefix => true,
+                TermModifier::Glob | TermModifier::Regex | TermModifier::Substring => return,
+            };
+            if matches!(
+                t.field,
+                None | Some(FieldKind::Name | FieldKind::Path | FieldKind::Content)
+            ) {
+                out.push(HighlightTerm {
+                    field: t.field,
+                    value: t.value.clone(),
+                    prefix,
+                });
+            }
+        }
+        QueryExpr::And(items) | QueryExpr::Or(items) => {
+            items.iter().for_each(|e| collect_terms(e, out));
+        }
+        QueryExpr::Range(_) | QueryExpr::Not(_) => {}
+    }
+}
+
+/// Byte window of at most `SNIPPET_MAX_BYTES` covering the densest run of
+/// matches, widened to word and char boundaries.
+fn snippet_window(text: &str, spans: &[(usize, usize)]) -> (usize, usize) {
+    let Some(anchor) = (0..spans.len()).max_by_key(|&i| {
+        let limit = spans[i].0 + SNIPPET_MAX_BYTES;
+        // Prefer more matches, then the earliest window.
+        (
+            spans[i..].iter().take_while(|s| s.1 <= limit).count(),
+            std::cmp::Reverse(i),
+        )
+    }) else {
+        let end = floor_boundary(text, SNIPPET_MAX_BYTES.min(text.len()));
+        return (0, word_end(text, 0, end));
+    };
+
+    let first = spans[anchor].0;
+    let mut start = ceil_boundary(text, first.saturating_sub(SNIPPET_LEAD_BYTES));
+    if start > 0
+        && let Some((ws, c)) = text[start..first]
+            .char_indices()
+            .find(|(_, c)| c.is_whitespace())
+    {
+        start += ws + c.len_utf8();
+    }
+    let end = floor_boundary(text, (start + SNIPPET_MAX_BYTES).min(text.len()));
+    let last_match = spans[anchor..]
+        .iter()
+        .map(|s| s.1)
+        .take_while(|&e| e <= end)
+        .last()
+        .unwrap_or(start);
+    (start, word_end(text, last_match, end))
+}
+
+/// Pull `end` back to the last whitespace after `min`, unless it is the end.
+fn word_end(text: &str, min: usize, end: usize) -> usize {
+    if end >= text.len() {
+        return text.len();
+    }
+    text[min..end]
+        .rfind(char::is_whitespace)
+        .map_or(end, |ws| min + ws)
+}
+
+fn floor_boundary(text: &str, mut i: usize) -> usize {
+    while !text.is_char_boundary(i) {
+        i -= 1;
+    }
+    i
+}
+
+fn ceil_boundary(text: &str, mut i: usize) -> usize {
+    while !text.is_char_boundary(i) {
+        i += 1;
+    }
+    i
+}
+
+fn to_range((start, end): (usize, usize)) -> MatchRange {
+    MatchRange {
+        start: start as u32,
+        end: end as u32,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core_types::query::parse_query;
+    use tantivy::schema::{Schema, TEXT};
+
+    fn index() -> (Index, Field) {
+        let mut builder = Schema::builder();
+        let field = builder.add_text_field("content", TEXT);
+        (Index::create_in_ram(builder.build()), field)
+    }
+
+    fn marked(text: &str, ranges: &[MatchRange]) -> String {
+        let mut out = String::new();
+        let mut at = 0;
+        for r in ranges {
+            let (s, e) = (r.start as usize, r.end as usize);
+            out.push_str(&text[at..s]);
+            out.push('[');
+            out.push_str(&text[s..e]);
+            out.push(']');
+            at = e;
+        }
+        out.push_str(&text[at..]);
+        out
+    }
+
+    #[test]
+    fn highlights_terms_and_prefixes_but_not_negations() {
+        let (index, field) = index();
+        let h = Highlighter::new(&parse_query("Quarterly rep* -draft").unwrap());
+        let text = "Quarterly-Report draft reply";
+        let ranges = h.ranges(&index, field, FieldKind::Name, text);
+        assert_eq!(marked(text, &ranges), "[Quarterly]-[Report] draft [reply]");
+
+        let h = Highlighter::new(&parse_query("name:report").unwrap());
+        assert!(h.ranges(&index, field, FieldKind::Path, text).is_empty());
+    }
+
+    #[test]
+    fn snippet_centres_on_matches() {
+        let (index, field) = index();
+        let filler = "lorem ipsum dolor sit amet ".repeat(20);
+        let text = format!("{filler}the invoice total is due {filler}");
+        let h = Highlighter::new(&parse_query("invoice due").unwrap());
+
+        let (fragment, ranges) = h.snippet(&index, field, &text).unwrap();
+        assert!(fragment.len() <= SNIPPET_MAX_BYTES);
+        assert!(!fragment.starts_with(' ') && !fragment.ends_with(' '));
+        let marked = marked(&fragment, &ranges);
+        assert!(marked.contains("the [invoice] total is [due]"), "{marked}");
+
+        // Filter-only queries still get the opening text.
+        let h = Highlighter::new(&parse_query("size:>1kb").unwrap());
+        let (fragment, ranges) = h.snippet(&index, field, "héllo wörld").unwrap();
+        assert_eq!(fragment, "héllo wörld");
+        assert!(ranges.is_empty());
+    }
+}
@@ -1,19 +1,21 @@
 #![cfg(target_os = "windows")]
 
 use std::env;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
-use crate::metrics::{global_metrics_snapshot, record_ipc_request};
-use crate::search_handler::search;
+use crate::metrics::{fill_result_cache_counts, global_metrics_snapshot, record_ipc_request};
+use crate::saved_searches::handle_saved_search;
+use crate::search_handler::{name_ngram_bytes, search, search_stream, suggest};
 use crate::status::make_status_response;
 use crate::status_provider::status_snapshot;
 use anyhow::Result;
+#[cfg(test)]
+use ipc::StatusResponse;
 use ipc::{
-    MetricsSnapshot, ReloadConfigRequest, ReloadConfigResponse, RescanRequest, RescanResponse,
-    SearchRequest, StatusRequest, framing,
+    HitBatch, MetricsSnapshot, ReloadConfigRequest, ReloadConfigResponse, RescanRequest,
+    RescanResponse, SavedSearchRequest, SearchFrame, SearchRequest, SearchResponse, SearchSummary,
+    StatusRequest, SuggestRequest, framing,
 };
-#[cfg(test)]
-use ipc::{SearchResponse, StatusResponse};
 use std::io::Cursor;
 use tokio::io::{AsyncReadExt, AsyncWriteExt};
 use tokio::net::windows::named_pipe::NamedPipeServer;
@@ -171,6 +173,11 @@ async fn handle_connection(mut conn: NamedPipeServer) -> Result<()> {
         // But wait, `buf` IS the payload.
         // framing::decode_frame also checks length.
 
+        if let Some(req) = deserialize_exact::<SearchRequest>(&buf).filter(|r| r.stream) {
+            stream_search(&mut conn, req).await?;
+            continue;
+        }
+
         let response = dispatch(&buf);
         let framed = framing::encode_frame(&response).unwrap_or_default();
         // framed includes length prefix.
@@ -179,15 +186,47 @@ async fn handle_connection(mut conn: NamedPipeServer) -> Result<()> {
     Ok(())
 }
 
-fn dispatch(payload: &[u8]) -> Vec<u8> {
-    fn deserialize_exact<T: serde::de::DeserializeOwned>(payload: &[u8]) -> Option<T> {
-        let mut cursor = Cursor::new(payload);
-        match bincode::deserialize_from::<_, T>(&mut cursor) {
-            Ok(v) if cursor.position() as usize == payload.len() => Some(v),
-            _ => None,
+/// Answer a streamed search: a frame per hit batch as the handler ranks
+/// it, then the summary.
+async fn stream_search(conn: &mut NamedPipeServer, req: SearchRequest) -> Result<()> {
+    let start = Instant::now();
+    let id = req.id;
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let worker = tokio::task::spawn_blocking(move || {
+        let batches = tx.clone();
+        let mut resp = search_stream(req, &mut |source, hits| {
+            if !hits.is_empty() {
+                let _ = batches.send(SearchFrame::Hits(HitBatch {
+                    id,
+                    source,
+                    hits: hits.to_vec(),
+                }));
+            }
+        });
+        finish_search(&mut resp, id, start.elapsed());
+        let _ = tx.send(SearchFrame::Done(Box::new(SearchSummary::from_response(
+            resp,
+        ))));
+    });
+    while let Some(frame) = rx.recv().await {
+        for bytes in framing::encode_search_frames(&frame)? {
+            conn.write_all(&bytes).await?;
         }
     }
+    worker.await?;
+    record_ipc_request(start.elapsed());
+    Ok(())
+}
+
+fn deserialize_exact<T: serde::de::DeserializeOwned>(payload: &[u8]) -> Option<T> {
+    let mut cursor = Cursor::new(payload);
+    match bincode::deserialize_from::<_, T>(&mut cursor) {
+        Ok(v) if cursor.position() as usize == payload.len() => Some(v),
+        _ => None,
+    }
+}
 
+fn dispatch(payload: &[u8]) -> Vec<u8> {
     // Fast-path: ping echo when payload is prefixed with "PING" + UUID.
     if payload.len() >= 20
         && payload.starts_with(b"PING")
@@ -200,7 +239,7 @@ fn dispatch(payload: &[u8]) -> Vec<u8> {
     if let Some(req) = deserialize_exact::<StatusRequest>(payload) {
         let started = Instant::now();
         let snap = status_snapshot();
-        let empty_metrics =
+        let mut empty_metrics =
             snap.metrics.or(
                 global_metrics_snapshot(Some(0), Some(0), Some(0), Some(0)).or(Some(
                     MetricsSnapshot {
@@ -212,9 +251,16 @@ fn dispatch(payload: &[u8]) -> Vec<u8> {
                         active_workers: Some(0),
                         content_enqueued: Some(0),
                         content_dropped: Some(0),
+                        cache_hits: Some(0),
+                        cache_misses: Some(0),
+                        name_ngram_bytes: None,
                     },
                 )),
             );
+        if let Some(metrics) = &mut empty_metrics {
+            fill_result_cache_counts(metrics);
+            metrics.name_ngram_bytes = name_ngram_bytes();
+        }
         let resp = make_status_response(
             req.id,
             snap.volumes,
@@ -231,6 +277,15 @@ fn dispatch(payload: &[u8]) -> Vec<u8> {
         return encoded;
     }
 
+    // Handle SuggestRequest (sent per keystroke, so checked early).
+    if let Some(req) = deserialize_exact::<SuggestRequest>(payload) {
+        let started = Instant::now();
+        let resp = suggest(req);
+        let encoded = bincode::serialize(&resp).unwrap_or_default();
+        record_ipc_request(started.elapsed());
+        return encoded;
+    }
+
     // Handle ReloadConfigRequest
     if let Some(req) = deserialize_exact::<ReloadConfigRequest>(payload) {
         let started = Instant::now();
@@ -278,25 +333,22 @@ fn dispatch(payload: &[u8]) -> Vec<u8> {
         return encoded;
     }
 
+    // Handle SavedSearchRequest
+    if let Some(req) = deserialize_exact::<SavedSearchRequest>(payload) {
+        let started = Instant::now();
+        let resp = handle_saved_search(req);
+        let encoded = bincode::serialize(&resp).unwrap_or_default();
+        record_ipc_request(started.elapsed());
+        return encoded;
+    }
+
     // Fallback: dispatch SearchRequest.
     if let Some(req) = deserialize_exact::<SearchRequest>(payload) {
         let start = Instant::now();
-        let req_clone = req.clone();
+        let id = req.id;
         let mut resp = search(req);
-        // Ensure the echoed id always matches the request for protocol stability.
-        // search(req) should propagate id, but we enforce it defensively.
-        // Use the id already in resp if set, otherwise fallback to request id.
-        if resp.id.is_nil() {
-            resp.id = req_clone.id;
-        }
         let elapsed = start.elapsed();
-        let took = elapsed.as_millis().min(u32::MAX as u128) as u32;
-        if resp.took_ms == 0 {
-            resp.took_ms = took;
-        }
-        if resp.served_by.is_none() {
-            resp.served_by = Some(host_label());
-        }
+        finish_search(&mut resp, id, elapsed);
         let encoded = bincode::serialize(&resp).unwrap_or_default();
         record_ipc_request(elapsed);
         return encoded;
@@ -305,6 +357,22 @@ fn dispatch(payload: &[u8]) -> Vec<u8> {
     Vec::new()
 }
 
+/// Fill in what the handler may have left unset.
+fn finish_search(resp: &mut SearchResponse, id: Uuid, elapsed: Duration) {
+    // Ensure the echoed id always matches the request for protocol stability.
+    // search(req) should propagate id, but we enforce it defensively.
+    // Use the id already in resp if set, otherwise fallback to request id.
+    if resp.id.is_nil() {
+        resp.id = id;
+    }
+    if resp.took_ms == 0 {
+        resp.took_ms = elapsed.as_millis().min(u32::MAX as u128) as u32;
+    }
+    if resp.served_by.is_none() {
+        resp.served_by = Some(host_label());
+    }
+}
+
 fn host_label() -> String {
     env::var("COMPUTERNAME")
         .or_else(|_| env::var("HOSTNAME"))
@@ -348,6 +416,11 @@ mod tests {
             mode: ipc::SearchMode::Auto,
             timeout: None,
             offset: 0,
+            sort: None,
+            facets: None,
+            explain: false,
+            cursor: None,
+            stream: false,
         };
         let resp_bytes = dispatch(&bincode::serialize(&req).unwrap());
         let resp: SearchResponse = bincode::deserialize(&resp_bytes).unwrap();
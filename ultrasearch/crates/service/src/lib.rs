@@ -1,18 +1,28 @@
 //! Service support library: tracing/logging bootstrap and metrics helpers.
 
 pub mod bootstrap;
+mod cursor;
 pub mod dispatcher;
+mod facets;
+mod flag_query;
+mod highlight;
 mod logging;
 pub mod memory;
 pub mod meta_ingest;
 pub mod metrics;
+mod not_query;
 pub mod planner;
 pub mod priority;
+mod result_cache;
+pub mod saved_searches;
 pub mod scanner;
 pub mod scheduler_runtime;
 pub mod search_handler;
+mod sort_collector;
+mod spelling;
 pub mod status;
 pub mod status_provider;
+mod time_budget;
 
 #[cfg(windows)]
 pub mod windows;
@@ -27,7 +37,8 @@ pub use metrics::{
 pub use priority::{ProcessPriority, set_process_priority};
 pub use scheduler_runtime::{SchedulerRuntime, set_live_active_workers, set_live_queue_counts};
 pub use search_handler::{
-    SearchHandler, StubSearchHandler, UnifiedSearchHandler, search, set_search_handler,
+    HitSink, SearchHandler, StubSearchHandler, UnifiedSearchHandler, search, search_stream,
+    set_search_handler, suggest,
 };
 pub use status_provider::{
     BasicStatusProvider, init_basic_status_provider, set_status_provider, status_snapshot,
@@ -111,7 +122,9 @@ mod e2e_windows_tests {
         client::PipeClient,
     };
     use anyhow::Result;
-    use content_index::{ContentDoc, WriterConfig, add_content_doc, create_writer, open_or_create};
+    use content_index::{
+        ContentDoc, TextKind, WriterConfig, add_content_doc, create_writer, open_or_create,
+    };
     use core_types::{DocKey, FileFlags, FileMeta, Timestamp};
     use tempfile::tempdir;
     use tokio::io::AsyncWriteExt;
@@ -227,6 +240,11 @@ mod e2e_windows_tests {
             mode: SearchMode::NameOnly,
             timeout: Some(Duration::from_secs(2)),
             offset: 0,
+            sort: None,
+            facets: None,
+            explain: false,
+            cursor: None,
+            stream: false,
         };
         let resp = client.search(search_req).await?;
         assert!(
@@ -251,6 +269,11 @@ mod e2e_windows_tests {
                     mode: SearchMode::Content,
                     timeout: Some(Duration::from_secs(2)),
                     offset: 0,
+                    sort: None,
+                    facets: None,
+                    explain: false,
+                    cursor: None,
+                    stream: false,
                 };
                 let resp = client.search(content_req).await?;
                 if resp.total > 0 {
@@ -302,8 +325,11 @@ mod e2e_windows_tests {
             ext: Some("txt".into()),
             size: 20,
             modified: now_ts(),
+            created: now_ts(),
             content_lang: Some("en".into()),
             content: "lorem ipsum ultrasearch content".into(),
+            ancestors: Vec::new(),
+            text_kind: TextKind::Plain,
         };
         add_content_doc(&mut writer, &content_idx.fields, &doc)?;
         writer.commit()?;
@@ -368,6 +394,11 @@ mod e2e_windows_tests {
             mode: SearchMode::Content,
             timeout: Some(Duration::from_secs(2)),
             offset: 0,
+            sort: None,
+            facets: None,
+            explain: false,
+            cursor: None,
+            stream: false,
         };
         let resp = client.search(search_req).await?;
         assert!(
@@ -475,6 +506,11 @@ mod e2e_windows_tests {
             mode: SearchMode::NameOnly,
             timeout: Some(Duration::from_secs(2)),
             offset: 0,
+            sort: None,
+            facets: None,
+            explain: false,
+            cursor: None,
+            stream: false,
         };
         let resp = client.search(search_req).await?;
         assert!(
@@ -1,19 +1,41 @@
 use anyhow::Result;
 use core_types::FileMeta;
 use core_types::config::PathsSection;
-use meta_index::{WriterConfig, add_file_meta_batch, create_writer, open_or_create_index};
+use meta_index::fst::FST_FILE_NAME;
+use meta_index::{
+    MetaIndex, SchemaOptions, WriterConfig, add_file_meta_batch, create_writer,
+    open_or_create_index_with, write_name_fst,
+};
 use std::path::Path;
+use tracing::warn;
 
-/// Ingest a batch of `FileMeta` records into the metadata index and commit.
+/// Open the metadata index, creating it with the configured optional fields.
+pub fn open_meta_index(index_path: &Path) -> Result<MetaIndex> {
+    let features = core_types::config::get_current_config().features;
+    let opts = SchemaOptions {
+        name_ngrams: features.name_ngram_index,
+        code_names: features.doc_type_analyzers,
+    };
+    open_or_create_index_with(index_path, opts)
+}
+
+/// Ingest a batch of `FileMeta` records into the metadata index, commit, and
+/// rebuild the name FST from the committed index. A failed rebuild is logged,
+/// not returned: the batch itself is already committed.
 pub fn ingest_file_meta_batch(
     index_path: &Path,
     metas: impl IntoIterator<Item = FileMeta>,
     writer_cfg: Option<WriterConfig>,
 ) -> Result<()> {
-    let meta = open_or_create_index(index_path)?;
+    let meta = open_meta_index(index_path)?;
     let mut writer = create_writer(&meta, &writer_cfg.unwrap_or_default())?;
     add_file_meta_batch(&mut writer, &meta.fields, metas)?;
     writer.commit()?;
+    let searcher = meta.index.reader()?.searcher();
+    // Regex search keeps the FST it already has until a later batch succeeds.
+    if let Err(err) = write_name_fst(&searcher, &meta.fields, &index_path.join(FST_FILE_NAME)) {
+        warn!("failed to rebuild the name FST: {err:#}");
+    }
     Ok(())
 }
 
@@ -25,3 +47,72 @@ pub fn ingest_with_paths(
 ) -> Result<()> {
     ingest_file_meta_batch(Path::new(&paths.meta_index), metas, writer_cfg)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search_handler::{SearchHandler, UnifiedSearchHandler};
+    use core_types::{DocKey, FileFlags};
+    use ipc::{SearchMode, SearchRequest};
+
+    #[test]
+    fn ingest_writes_the_name_fst_used_by_regex_search() {
+        let dir = tempfile::tempdir().unwrap();
+        let meta_path = dir.path().join("meta");
+        std::fs::create_dir_all(&meta_path).unwrap();
+        let metas = ["Report-2024.pdf", "notes.txt", "report.docx"]
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                FileMeta::new(
+                    DocKey::from_parts(1, i as u64 + 1),
+                    1,
+                    None,
+                    name.to_string(),
+                    None,
+                    1,
+                    0,
+                    0,
+                    FileFlags::empty(),
+                )
+            });
+        ingest_file_meta_batch(&meta_path, metas, None).unwrap();
+        assert!(meta_path.join(FST_FILE_NAME).exists());
+
+        let handler =
+            UnifiedSearchHandler::try_new(&meta_path, &dir.path().join("content")).unwrap();
+        let resp = handler.search(SearchRequest {
+            query: core_types::query::parse_query(r"regex:^report").unwrap(),
+            mode: SearchMode::NameOnly,
+            explain: true,
+            ..SearchRequest::default()
+        });
+        assert_eq!(resp.error, None);
+        assert_eq!(resp.total, 2);
+        let explain = resp.explain.unwrap();
+        assert_eq!(explain.queries[0].index, "names");
+    }
+
+    #[test]
+    fn a_failed_fst_write_keeps_the_batch() {
+        let dir = tempfile::tempdir().unwrap();
+        let meta_path = dir.path().join("meta");
+        // A non-empty directory where the FST goes makes the rename fail.
+        std::fs::create_dir_all(meta_path.join(FST_FILE_NAME).join("blocked")).unwrap();
+        let meta = FileMeta::new(
+            DocKey::from_parts(1, 1),
+            1,
+            None,
+            "notes.txt".to_string(),
+            None,
+            1,
+            0,
+            0,
+            FileFlags::empty(),
+        );
+        ingest_file_meta_batch(&meta_path, [meta], None).unwrap();
+
+        let index = open_meta_index(&meta_path).unwrap();
+        assert_eq!(index.index.reader().unwrap().searcher().num_docs(), 1);
+    }
+}
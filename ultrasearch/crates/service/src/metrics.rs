@@ -16,6 +16,9 @@ pub struct ServiceMetrics {
     pub request_latency: Histogram,
     pub worker_failures: IntCounter,
     pub worker_failure_threshold: u64,
+    pub search_timeouts: IntCounter,
+    pub result_cache_hits: IntCounter,
+    pub result_cache_misses: IntCounter,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -27,6 +30,9 @@ pub struct ServiceMetricsSnapshot {
     pub active_workers: Option<u32>,
     pub content_enqueued: Option<u64>,
     pub content_dropped: Option<u64>,
+    pub search_timeouts: u64,
+    pub result_cache_hits: u64,
+    pub result_cache_misses: u64,
 }
 
 impl ServiceMetrics {
@@ -43,10 +49,25 @@ impl ServiceMetrics {
         let request_latency = Histogram::with_opts(hist_opts)?;
         let worker_failures =
             IntCounter::with_opts(opts!("worker_failures_total", "Index worker failures"))?;
+        let search_timeouts = IntCounter::with_opts(opts!(
+            "search_timeouts_total",
+            "Searches cut short by their request timeout"
+        ))?;
+        let result_cache_hits = IntCounter::with_opts(opts!(
+            "result_cache_hits_total",
+            "Searches answered from the result cache"
+        ))?;
+        let result_cache_misses = IntCounter::with_opts(opts!(
+            "result_cache_misses_total",
+            "Cacheable searches that ran against the indexes"
+        ))?;
 
         registry.register(Box::new(requests_total.clone()))?;
         registry.register(Box::new(request_latency.clone()))?;
         registry.register(Box::new(worker_failures.clone()))?;
+        registry.register(Box::new(search_timeouts.clone()))?;
+        registry.register(Box::new(result_cache_hits.clone()))?;
+        registry.register(Box::new(result_cache_misses.clone()))?;
 
         Ok(Self {
             registry,
@@ -54,6 +75,9 @@ impl ServiceMetrics {
             request_latency,
             worker_failures,
             worker_failure_threshold: cfg.worker_failure_threshold,
+            search_timeouts,
+            result_cache_hits,
+            result_cache_misses,
         })
     }
 
@@ -82,6 +106,20 @@ impl ServiceMetrics {
         tripped
     }
 
+    /// Record a search that returned partial results at its deadline.
+    pub fn record_search_timeout(&self) {
+        self.search_timeouts.inc();
+    }
+
+    /// Record a result cache lookup.
+    pub fn record_result_cache(&self, hit: bool) {
+        if hit {
+            self.result_cache_hits.inc();
+        } else {
+            self.result_cache_misses.inc();
+        }
+    }
+
     /// Reset the worker failure counter (used after a healthy run).
     pub fn reset_worker_failures(&self) {
         self.worker_failures.reset();
@@ -102,6 +140,9 @@ impl ServiceMetrics {
             active_workers,
             content_enqueued,
             content_dropped,
+            search_timeouts: self.search_timeouts.get(),
+            result_cache_hits: self.result_cache_hits.get(),
+            result_cache_misses: self.result_cache_misses.get(),
         }
     }
 
@@ -116,6 +157,9 @@ impl ServiceMetrics {
             active_workers: None,
             content_enqueued: None,
             content_dropped: None,
+            search_timeouts: self.search_timeouts.get(),
+            result_cache_hits: self.result_cache_hits.get(),
+            result_cache_misses: self.result_cache_misses.get(),
         }
     }
 }
@@ -168,6 +212,9 @@ pub fn global_metrics_snapshot(
             active_workers: snap.active_workers,
             content_enqueued: snap.content_enqueued,
             content_dropped: snap.content_dropped,
+            cache_hits: Some(snap.result_cache_hits),
+            cache_misses: Some(snap.result_cache_misses),
+            name_ngram_bytes: None,
         }
     })
 }
@@ -177,6 +224,24 @@ pub fn record_ipc_request(duration: Duration) {
     let _ = with_global_metrics(|m| m.record_request_duration(duration));
 }
 
+/// Record a timed-out search against the global metrics handle (no-op if uninitialized).
+pub fn record_search_timeout() {
+    let _ = with_global_metrics(|m| m.record_search_timeout());
+}
+
+/// Record a result cache hit or miss against the global metrics handle (no-op if uninitialized).
+pub fn record_result_cache(hit: bool) {
+    let _ = with_global_metrics(|m| m.record_result_cache(hit));
+}
+
+/// Copy the global result cache counters into `snap` (no-op if uninitialized).
+pub fn fill_result_cache_counts(snap: &mut MetricsSnapshot) {
+    let _ = with_global_metrics(|m| {
+        snap.cache_hits = Some(m.result_cache_hits.get());
+        snap.cache_misses = Some(m.result_cache_misses.get());
+    });
+}
+
 /// Record a worker failure and return true if the failure threshold was met; no-op if metrics unset.
 pub fn record_worker_failure_global() -> Option<bool> {
     with_global_metrics(|m| m.record_worker_failure())
@@ -209,6 +274,26 @@ mod tests {
         assert!(metrics.requests_total.get() >= 1);
     }
 
+    #[test]
+    fn search_timeouts_counted() {
+        let metrics = ServiceMetrics::new(&MetricsSection::default()).unwrap();
+        metrics.record_search_timeout();
+        assert_eq!(metrics.snapshot().search_timeouts, 1);
+        let text = String::from_utf8(scrape_metrics(&metrics).unwrap()).unwrap();
+        assert!(text.contains("search_timeouts_total 1"));
+    }
+
+    #[test]
+    fn result_cache_lookups_counted() {
+        let metrics = ServiceMetrics::new(&MetricsSection::default()).unwrap();
+        metrics.record_result_cache(true);
+        metrics.record_result_cache(false);
+        metrics.record_result_cache(true);
+        let snap = metrics.snapshot();
+        assert_eq!(snap.result_cache_hits, 2);
+        assert_eq!(snap.result_cache_misses, 1);
+    }
+
     #[test]
     fn snapshot_with_queue_state_sets_fields() {
         let metrics = ServiceMetrics::new(&MetricsSection::default()).unwrap();
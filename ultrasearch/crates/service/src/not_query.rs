@@ -0,0 +1,128 @@
+//! Complement of a query: every document the inner query does not match.
+//!
+//! A Tantivy `BooleanQuery` made only of `MustNot` clauses matches nothing,
+//! and giving it an `AllQuery` base yields an `Exclude` scorer that cannot
+//! be positioned on an earlier document, which `Weight::explain` does for
+//! non-matching children of a union. This scorer walks doc ids instead and
+//! skips the ones the inner scorer hits.
+
+use tantivy::query::{ConstScorer, EnableScoring, Explanation, Query, Scorer, Weight};
+use tantivy::{DocId, DocSet, Score, SegmentReader, TERMINATED, TantivyError};
+
+#[derive(Debug)]
+pub(crate) struct NotQuery {
+    inner: Box<dyn Query>,
+}
+
+impl NotQuery {
+    pub(crate) fn new(inner: Box<dyn Query>) -> Self {
+        Self { inner }
+    }
+}
+
+impl Clone for NotQuery {
+    fn clone(&self) -> Self {
+        Self {
+            inner: self.inner.box_clone(),
+        }
+    }
+}
+
+impl Query for NotQuery {
+    fn weight(&self, enable_scoring: EnableScoring<'_>) -> tantivy::Result<Box<dyn Weight>> {
+        Ok(Box::new(NotWeight {
+            inner: self.inner.weight(enable_scoring)?,
+        }))
+    }
+}
+
+struct NotWeight {
+    inner: Box<dyn Weight>,
+}
+
+impl Weight for NotWeight {
+    fn scorer(&self, reader: &SegmentReader, boost: Score) -> tantivy::Result<Box<dyn Scorer>> {
+        let excluded = self.inner.scorer(reader, 1.0)?;
+        Ok(Box::new(ConstScorer::new(
+            Complement::new(excluded, reader.max_doc()),
+            boost,
+        )))
+    }
+
+    fn explain(&self, reader: &SegmentReader, doc: DocId) -> tantivy::Result<Explanation> {
+        let mut excluded = self.inner.scorer(reader, 1.0)?;
+        // A fresh scorer sits on its first match; only seek forward.
+        let matched = match excluded.doc() {
+            first if first < doc => excluded.seek(doc) == doc,
+            first => first == doc,
+        };
+        if matched {
+            Err(TantivyError::InvalidArgument(format!(
+                "Document #({doc}) does not match"
+            )))
+        } else {
+            Ok(Explanation::new("NotQuery", 1.0))
+        }
+    }
+}
+
+/// Doc ids in `0..max_doc` that `excluded` does not contain.
+struct Complement {
+    excluded: Box<dyn Scorer>,
+    doc: DocId,
+    max_doc: DocId,
+}
+
+impl Complement {
+    fn new(excluded: Box<dyn Scorer>, max_doc: DocId) -> Self {
+        let mut out = Self {
+            excluded,
+            doc: 0,
+            max_doc,
+        };
+        out.settle();
+        out
+    }
+
+    /// Move forward from `self.doc` to the first id not in `excluded`.
+    fn settle(&mut self) -> DocId {
+        while self.doc < self.max_doc {
+            let mut hit = self.excluded.doc();
+            if hit < self.doc {
+                hit = self.excluded.seek(self.doc);
+            }
+            if hit != self.doc {
+                return self.doc;
+            }
+            self.doc += 1;
+        }
+        self.doc = TERMINATED;
+        self.doc
+    }
+}
+
+impl DocSet for Complement {
+    fn advance(&mut self) -> DocId {
+        if self.doc == TERMINATED {
+            return TERMINATED;
+        }
+        self.doc += 1;
+        self.settle()
+    }
+
+    fn seek(&mut self, target: DocId) -> DocId {
+        if target <= self.doc {
+            return self.doc;
+        }
+        self.doc = target;
+        self.settle()
+    }
+
+    fn doc(&self) -> DocId {
+        self.doc
+    }
+
+    fn size_hint(&self) -> u32 {
+        self.max_doc.saturating_sub(self.excluded.size_hint())
+    }
+}
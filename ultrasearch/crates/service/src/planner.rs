@@ -1,4 +1,19 @@
-use ipc::QueryExpr;
+use core_types::kind;
+use ipc::{FieldKind, QueryExpr, SearchMode, TermExpr, TermModifier};
+
+/// Kinds whose files never carry indexed text, so a query restricted to them
+/// cannot match anything in the content index.
+const NON_TEXT_KINDS: &[&str] = &[
+    kind::VIDEO,
+    kind::AUDIO,
+    kind::ARCHIVE,
+    kind::EXECUTABLE,
+    kind::FONT,
+    kind::FOLDER,
+];
+
+/// Free-text terms up to this many characters are treated as name lookups.
+const SHORT_TERM_CHARS: usize = 3;
 
 /// Optimizes a raw query AST for execution.
 pub struct QueryPlanner;
@@ -6,10 +21,102 @@ pub struct QueryPlanner;
 impl QueryPlanner {
     /// Optimize the query expression.
     pub fn optimize(expr: QueryExpr) -> QueryExpr {
-        // TODO: Analyze `ext:` terms to select specialized fields/analyzers in the future.
         Self::flatten(Self::push_down_not(expr))
     }
 
+    /// Choose the index(es) a `SearchMode::Auto` search should run against.
+    ///
+    /// - Explicit `content:` terms need the content index.
+    /// - Queries without free text (only filters, `name:`/`path:` terms),
+    ///   `is:` attribute filters, and queries restricted by `ext:`/`kind:`
+    ///   to files without text are answered by the metadata index alone.
+    /// - Free text that looks like a file name (`*.rs`, `report.pdf`, `ab`)
+    ///   is a name lookup too.
+    /// - Everything else runs hybrid.
+    pub fn route(expr: &QueryExpr) -> SearchMode {
+        if Self::uses_field(expr, FieldKind::Content) {
+            return SearchMode::Content;
+        }
+        if Self::uses_field(expr, FieldKind::Flags) || Self::restricted_to_non_text(expr) {
+            return SearchMode::NameOnly;
+        }
+        let mut free = Vec::new();
+        Self::free_text(expr, &mut free);
+        if free.iter().all(|t| Self::name_like(t)) {
+            SearchMode::NameOnly
+        } else {
+            SearchMode::Hybrid
+        }
+    }
+
+    fn uses_field(expr: &QueryExpr, field: FieldKind) -> bool {
+        match expr {
+            QueryExpr::Term(t) => t.field == Some(field),
+            QueryExpr::Range(r) => r.field == field,
+            QueryExpr::Not(inner) => Self::uses_field(inner, field),
+            QueryExpr::And(items) | QueryExpr::Or(items) => {
+                items.iter().any(|e| Self::uses_field(e, field))
+            }
+        }
+    }
+
+    /// Positive unqualified terms, i.e. the ones that may match file text.
+    fn free_text<'a>(expr: &'a QueryExpr, out: &mut Vec<&'a TermExpr>) {
+        match expr {
+            QueryExpr::Term(t) if t.field.is_none() => out.push(t),
+            QueryExpr::And(items) | QueryExpr::Or(items) => {
+                items.iter().for_each(|e| Self::free_text(e, out));
+            }
+            _ => {}
+        }
+    }
+
+    fn name_like(term: &TermExpr) -> bool {
+        match term.modifier {
+            TermModifier::Glob | TermModifier::Regex | TermModifier::Substring => true,
+            TermModifier::Phrase => false,
+            _ => {
+                let value = term.value.trim();
+                value.chars().count() <= SHORT_TERM_CHARS
+                    || value.contains(['\\', '/'])
+                    || value.rsplit_once('.').is_some_and(|(stem, ext)| {
+                        !stem.is_empty() && kind::classify(Some(ext), None) != kind::OTHER
+                    })
+            }
+        }
+    }
+
+    /// Whether a top-level `ext:` or `kind:` filter only admits files
+    /// without indexed text.
+    fn restricted_to_non_text(expr: &QueryExpr) -> bool {
+        let conjuncts = match expr {
+            QueryExpr::And(items) => items.as_slice(),
+            other => std::slice::from_ref(other),
+        };
+        conjuncts.iter().any(|e| {
+            let alternatives = match e {
+                QueryExpr::Or(items) => items.as_slice(),
+                other => std::slice::from_ref(other),
+            };
+            alternatives.iter().all(Self::is_non_text_filter)
+        })
+    }
+
+    fn is_non_text_filter(expr: &QueryExpr) -> bool {
+        let QueryExpr::Term(t) = expr else {
+            return false;
+        };
+        if t.modifier != TermModifier::Term {
+            return false;
+        }
+        let kind = match t.field {
+            Some(FieldKind::Kind) => t.value.to_ascii_lowercase(),
+            Some(FieldKind::Ext) => kind::classify(Some(&t.value), None),
+            _ => return false,
+        };
+        NON_TEXT_KINDS.contains(&kind.as_str())
+    }
+
     /// Distribute NOTs: `Not(And([A, B]))` -> `Or([Not(A), Not(B)])` (De Morgan's).
     /// This canonicalizes negations to be closer to leaves.
     fn push_down_not(expr: QueryExpr) -> QueryExpr {
@@ -20,7 +127,7 @@ impl QueryPlanner {
                     // Not(A and B) -> Not(A) or Not(B)
                     QueryExpr::Or(
                         subs.into_iter()
-                            .map(|s| QueryExpr::Not(Box::new(Self::push_down_not(s))))
+                            .map(|s| Self::push_down_not(QueryExpr::Not(Box::new(s))))
                             .collect(),
                     )
                 }
@@ -28,7 +135,7 @@ impl QueryPlanner {
                     // Not(A or B) -> Not(A) and Not(B)
                     QueryExpr::And(
                         subs.into_iter()
-                            .map(|s| QueryExpr::Not(Box::new(Self::push_down_not(s))))
+                            .map(|s| Self::push_down_not(QueryExpr::Not(Box::new(s))))
                             .collect(),
                     )
                 }
@@ -128,4 +235,44 @@ mod tests {
             assert!(matches!(subs[1], QueryExpr::Not(_)));
         }
     }
+
+    #[test]
+    fn test_push_down_not_nested() {
+        // Not(A and (B or Not C)) -> Not(A) or (Not(B) and C)
+        let q = QueryExpr::Not(Box::new(QueryExpr::And(vec![
+            term("A"),
+            QueryExpr::Or(vec![term("B"), QueryExpr::Not(Box::new(term("C")))]),
+        ])));
+        let not = |e: QueryExpr| QueryExpr::Not(Box::new(e));
+        assert_eq!(
+            QueryPlanner::optimize(q),
+            QueryExpr::Or(vec![
+                not(term("A")),
+                QueryExpr::And(vec![not(term("B")), term("C")]),
+            ])
+        );
+    }
+
+    #[test]
+    fn route_picks_indexes_by_query_shape() {
+        use core_types::query::parse_query;
+
+        let route = |q: &str| QueryPlanner::route(&parse_query(q).unwrap());
+        // Filters and name-shaped text stay on the metadata index.
+        assert_eq!(route("ext:pdf size:>10mb"), SearchMode::NameOnly);
+        assert_eq!(route("name:report"), SearchMode::NameOnly);
+        assert_eq!(route("*.rs"), SearchMode::NameOnly);
+        assert_eq!(route("report.pdf"), SearchMode::NameOnly);
+        assert_eq!(route("ab"), SearchMode::NameOnly);
+        assert_eq!(route("is:hidden quarterly"), SearchMode::NameOnly);
+        assert_eq!(route("holiday kind:video"), SearchMode::NameOnly);
+        assert_eq!(route("holiday (ext:mp4 OR ext:mkv)"), SearchMode::NameOnly);
+        // Explicit content terms go to the content index.
+        assert_eq!(route("content:invoice ext:pdf"), SearchMode::Content);
+        // Everything else is hybrid.
+        assert_eq!(route("quarterly report"), SearchMode::Hybrid);
+        assert_eq!(route("\"ab cd\""), SearchMode::Hybrid);
+        assert_eq!(route("holiday (ext:mp4 OR ext:txt)"), SearchMode::Hybrid);
+        assert_eq!(route("v1.2"), SearchMode::Hybrid);
+    }
 }
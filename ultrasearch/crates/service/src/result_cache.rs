@@ -0,0 +1,121 @@
+//! Responses of recent searches, reused while the indexes stay unchanged.
+//!
+//! Entries are keyed by the planned query, mode, sort and page, and belong
+//! to the reader generations they were computed on. A lookup against newer
+//! generations (some index committed) drops every entry, so a cached page is
+//! never staler than the searcher that would otherwise answer it.
+
+use std::num::NonZeroUsize;
+
+use ipc::{SearchMode, SearchRequest, SearchResponse};
+use lru::LruCache;
+use parking_lot::Mutex;
+
+/// Meta and content reader generations a response was computed on.
+pub(crate) type Generations = (u64, Option<u64>);
+
+pub(crate) struct ResultCache {
+    inner: Mutex<Inner>,
+}
+
+struct Inner {
+    generations: Option<Generations>,
+    entries: LruCache<Vec<u8>, SearchResponse>,
+}
+
+impl ResultCache {
+    pub(crate) fn new(capacity: NonZeroUsize) -> Self {
+        Self {
+            inner: Mutex::new(Inner {
+                generations: None,
+                entries: LruCache::new(capacity),
+            }),
+        }
+    }
+
+    /// Cache key of `req` run in `mode`; `None` for cursor pages, which read
+    /// pinned snapshots rather than the current generations.
+    pub(crate) fn key(req: &SearchRequest, mode: SearchMode) -> Option<Vec<u8>> {
+        if req.cursor.is_some() {
+            return None;
+        }
+        // The requested mode too: routed searches report it in `served_by`.
+        bincode::serialize(&(
+            &req.query,
+            req.mode,
+            mode,
+            req.sort,
+            req.offset,
+            req.limit,
+            &req.facets,
+            req.explain,
+        ))
+        .ok()
+    }
+
+    pub(crate) fn get(&self, key: &[u8], generations: Generations) -> Option<SearchResponse> {
+        let mut inner = self.inner.lock();
+        if inner.generations != Some(generations) {
+            inner.entries.clear();
+            inner.generations = Some(generations);
+            return None;
+        }
+        inner.entries.get(key).cloned()
+    }
+
+    /// Keep `resp` unless the indexes moved on while it was computed.
+    pub(crate) fn put(&self, key: Vec<u8>, generations: Generations, resp: SearchResponse) {
+        let mut inner = self.inner.lock();
+        if inner.generations == Some(generations) {
+            inner.entries.put(key, resp);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core_types::query::parse_query;
+
+    #[test]
+    fn entries_live_until_a_generation_changes() {
+        let cache = ResultCache::new(NonZeroUsize::new(2).unwrap());
+        let req = |q: &str| SearchRequest::with_query(parse_query(q).unwrap());
+        let key = |q: &str| ResultCache::key(&req(q), SearchMode::NameOnly).unwrap();
+        let resp = |total| SearchResponse {
+            id: Default::default(),
+            hits: Vec::new(),
+            total,
+            truncated: false,
+            took_ms: 0,
+            served_by: None,
+            error: None,
+            facets: Vec::new(),
+            explain: None,
+            next_cursor: None,
+            suggestions: Vec::new(),
+        };
+
+        assert!(cache.get(&key("a"), (1, Some(1))).is_none());
+        cache.put(key("a"), (1, Some(1)), resp(1));
+        assert_eq!(cache.get(&key("a"), (1, Some(1))).unwrap().total, 1);
+        assert_ne!(
+            key("a"),
+            ResultCache::key(&req("a"), SearchMode::Content).unwrap()
+        );
+        assert!(ResultCache::key(&req("a").with_cursor("00"), SearchMode::NameOnly).is_none());
+
+        // Least recently used goes first.
+        cache.put(key("b"), (1, Some(1)), resp(2));
+        cache.put(key("c"), (1, Some(1)), resp(3));
+        assert!(cache.get(&key("a"), (1, Some(1))).is_none());
+        assert!(cache.get(&key("c"), (1, Some(1))).is_some());
+
+        // A content commit invalidates everything, and late results computed
+        // on the old generations are not stored.
+        assert!(cache.get(&key("c"), (1, Some(2))).is_none());
+        cache.put(key("c"), (1, Some(1)), resp(3));
+        assert!(cache.get(&key("c"), (1, Some(2))).is_none());
+        assert!(cache.get(&key("b"), (1, Some(2))).is_none());
+    }
+}
@@ -0,0 +1,232 @@
+//! Named searches kept by the service across restarts.
+//!
+//! The list lives in `state_dir/saved_searches.json` and is rewritten whole
+//! on every change. Queries are stored as typed and parsed on each run, so a
+//! saved search picks up parser improvements and stays readable in the file.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result, anyhow, bail};
+use core_types::query::parse_query;
+use ipc::{
+    SavedSearch, SavedSearchOp, SavedSearchRequest, SavedSearchResponse, SearchRequest,
+    SearchResponse,
+};
+use parking_lot::Mutex;
+
+use crate::search_handler::search;
+
+const FILE_NAME: &str = "saved_searches.json";
+
+/// Serializes read-modify-write cycles from concurrent IPC connections.
+static LOCK: Mutex<()> = Mutex::new(());
+
+/// The saved searches stored in one state directory.
+pub struct SavedSearches {
+    path: PathBuf,
+}
+
+impl SavedSearches {
+    pub fn in_dir(state_dir: impl AsRef<Path>) -> Self {
+        Self {
+            path: state_dir.as_ref().join(FILE_NAME),
+        }
+    }
+
+    /// All saved searches, by name.
+    pub fn list(&self) -> Result<Vec<SavedSearch>> {
+        let _guard = LOCK.lock();
+        self.load()
+    }
+
+    /// Store `saved`, replacing any search with the same name.
+    pub fn save(&self, mut saved: SavedSearch) -> Result<()> {
+        saved.name = saved.name.trim().to_string();
+        if saved.name.is_empty() {
+            bail!("saved search needs a name");
+        }
+        parse_query(&saved.query).map_err(|err| anyhow!("invalid query: {err}"))?;
+        saved.last_total = None;
+
+        let _guard = LOCK.lock();
+        let mut all = self.load()?;
+        all.retain(|s| s.name != saved.name);
+        all.push(saved);
+        self.store(&mut all)
+    }
+
+    /// Delete the search called `name`; false if there was none.
+    pub fn remove(&self, name: &str) -> Result<bool> {
+        let _guard = LOCK.lock();
+        let mut all = self.load()?;
+        let before = all.len();
+        all.retain(|s| s.name != name);
+        if all.len() == before {
+            return Ok(false);
+        }
+        self.store(&mut all)?;
+        Ok(true)
+    }
+
+    /// Run the search called `name` through `search`. Tracked searches record
+    /// the match count and return how many matches the previous run lacked.
+    pub fn run(
+        &self,
+        name: &str,
+        limit: u32,
+        search: impl FnOnce(SearchRequest) -> SearchResponse,
+    ) -> Result<(SearchResponse, Option<u64>)> {
+        let saved = self
+            .list()?
+            .into_iter()
+            .find(|s| s.name == name)
+            .with_context(|| format!("no saved search named {name:?}"))?;
+        let query = parse_query(&saved.query).map_err(|err| anyhow!("invalid query: {err}"))?;
+        let mut req = SearchRequest::with_query(query)
+            .with_limit(limit)
+            .with_mode(saved.mode);
+        req.sort = saved.sort;
+
+        // The lock is not held while searching; a concurrent edit wins.
+        let resp = search(req);
+        if !saved.track || resp.error.is_some() {
+            return Ok((resp, None));
+        }
+        let new_matches = saved.last_total.map(|last| resp.total.saturating_sub(last));
+
+        let _guard = LOCK.lock();
+        let mut all = self.load()?;
+        if let Some(s) = all
+            .iter_mut()
+            .find(|s| s.name == name && s.query == saved.query)
+        {
+            s.last_total = Some(resp.total);
+            self.store(&mut all)?;
+        }
+        Ok((resp, new_matches))
+    }
+
+    fn load(&self) -> Result<Vec<SavedSearch>> {
+        match std::fs::read(&self.path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .with_context(|| format!("reading {}", self.path.display())),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+            Err(err) => Err(err).with_context(|| format!("reading {}", self.path.display())),
+        }
+    }
+
+    /// Replace the file via a temporary sibling so a crash keeps the old list.
+    fn store(&self, all: &mut [SavedSearch]) -> Result<()> {
+        all.sort_by(|a, b| a.name.cmp(&b.name));
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let tmp = self.path.with_extension("json.tmp");
+        std::fs::write(&tmp, serde_json::to_vec_pretty(all)?)?;
+        std::fs::rename(&tmp, &self.path)
+            .with_context(|| format!("writing {}", self.path.display()))
+    }
+}
+
+/// Answer a saved-search IPC request against the configured state directory.
+pub fn handle_saved_search(req: SavedSearchRequest) -> SavedSearchResponse {
+    let cfg = core_types::config::get_current_config();
+    let store = SavedSearches::in_dir(&cfg.paths.state_dir);
+    let mut resp = SavedSearchResponse {
+        id: req.id,
+        searches: Vec::new(),
+        results: None,
+        new_matches: None,
+        error: None,
+    };
+    let outcome = match req.op {
+        SavedSearchOp::List => Ok(()),
+        SavedSearchOp::Save(saved) => store.save(saved),
+        SavedSearchOp::Run { name, limit } => {
+            store.run(&name, limit, search).map(|(results, new)| {
+                resp.results = Some(results);
+                resp.new_matches = new;
+            })
+        }
+        SavedSearchOp::Remove { name } => store.remove(&name).and_then(|found| {
+            if found {
+                Ok(())
+            } else {
+                Err(anyhow!("no saved search named {name:?}"))
+            }
+        }),
+    };
+    match outcome.and_then(|()| store.list()) {
+        Ok(searches) => resp.searches = searches,
+        Err(err) => resp.error = Some(format!("{err:#}")),
+    }
+    resp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+    use uuid::Uuid;
+
+    fn answer(total: u64) -> impl FnOnce(SearchRequest) -> SearchResponse {
+        move |req| SearchResponse {
+            id: req.id,
+            hits: Vec::new(),
+            total,
+            truncated: false,
+            took_ms: 0,
+            served_by: None,
+            error: None,
+            facets: Vec::new(),
+            explain: None,
+            next_cursor: None,
+            suggestions: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn saved_searches_persist_and_count_new_matches() {
+        let dir = TempDir::new().unwrap();
+        let store = SavedSearches::in_dir(dir.path());
+        assert!(store.list().unwrap().is_empty());
+
+        let mut logs = SavedSearch::new("logs", "ext:log size:>10mb");
+        logs.track = true;
+        store.save(logs).unwrap();
+        store.save(SavedSearch::new("drafts", "draft")).unwrap();
+        assert!(store.save(SavedSearch::new("bad", "size:>")).is_err());
+        assert!(store.save(SavedSearch::new("  ", "x")).is_err());
+
+        let names: Vec<String> = SavedSearches::in_dir(dir.path())
+            .list()
+            .unwrap()
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        assert_eq!(names, ["drafts", "logs"]);
+
+        let (resp, new) = store.run("logs", 10, answer(3)).unwrap();
+        assert_eq!(resp.total, 3);
+        assert_eq!(new, None);
+        let (_, new) = store.run("logs", 10, answer(5)).unwrap();
+        assert_eq!(new, Some(2));
+        let (_, new) = store.run("drafts", 10, answer(5)).unwrap();
+        assert_eq!(new, None);
+        assert!(store.run("missing", 10, answer(0)).is_err());
+
+        assert!(store.remove("drafts").unwrap());
+        assert!(!store.remove("drafts").unwrap());
+        let left = store.list().unwrap();
+        assert_eq!(left.len(), 1);
+        assert_eq!(left[0].last_total, Some(5));
+
+        let req = SavedSearchRequest {
+            id: Uuid::new_v4(),
+            op: SavedSearchOp::Remove {
+                name: "missing".into(),
+            },
+        };
+        assert!(handle_saved_search(req).error.is_some());
+    }
+}
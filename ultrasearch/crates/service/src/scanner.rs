@@ -4,6 +4,7 @@ use crate::scheduler_runtime::{content_job_from_meta, enqueue_content_job};
 use crate::status_provider::{update_status_last_commit, update_status_volumes};
 use anyhow::Result;
 use core_types::FileMeta;
+use core_types::ancestors::AncestorIndex;
 use core_types::config::AppConfig;
 use ipc::VolumeStatus;
 #[cfg(any())]
@@ -215,11 +216,17 @@ fn unix_timestamp_secs() -> i64 {
 
 fn build_content_jobs(metas: &[FileMeta], cfg: &AppConfig) -> (Vec<JobSpec>, u64) {
     let mut total_bytes = 0u64;
+    let chains = AncestorIndex::from_metas(metas);
     let jobs = metas
         .iter()
         .filter_map(|meta| {
-            if let Some(job) = content_job_from_meta(meta, &cfg.extract) {
+            if let Some(mut job) = content_job_from_meta(meta, &cfg.extract) {
                 total_bytes = total_bytes.saturating_add(meta.size);
+                job.ancestors = chains
+                    .ancestors(meta.key, meta.parent)
+                    .into_iter()
+                    .map(|k| k.0)
+                    .collect();
                 Some(job)
             } else {
                 None
@@ -307,6 +307,12 @@ pub fn content_job_from_meta(meta: &FileMeta, extract: &ExtractSection) -> Optio
         max_bytes: Some(to_usize(extract.max_bytes_per_file)),
         max_chars: Some(to_usize(extract.max_chars_per_file)),
         file_size: meta.size,
+        ancestors: meta
+            .parent
+            .filter(|p| *p != meta.key)
+            .map(|p| p.0)
+            .into_iter()
+            .collect(),
     })
 }
 
@@ -332,6 +338,7 @@ mod tests {
             max_bytes: None,
             max_chars: None,
             file_size: 0,
+            ancestors: Vec::new(),
         }
     }
 
@@ -1,22 +1,69 @@
-use anyhow::Result;
+use crate::cursor::{Cursor, Fusion, SearcherLeases};
+use crate::facets::{FacetCollector, FacetCounts};
+use crate::flag_query::FlagsQuery;
+use crate::highlight::Highlighter;
+use crate::meta_ingest::open_meta_index;
+use crate::metrics::{record_result_cache, record_search_timeout};
+use crate::not_query::NotQuery;
+use crate::planner::QueryPlanner;
+use crate::result_cache::{Generations, ResultCache};
+use crate::sort_collector::{Position, SortedTopDocs, compare_hits};
+use crate::spelling::{self, FEW_RESULTS};
+use crate::time_budget::Budget;
+use anyhow::{Result, anyhow, bail};
 use content_index::{ContentIndex, open_or_create as open_content};
+use core_types::config::{HybridMerge, SearchSection};
+use core_types::{DocKey, FileFlags};
 use ipc::{
-    FieldKind, QueryExpr, SearchHit, SearchMode, SearchRequest, SearchResponse, TermExpr,
-    TermModifier,
+    Completion, FieldKind, HitExplanation, HitSource, IndexQuery, QueryExpr, RangeExpr, RangeOp,
+    RangeValue, SearchExplain, SearchHit, SearchMode, SearchRequest, SearchResponse, SortDirection,
+    SortField, SortSpec, SuggestRequest, SuggestResponse, Suggestion, TermExpr, TermModifier,
 };
-use meta_index::{MetaFields, MetaIndex, open_or_create_index, open_reader};
-use std::path::Path;
-use std::sync::OnceLock;
-use std::time::Instant;
+use meta_index::fst::{FST_FILE_NAME, FstIndex};
+use meta_index::{MetaFields, MetaIndex, open_reader};
+use std::collections::{HashMap, HashSet};
+use std::num::NonZeroUsize;
+use std::ops::Bound;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::{Instant, SystemTime};
 use tantivy::collector::{Count, TopDocs};
-use tantivy::query::{BooleanQuery, Occur, Query, QueryParser, TermQuery};
-use tantivy::schema::{Document, IndexRecordOption, TantivyDocument, Value};
-use tantivy::{IndexReader, Score, Term};
+use tantivy::query::{
+    BooleanQuery, EnableScoring, FuzzyTermQuery, Occur, PhrasePrefixQuery, PhraseQuery, Query,
+    QueryParser, RangeQuery, RegexQuery, TermQuery, Weight,
+};
+use tantivy::schema::{Document, Field, IndexRecordOption, TantivyDocument, Value};
+use tantivy::{DocAddress, DocSet, IndexReader, Score, Searcher, Term};
 use tracing::warn;
 
+/// Receives the hits of each index pass of a streamed search.
+pub type HitSink<'a> = dyn FnMut(HitSource, &[SearchHit]) + 'a;
+
 /// Trait for handling search requests.
 pub trait SearchHandler: Send + Sync {
     fn search(&self, req: SearchRequest) -> SearchResponse;
+
+    /// Like `search`, but hands each index pass's hits to `sink` as soon as
+    /// they are ranked. The default reports the final hits as one batch.
+    fn search_stream(&self, req: SearchRequest, sink: &mut HitSink) -> SearchResponse {
+        let resp = self.search(req);
+        sink(HitSource::Meta, &resp.hits);
+        resp
+    }
+
+    /// File names starting with a prefix. Called per keystroke, so
+    /// implementations must not run index queries; the default has none.
+    fn suggest(&self, req: SuggestRequest) -> SuggestResponse {
+        SuggestResponse {
+            id: req.id,
+            completions: Vec::new(),
+        }
+    }
+
+    /// Bytes the metadata index spends on name trigrams; `None` without them.
+    fn name_ngram_bytes(&self) -> Option<u64> {
+        None
+    }
 }
 
 /// Simple placeholder handler that returns an empty response.
@@ -32,6 +79,11 @@ impl SearchHandler for StubSearchHandler {
             truncated: false,
             took_ms: 0,
             served_by: Some("service-stub".into()),
+            error: None,
+            facets: Vec::new(),
+            explain: None,
+            next_cursor: None,
+            suggestions: Vec::new(),
         }
     }
 }
@@ -41,6 +93,104 @@ pub struct UnifiedSearchHandler {
     meta: MetaIndex,
     meta_reader: IndexReader,
     content: Option<(ContentIndex, IndexReader)>,
+    /// Name FST next to the metadata index, used for regex name searches
+    /// and completions.
+    names: NameFst,
+    /// Hybrid merge strategy and weights (`[search]` config).
+    ranking: SearchSection,
+    /// Searcher generations pinned by outstanding cursors.
+    meta_leases: SearcherLeases,
+    content_leases: SearcherLeases,
+    /// Recent responses for the current reader generations.
+    results: Option<ResultCache>,
+}
+
+/// Index snapshot a page reads, where in the result order it starts and
+/// how long it may take.
+#[derive(Clone)]
+struct Page {
+    meta: Searcher,
+    content: Option<Searcher>,
+    /// Resume strictly after this hit; set for cursor requests.
+    after: Option<Position>,
+    /// Hits before this page: the request offset, or what a cursor served.
+    start: usize,
+    /// Fused-list tier of a hybrid cursor.
+    fusion: Option<Fusion>,
+    /// `SearchRequest::timeout`, shared by every pass of the search.
+    budget: Budget,
+}
+
+impl Page {
+    fn generations(&self) -> Generations {
+        (
+            self.meta.generation().generation_id(),
+            self.content
+                .as_ref()
+                .map(|s| s.generation().generation_id()),
+        )
+    }
+}
+
+/// The name FST that ingest rewrites after each metadata commit. It is
+/// reopened once the metadata reader moves to a new generation and the
+/// file on disk has changed.
+struct NameFst {
+    path: PathBuf,
+    loaded: RwLock<Option<LoadedFst>>,
+}
+
+struct LoadedFst {
+    /// Metadata generation the file was last checked for.
+    generation: u64,
+    modified: SystemTime,
+    fst: Arc<FstIndex>,
+}
+
+impl NameFst {
+    fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            loaded: RwLock::new(None),
+        }
+    }
+
+    /// The FST matching metadata generation `generation`, if there is one.
+    fn get(&self, generation: u64) -> Option<Arc<FstIndex>> {
+        if let Some(loaded) = self.loaded.read().unwrap().as_ref()
+            && loaded.generation == generation
+        {
+            return Some(loaded.fst.clone());
+        }
+
+        let mut slot = self.loaded.write().unwrap();
+        let Ok(modified) = std::fs::metadata(&self.path).and_then(|m| m.modified()) else {
+            *slot = None;
+            return None;
+        };
+        if let Some(loaded) = slot.as_ref()
+            && loaded.modified == modified
+        {
+            // Ingest has not rewritten the file for this commit yet; keep
+            // the old generation so the next call looks again.
+            return Some(loaded.fst.clone());
+        }
+        match FstIndex::open(&self.path) {
+            Ok(fst) => {
+                let fst = Arc::new(fst);
+                *slot = Some(LoadedFst {
+                    generation,
+                    modified,
+                    fst: fst.clone(),
+                });
+                Some(fst)
+            }
+            Err(e) => {
+                warn!("failed to open name FST at {:?}: {}", self.path, e);
+                slot.as_ref().map(|loaded| loaded.fst.clone())
+            }
+        }
+    }
 }
 
 impl UnifiedSearchHandler {
@@ -49,7 +199,7 @@ impl UnifiedSearchHandler {
             "UnifiedSearchHandler: opening meta index at {:?}",
             meta_path
         );
-        let meta = open_or_create_index(meta_path)?;
+        let meta = open_meta_index(meta_path)?;
         let meta_reader = open_reader(&meta)?;
 
         let content = match open_content(content_path) {
@@ -67,13 +217,33 @@ impl UnifiedSearchHandler {
             }
         };
 
+        let names = NameFst::new(meta_path.join(FST_FILE_NAME));
+
         Ok(Self {
             meta,
             meta_reader,
             content,
+            names,
+            ranking: SearchSection::default(),
+            meta_leases: SearcherLeases::default(),
+            content_leases: SearcherLeases::default(),
+            results: NonZeroUsize::new(SearchSection::default().result_cache_entries)
+                .map(ResultCache::new),
         })
     }
 
+    /// Use the `[search]` ranking settings instead of the defaults.
+    pub fn with_ranking(mut self, ranking: SearchSection) -> Self {
+        self.ranking = ranking;
+        self
+    }
+
+    /// Keep up to `entries` recent responses; 0 disables the cache.
+    pub fn with_result_cache(mut self, entries: usize) -> Self {
+        self.results = NonZeroUsize::new(entries).map(ResultCache::new);
+        self
+    }
+
     fn build_meta_query(&self, expr: &QueryExpr) -> Result<Box<dyn Query>> {
         self.build_query(expr, &self.meta.fields, &self.meta.index)
     }
@@ -86,17 +256,20 @@ impl UnifiedSearchHandler {
     ) -> Result<Box<dyn Query>> {
         Ok(match expr {
             QueryExpr::Term(t) => self.term_query(t, fields, index)?,
-            QueryExpr::Range(_) => Box::new(BooleanQuery::new(vec![])),
-            QueryExpr::Not(inner) => Box::new(BooleanQuery::new(vec![(
-                Occur::MustNot,
-                self.build_query(inner, fields, index)?,
-            )])),
-            QueryExpr::And(items) => Box::new(BooleanQuery::new(
+            QueryExpr::Range(r) => range_query(
+                r,
+                RangeFields::new(fields.size, fields.modified, fields.created),
+            )?,
+            QueryExpr::Not(inner) => negate(self.build_query(inner, fields, index)?),
+            QueryExpr::And(items) => conjunction(
                 items
                     .iter()
-                    .map(|q| Ok((Occur::Must, self.build_query(q, fields, index)?)))
+                    .map(|q| {
+                        let (occur, q) = conjunct(q);
+                        Ok((occur, self.build_query(q, fields, index)?))
+                    })
                     .collect::<Result<Vec<_>>>()?,
-            )),
+            ),
             QueryExpr::Or(items) => Box::new(BooleanQuery::new(
                 items
                     .iter()
@@ -126,42 +299,84 @@ impl UnifiedSearchHandler {
         for field in target_fields {
             match field {
                 FieldKind::Ext => {
-                    let t = Term::from_field_text(fields.ext, value);
-                    clauses.push((
-                        Occur::Should,
-                        Box::new(TermQuery::new(t, IndexRecordOption::WithFreqs)) as Box<dyn Query>,
-                    ));
+                    let q: Option<Box<dyn Query>> = match term.modifier {
+                        TermModifier::Prefix => prefix_query(index, fields.ext, value)?,
+                        TermModifier::Glob => Some(glob_query(fields.ext, value)?),
+                        TermModifier::Regex => Some(regex_query(fields.ext, value)?),
+                        TermModifier::Substring => Some(substring_query(fields.ext, value)?),
+                        _ => {
+                            let t = Term::from_field_text(fields.ext, value);
+                            Some(Box::new(TermQuery::new(t, IndexRecordOption::WithFreqs)))
+                        }
+                    };
+                    clauses.extend(q.map(|q| (Occur::Should, q)));
                 }
-                FieldKind::Name | FieldKind::Path => match term.modifier {
-                    TermModifier::Prefix => {
-                        let pf = if matches!(field, FieldKind::Name) {
-                            fields.name
-                        } else {
-                            fields.path
-                        };
-                        // Fallback for PrefixQuery removal
-                        let t = Term::from_field_text(pf, value);
-                        clauses.push((
-                            Occur::Should,
-                            Box::new(TermQuery::new(t, IndexRecordOption::WithFreqs))
-                                as Box<dyn Query>,
-                        ));
-                    }
-                    _ => {
-                        let mut parser = QueryParser::for_index(
-                            index,
-                            vec![if matches!(field, FieldKind::Name) {
-                                fields.name
+                FieldKind::Name | FieldKind::Path => {
+                    let tf = if matches!(field, FieldKind::Name) {
+                        fields.name
+                    } else {
+                        fields.path
+                    };
+                    match term.modifier {
+                        TermModifier::Prefix => {
+                            if let Some(q) = prefix_query(index, tf, value)? {
+                                clauses.push((Occur::Should, q));
+                            }
+                        }
+                        TermModifier::Fuzzy(distance) => {
+                            if let Some(q) = fuzzy_query(index, tf, value, distance)? {
+                                clauses.push((Occur::Should, q));
+                            }
+                        }
+                        TermModifier::Glob => {
+                            let gf = if matches!(field, FieldKind::Name) {
+                                fields.name_raw
                             } else {
                                 fields.path
-                            }],
-                        );
-                        parser.set_conjunction_by_default();
-                        if let Ok(q) = parser.parse_query(value) {
+                            };
+                            clauses.push((Occur::Should, glob_query(gf, value)?));
+                        }
+                        TermModifier::Regex => {
+                            let rf = if matches!(field, FieldKind::Name) {
+                                fields.name_raw
+                            } else {
+                                fields.path
+                            };
+                            clauses.push((Occur::Should, regex_query(rf, value)?));
+                        }
+                        TermModifier::Substring => {
+                            let q = if matches!(field, FieldKind::Name) {
+                                name_substring_query(index, fields, value)?
+                            } else {
+                                substring_query(fields.path, value)?
+                            };
                             clauses.push((Occur::Should, q));
                         }
+                        TermModifier::Phrase => {
+                            if let Some(q) = phrase_query(index, tf, value)? {
+                                clauses.push((Occur::Should, q));
+                            }
+                        }
+                        _ => {
+                            let mut parser = QueryParser::for_index(index, vec![tf]);
+                            parser.set_conjunction_by_default();
+                            if let Ok(q) = parser.parse_query(value) {
+                                clauses.push((Occur::Should, q));
+                            }
+                        }
                     }
-                },
+                }
+                FieldKind::Flags => {
+                    clauses.push((Occur::Should, flags_query(fields.flags, value)?))
+                }
+                FieldKind::Volume => {
+                    clauses.push((Occur::Should, volume_query(fields.volume, value)?))
+                }
+                FieldKind::Kind => clauses.push((Occur::Should, kind_query(fields.kind, value)?)),
+                FieldKind::Folder => clauses.push((
+                    Occur::Should,
+                    ancestor_query(fields.ancestors, self.resolve_folder(value)?),
+                )),
                 _ => {}
             }
         }
@@ -169,6 +384,45 @@ impl UnifiedSearchHandler {
         Ok(Box::new(BooleanQuery::new(clauses)))
     }
 
+    /// Resolve an `in:` value to the folder's `DocKey`.
+    ///
+    /// Accepts the key's display form directly; otherwise looks the path up
+    /// among indexed directories (case-insensitive, either slash style).
+    fn resolve_folder(&self, value: &str) -> Result<DocKey> {
+        if let Ok(key) = value.parse::<DocKey>() {
+            return Ok(key);
+        }
+        let fields = &self.meta.fields;
+        let wanted = normalize_folder(value);
+        let Some(path) = phrase_query(&self.meta.index, fields.path, value)? else {
+            bail!("`in:` expects a folder path, got `{value}`");
+        };
+        let query = BooleanQuery::new(vec![
+            (Occur::Must, path),
+            (
+                Occur::Must,
+                Box::new(FlagsQuery::any(
+                    fields.flags,
+                    FileFlags::IS_DIR.bits() as u64,
+                )),
+            ),
+        ]);
+
+        let searcher = self.meta_reader.searcher();
+        let candidates = searcher.search(&query, &TopDocs::with_limit(FOLDER_CANDIDATES))?;
+        for (_, addr) in candidates {
+            let doc: TantivyDocument = searcher.doc(addr)?;
+            let matches = doc
+                .get_first(fields.path)
+                .and_then(|v| v.as_str())
+                .is_some_and(|p| normalize_folder(p) == wanted);
+            if matches && let Some(key) = doc.get_first(fields.doc_key).and_then(|v| v.as_u64()) {
+                return Ok(DocKey(key));
+            }
+        }
+        bail!("folder not found: `{value}`")
+    }
+
     fn build_content_query(&self, expr: &QueryExpr) -> Result<Box<dyn Query>> {
         if let Some((idx, _)) = &self.content {
             // For content query, default fields might include content + name/path
@@ -180,17 +434,20 @@ impl UnifiedSearchHandler {
 
             Ok(match expr {
                 QueryExpr::Term(t) => self.term_query_content(t, &idx.fields, &idx.index)?,
-                QueryExpr::Range(_) => Box::new(BooleanQuery::new(vec![])),
-                QueryExpr::Not(inner) => Box::new(BooleanQuery::new(vec![(
-                    Occur::MustNot,
-                    self.build_content_query(inner)?,
-                )])),
-                QueryExpr::And(items) => Box::new(BooleanQuery::new(
+                QueryExpr::Range(r) => range_query(
+                    r,
+                    RangeFields::new(idx.fields.size, idx.fields.modified, idx.fields.created),
+                )?,
+                QueryExpr::Not(inner) => negate(self.build_content_query(inner)?),
+                QueryExpr::And(items) => conjunction(
                     items
                         .iter()
-                        .map(|q| Ok((Occur::Must, self.build_content_query(q)?)))
+                        .map(|q| {
+                            let (occur, q) = conjunct(q);
+                            Ok((occur, self.build_content_query(q)?))
+                        })
                         .collect::<Result<Vec<_>>>()?,
-                )),
+                ),
                 QueryExpr::Or(items) => Box::new(BooleanQuery::new(
                     items
                         .iter()
@@ -221,25 +478,77 @@ impl UnifiedSearchHandler {
 
         let mut clauses = Vec::new();
         for field in target_fields {
-            // Map FieldKind to tantivy::schema::Field in ContentFields
-            let t_field = match field {
-                FieldKind::Name => Some(fields.name),
-                FieldKind::Path => Some(fields.path),
-                FieldKind::Ext => Some(fields.ext),
-                FieldKind::Content => Some(fields.content),
+            match field {
+                FieldKind::Volume => {
+                    clauses.push((Occur::Should, volume_query(fields.volume, value)?));
+                    continue;
+                }
+                // The content index does not store attributes.
+                FieldKind::Flags => bail!("is: filters are only available in name search"),
+                FieldKind::Kind => {
+                    clauses.push((Occur::Should, kind_query(fields.kind, value)?));
+                    continue;
+                }
+                FieldKind::Folder => {
+                    let folder = self.resolve_folder(value)?;
+                    clauses.push((Occur::Should, ancestor_query(fields.ancestors, folder)));
+                    continue;
+                }
+                _ => {}
+            }
+
+            // Map FieldKind to tantivy::schema::Field in ContentFields; text
+            // sits in one of several fields depending on how it was analyzed.
+            let t_fields = match field {
+                FieldKind::Name => vec![fields.name],
+                FieldKind::Path => vec![fields.path],
+                FieldKind::Ext => vec![fields.ext],
+                FieldKind::Content => fields.text_fields().to_vec(),
                 // Other fields like size/modified handled in ranges or ignored for text search
-                _ => None,
+                _ => Vec::new(),
             };
 
-            if let Some(tf) = t_field {
+            for tf in t_fields {
                 match term.modifier {
                     TermModifier::Prefix => {
-                        let t = Term::from_field_text(tf, value);
-                        clauses.push((
-                            Occur::Should,
-                            Box::new(TermQuery::new(t, IndexRecordOption::WithFreqs))
-                                as Box<dyn Query>,
-                        ));
+                        if let Some(q) = prefix_query(index, tf, value)? {
+                            clauses.push((Occur::Should, q));
+                        }
+                    }
+                    TermModifier::Fuzzy(distance) if field != FieldKind::Ext => {
+                        if let Some(q) = fuzzy_query(index, tf, value, distance)? {
+                            clauses.push((Occur::Should, q));
+                        }
+                    }
+                    TermModifier::Glob => {
+                        let gf = if field == FieldKind::Name {
+                            fields.name_raw
+                        } else {
+                            tf
+                        };
+                        clauses.push((Occur::Should, glob_query(gf, value)?));
+                    }
+                    TermModifier::Regex => {
+                        let rf = if field == FieldKind::Name {
+                            fields.name_raw
+                        } else {
+                            tf
+                        };
+                        clauses.push((Occur::Should, regex_query(rf, value)?));
+                    }
+                    // The content index has no name trigrams.
+                    TermModifier::Substring => {
+                        let sf = if field == FieldKind::Name {
+                            fields.name_raw
+                        } else {
+                            tf
+                        };
+                        clauses.push((Occur::Should, substring_query(sf, value)?));
+                    }
+                    TermModifier::Phrase => {
+                        if let Some(q) = phrase_query(index, tf, value)? {
+                            clauses.push((Occur::Should, q));
+                        }
                     }
                     _ => {
                         let mut parser = QueryParser::for_index(index, vec![tf]);
@@ -254,28 +563,66 @@ impl UnifiedSearchHandler {
         Ok(Box::new(BooleanQuery::new(clauses)))
     }
 
-    fn search_meta(&self, req: &SearchRequest) -> SearchResponse {
+    fn search_meta(&self, req: &SearchRequest, page: &Page) -> SearchResponse {
+        let facets = req.facets.as_ref().map(FacetCollector::new);
+        with_facets(req, self.search_meta_faceted(req, facets, page))
+    }
+
+    fn search_meta_faceted(
+        &self,
+        req: &SearchRequest,
+        facets: Option<FacetCollector>,
+        page: &Page,
+    ) -> (SearchResponse, Option<FacetCounts>) {
+        if let QueryExpr::Term(term) = &req.query
+            && term.modifier == TermModifier::Regex
+            && matches!(term.field, None | Some(FieldKind::Name))
+            // Facets need a pass over the index.
+            && facets.is_none()
+            // The FST yields names in ascending order only.
+            && matches!(
+                req.sort,
+                None | Some(SortSpec {
+                    field: SortField::Name,
+                    direction: SortDirection::Asc,
+                })
+            )
+            && let Some(names) = self.names.get(page.meta.generation().generation_id())
+        {
+            return (self.search_names_regex(&names, term, req, page), None);
+        }
+
         let start = Instant::now();
         let limit = req.limit.max(1) as usize;
-        let offset = req.offset as usize;
+        // A cursor's position already excludes the earlier pages.
+        let offset = if page.after.is_some() { 0 } else { page.start };
 
-        let searcher = self.meta_reader.searcher();
+        let searcher = page.meta.clone();
         let query = match self.build_meta_query(&req.query) {
             Ok(q) => q,
             Err(err) => {
                 warn!(error = %err, "failed to build meta query");
-                return StubSearchHandler.search(req.clone());
+                return (error_response(req, &err), None);
             }
         };
+        let exact = match exact_variant(&req.query)
+            .map(|e| self.build_meta_query(&e))
+            .transpose()
+            .and_then(|q| exact_weight(&searcher, q))
+        {
+            Ok(w) => w,
+            Err(err) => return (error_response(req, &err), None),
+        };
 
         tracing::info!("executing meta query: {:?}", query);
 
         let top_k = limit.saturating_add(offset);
-        let (hits, total) = match searcher.search(&query, &(TopDocs::with_limit(top_k), Count)) {
+        let (hits, total, counts) = match top_docs(&searcher, &query, req.sort, top_k, page, facets)
+        {
             Ok(r) => r,
             Err(err) => {
                 warn!(error = %err, "meta search execution failed");
-                return StubSearchHandler.search(req.clone());
+                return (error_response(req, &err.into()), None);
             }
         };
 
@@ -285,200 +632,1220 @@ impl UnifiedSearchHandler {
             hits.len()
         );
 
+        let highlighter = Highlighter::new(&req.query);
+        let fields = &self.meta.fields;
         let out = hits
             .into_iter()
             .skip(offset)
             .filter_map(|(score, addr)| {
                 let retrieved = searcher.doc::<TantivyDocument>(addr).ok()?;
-                to_hit(&retrieved, &self.meta.fields, score)
+                let mut hit = to_hit(&retrieved, fields, score)?;
+                hit.approximate = is_approximate(&searcher, exact.as_deref(), addr);
+                highlight_name_path(
+                    &mut hit,
+                    &highlighter,
+                    &self.meta.index,
+                    fields.name,
+                    fields.path,
+                );
+                if req.explain {
+                    hit.explain = hit_explanation("meta", &searcher, &query, addr, score);
+                }
+                Some(hit)
             })
             .collect();
 
-        SearchResponse {
+        let resp = SearchResponse {
             id: req.id,
             hits: out,
             total: total as u64,
             truncated: false, // MVP
             took_ms: start.elapsed().as_millis().min(u32::MAX as u128) as u32,
             served_by: None,
+            error: None,
+            facets: Vec::new(),
+            explain: explain_for(req, SearchMode::NameOnly, vec![index_query("meta", &query)]),
+            next_cursor: None,
+            suggestions: Vec::new(),
+        };
+        (resp, counts)
+    }
+
+    /// Stream regex matches straight from the name FST, then load the
+    /// matching documents from the metadata index in FST (name) order.
+    ///
+    /// The FST has no generations, so cursors page it by count.
+    fn search_names_regex(
+        &self,
+        names: &FstIndex,
+        term: &TermExpr,
+        req: &SearchRequest,
+        page: &Page,
+    ) -> SearchResponse {
+        let start = Instant::now();
+        let limit = req.limit.max(1) as usize;
+        let offset = page.start;
+        let top_k = limit.saturating_add(offset);
+
+        let (keys, matched) = match names.search_regex(&term.value, top_k) {
+            Ok(found) => found,
+            Err(err) => {
+                warn!(error = %err, "invalid name regex");
+                return error_response(req, &err);
+            }
+        };
+        let wanted: Vec<core_types::DocKey> = keys.into_iter().skip(offset).collect();
+        if wanted.is_empty() {
+            return SearchResponse {
+                id: req.id,
+                hits: Vec::new(),
+                total: matched,
+                truncated: false,
+                took_ms: start.elapsed().as_millis().min(u32::MAX as u128) as u32,
+                served_by: None,
+                error: None,
+                facets: Vec::new(),
+                explain: explain_for(
+                    req,
+                    SearchMode::NameOnly,
+                    vec![index_query("names", format_args!("regex `{}`", term.value))],
+                ),
+                next_cursor: None,
+                suggestions: Vec::new(),
+            };
+        }
+
+        let searcher = &page.meta;
+        let doc_key = self.meta.fields.doc_key;
+        let query = BooleanQuery::new(
+            wanted
+                .iter()
+                .map(|key| (Occur::Should, u64_equals(doc_key, key.0)))
+                .collect(),
+        );
+        let found = match searcher.search(&query, &TopDocs::with_limit(wanted.len())) {
+            Ok(found) => found,
+            Err(err) => {
+                warn!(error = %err, "meta lookup for regex hits failed");
+                return error_response(req, &err.into());
+            }
+        };
+
+        let mut by_key: std::collections::HashMap<core_types::DocKey, SearchHit> = found
+            .into_iter()
+            .filter_map(|(_, addr)| {
+                let retrieved = searcher.doc::<TantivyDocument>(addr).ok()?;
+                to_hit(&retrieved, &self.meta.fields, 1.0)
+            })
+            .map(|hit| (hit.key, hit))
+            .collect();
+        let hits: Vec<SearchHit> = wanted.iter().filter_map(|k| by_key.remove(k)).collect();
+
+        SearchResponse {
+            id: req.id,
+            hits,
+            total: matched,
+            truncated: false,
+            took_ms: start.elapsed().as_millis().min(u32::MAX as u128) as u32,
+            served_by: None,
+            error: None,
+            facets: Vec::new(),
+            explain: explain_for(
+                req,
+                SearchMode::NameOnly,
+                vec![index_query("names", format_args!("regex `{}`", term.value))],
+            ),
+            next_cursor: None,
+            suggestions: Vec::new(),
         }
     }
 
-    fn search_content(&self, req: &SearchRequest) -> SearchResponse {
-        let Some((content_idx, reader)) = &self.content else {
-            return StubSearchHandler.search(req.clone());
+    fn search_content(&self, req: &SearchRequest, page: &Page) -> SearchResponse {
+        let facets = req.facets.as_ref().map(FacetCollector::new);
+        with_facets(req, self.search_content_faceted(req, facets, page))
+    }
+
+    fn search_content_faceted(
+        &self,
+        req: &SearchRequest,
+        facets: Option<FacetCollector>,
+        page: &Page,
+    ) -> (SearchResponse, Option<FacetCounts>) {
+        let (Some((content_idx, _)), Some(searcher)) = (&self.content, page.content.clone()) else {
+            return (StubSearchHandler.search(req.clone()), None);
         };
 
         let start = Instant::now();
         let limit = req.limit.max(1) as usize;
-        let offset = req.offset as usize;
+        let offset = if page.after.is_some() { 0 } else { page.start };
 
-        let searcher = reader.searcher();
         let query = match self.build_content_query(&req.query) {
             Ok(q) => q,
             Err(err) => {
                 warn!(error = %err, "failed to build content query");
-                return StubSearchHandler.search(req.clone());
+                return (error_response(req, &err), None);
             }
         };
+        let exact = match exact_variant(&req.query)
+            .map(|e| self.build_content_query(&e))
+            .transpose()
+            .and_then(|q| exact_weight(&searcher, q))
+        {
+            Ok(w) => w,
+            Err(err) => return (error_response(req, &err), None),
+        };
 
         let top_k = limit.saturating_add(offset);
-        let (hits, total) = match searcher.search(&query, &(TopDocs::with_limit(top_k), Count)) {
+        let (hits, total, counts) = match top_docs(&searcher, &query, req.sort, top_k, page, facets)
+        {
             Ok(r) => r,
             Err(err) => {
                 warn!(error = %err, "content search execution failed");
-                return StubSearchHandler.search(req.clone());
+                return (error_response(req, &err.into()), None);
             }
         };
 
+        let highlighter = Highlighter::new(&req.query);
+        let (index, fields) = (&content_idx.index, &content_idx.fields);
         let out = hits
             .into_iter()
             .skip(offset)
             .filter_map(|(score, addr)| {
                 let retrieved = searcher.doc::<TantivyDocument>(addr).ok()?;
                 // We need to_hit equivalent for content fields
-                to_hit_content(&retrieved, &content_idx.fields, score)
+                let mut hit = to_hit_content(&retrieved, fields, score)?;
+                hit.approximate = is_approximate(&searcher, exact.as_deref(), addr);
+                highlight_name_path(&mut hit, &highlighter, index, fields.name, fields.path);
+                // Indexes built before content was stored have nothing to show.
+                let stored = fields.text_fields().into_iter().find_map(|field| {
+                    let text = retrieved.get_first(field)?.as_str()?;
+                    Some((field, text))
+                });
+                if let Some((field, text)) = stored
+                    && let Some((snippet, ranges)) = highlighter.snippet(index, field, text)
+                {
+                    hit.snippet = Some(snippet);
+                    hit.highlights.snippet = ranges;
+                }
+                if req.explain {
+                    hit.explain = hit_explanation("content", &searcher, &query, addr, score);
+                }
+                Some(hit)
             })
             .collect();
 
-        SearchResponse {
+        let resp = SearchResponse {
             id: req.id,
             hits: out,
             total: total as u64,
             truncated: false,
             took_ms: start.elapsed().as_millis().min(u32::MAX as u128) as u32,
             served_by: None,
-        }
+            error: None,
+            facets: Vec::new(),
+            explain: explain_for(
+                req,
+                SearchMode::Content,
+                vec![index_query("content", &query)],
+            ),
+            next_cursor: None,
+            suggestions: Vec::new(),
+        };
+        (resp, counts)
     }
 
-    fn search_hybrid(&self, req: &SearchRequest) -> SearchResponse {
-        // Parallel execution? For MVP, sequential.
-        // 1. Meta search
-        // 2. Content search
-        // 3. Merge by DocKey
-
+    fn search_hybrid(
+        &self,
+        req: &SearchRequest,
+        page: &Page,
+        sink: &mut HitSink,
+    ) -> (SearchResponse, Fusion) {
         let start = Instant::now();
         let limit = req.limit.max(1) as usize;
 
-        // Fetch more to allow merging
-        let fetch_limit = limit * 2;
-
-        // Create sub-requests
-        let mut meta_req = req.clone();
-        meta_req.limit = fetch_limit as u32;
-        meta_req.offset = 0; // We handle paging after merge? Or simple approach: no deep paging in hybrid for now.
-
-        let meta_resp = self.search_meta(&meta_req);
+        // Fused scores depend on how deep each index was read, so pages of
+        // one search read the fused list at the depth of its first page.
+        // Once that list runs out, the next tier reads twice as deep and
+        // leaves out the files either index ranked above the old depth:
+        // earlier tiers served all of them. Both sides rerun on the page's
+        // snapshot, so every tier ranks the same way on every page.
+        let mut fusion = page.fusion.unwrap_or(Fusion {
+            depth: (page.start + limit) as u64,
+            skip: 0,
+            tier_start: 0,
+        });
+        let sub_page = Page {
+            after: None,
+            start: 0,
+            fusion: None,
+            ..page.clone()
+        };
 
-        let mut hits_map: std::collections::HashMap<core_types::DocKey, SearchHit> =
-            std::collections::HashMap::new();
+        let mut facets = req.facets.as_ref().map(FacetCollector::new);
+        let mut counts = None;
+        let mut queries = Vec::new();
+        let mut total = 0;
+        let mut hits = Vec::new();
+        for tier_no in 0.. {
+            let depth = fusion.depth as usize;
+            let tier = match self.hybrid_tier(req, &sub_page, depth, facets.take(), sink) {
+                Ok(tier) => tier,
+                Err(resp) => return (*resp, fusion),
+            };
+            // Counts and queries are the same on every tier.
+            if tier_no == 0 {
+                counts = tier.counts;
+                queries = tier.queries;
+            }
+            let exhausted = tier.meta_hits.len() < depth && tier.content_hits.len() < depth;
+            let skip = fusion.skip as usize;
+            let served: HashSet<DocKey> = tier.meta_hits[..skip.min(tier.meta_hits.len())]
+                .iter()
+                .chain(&tier.content_hits[..skip.min(tier.content_hits.len())])
+                .map(|hit| hit.key)
+                .collect();
 
-        for hit in meta_resp.hits {
-            hits_map.insert(hit.key, hit);
-        }
+            // Files matching both indexes are counted once in `merged`; the
+            // per-index totals bound the union from below.
+            let mut merged = merge_hybrid(tier.meta_hits, tier.content_hits, &self.ranking);
+            total = total.max(tier.total).max(merged.len() as u64);
+            merged.retain(|hit| !served.contains(&hit.key));
+            merged.sort_by(|a, b| compare_hits(req.sort, a, b));
 
-        if self.content.is_some() {
-            let mut content_req = req.clone();
-            content_req.limit = fetch_limit as u32;
-            content_req.offset = 0;
-            let content_resp = self.search_content(&content_req);
-
-            for hit in content_resp.hits {
-                hits_map
-                    .entry(hit.key)
-                    .and_modify(|e| {
-                        e.score = e.score.max(hit.score); // Max score strategy? Or sum? Max is safer for boolean queries.
-                        if e.snippet.is_none() {
-                            e.snippet = hit.snippet.clone();
-                        }
-                    })
-                    .or_insert(hit);
+            let position = page.start + hits.len() - fusion.tier_start as usize;
+            let wanted = limit - hits.len();
+            hits.extend(merged.into_iter().skip(position).take(wanted));
+            if hits.len() == limit || exhausted || page.budget.expired() {
+                break;
             }
+            fusion = Fusion {
+                depth: fusion.depth.saturating_mul(2),
+                skip: fusion.depth,
+                tier_start: (page.start + hits.len()) as u64,
+            };
         }
 
-        let mut merged: Vec<SearchHit> = hits_map.into_values().collect();
-        merged.sort_by(|a, b| {
-            b.score
-                .partial_cmp(&a.score)
-                .unwrap_or(std::cmp::Ordering::Equal)
-        });
-
-        let offset = req.offset as usize;
-        let total = merged.len();
-        let hits = merged.into_iter().skip(offset).take(limit).collect();
-
-        SearchResponse {
+        let resp = SearchResponse {
             id: req.id,
             hits,
-            total: total as u64, // Approx
+            total, // Approx
             truncated: false,
             took_ms: start.elapsed().as_millis().min(u32::MAX as u128) as u32,
             served_by: None,
+            error: None,
+            facets: Vec::new(),
+            explain: explain_for(req, SearchMode::Hybrid, queries),
+            next_cursor: None,
+            suggestions: Vec::new(),
+        };
+        (with_facets(req, (resp, counts)), fusion)
+    }
+
+    /// Each index's top `depth` hits for one tier of `search_hybrid`, with
+    /// facet counts when `facets` is given.
+    fn hybrid_tier(
+        &self,
+        req: &SearchRequest,
+        page: &Page,
+        depth: usize,
+        mut facets: Option<FacetCollector>,
+        sink: &mut HitSink,
+    ) -> std::result::Result<HybridTier, Box<SearchResponse>> {
+        let mut queries = Vec::new();
+
+        // Metadata runs first: it is the faster index, so streamed searches
+        // show name matches while the content pass is still running. It
+        // records the keys it counted so the content pass can skip them.
+        let mut meta_req = req.clone();
+        meta_req.limit = depth as u32;
+        meta_req.offset = 0;
+        let (meta_resp, mut counts) =
+            self.search_meta_faceted(&meta_req, facets.clone().map(|f| f.keeping_keys()), page);
+        if meta_resp.error.is_some() {
+            return Err(Box::new(meta_resp));
+        }
+        sink(HitSource::Meta, &meta_resp.hits);
+        queries.extend(meta_resp.explain.map(|e| e.queries).unwrap_or_default());
+        if let Some(c) = &mut counts {
+            let seen: HashSet<u64> = std::mem::take(&mut c.keys).into_iter().collect();
+            facets = facets.map(|f| f.skipping(Arc::new(seen)));
+        }
+
+        let mut content_hits = Vec::new();
+        let mut content_total = 0;
+        if self.content.is_some() && !uses_field(&req.query, FieldKind::Flags) {
+            let mut content_req = req.clone();
+            content_req.limit = depth as u32;
+            content_req.offset = 0;
+            let (content_resp, content_counts) =
+                self.search_content_faceted(&content_req, facets, page);
+            if content_resp.error.is_some() {
+                return Err(Box::new(content_resp));
+            }
+            sink(HitSource::Content, &content_resp.hits);
+            content_total = content_resp.total;
+            content_hits = content_resp.hits;
+            queries.extend(content_resp.explain.map(|e| e.queries).unwrap_or_default());
+            counts = match (counts, content_counts) {
+                (Some(mut m), Some(c)) => {
+                    m.merge(c);
+                    Some(m)
+                }
+                (m, c) => m.or(c),
+            };
         }
+
+        Ok(HybridTier {
+            total: meta_resp.total.max(content_total),
+            meta_hits: meta_resp.hits,
+            content_hits,
+            counts,
+            queries,
+        })
     }
 }
 
+/// Both indexes' hits at one depth of a hybrid search.
+struct HybridTier {
+    /// The larger per-index match count.
+    total: u64,
+    meta_hits: Vec<SearchHit>,
+    content_hits: Vec<SearchHit>,
+    counts: Option<FacetCounts>,
+    queries: Vec<IndexQuery>,
+}
+
 impl SearchHandler for UnifiedSearchHandler {
     fn search(&self, req: SearchRequest) -> SearchResponse {
-        match req.mode {
-            SearchMode::NameOnly => self.search_meta(&req),
-            SearchMode::Content => self.search_content(&req),
-            SearchMode::Hybrid | SearchMode::Auto => self.search_hybrid(&req),
+        self.search_stream(req, &mut |_, _| {})
+    }
+
+    /// Completions come from the name FST, ranked by the value it stores per
+    /// file (modification time when built with ranks). Each name is shown as
+    /// the highest-ranked file spells it; the FST only has it lowercased.
+    fn suggest(&self, req: SuggestRequest) -> SuggestResponse {
+        let prefix = req.prefix.trim().to_lowercase();
+        let searcher = self.meta_reader.searcher();
+        let found = match self.names.get(searcher.generation().generation_id()) {
+            Some(names) if !prefix.is_empty() => {
+                names.complete(&prefix, req.limit.clamp(1, MAX_COMPLETIONS) as usize)
+            }
+            _ => Vec::new(),
+        };
+        let mut spelled = if found.is_empty() {
+            HashMap::new()
+        } else {
+            let keys: Vec<_> = found.iter().map(|c| c.doc_key).collect();
+            self.stored_names(&searcher, &keys).unwrap_or_else(|err| {
+                warn!("failed to look up completion names: {err:#}");
+                HashMap::new()
+            })
+        };
+        let completions = found
+            .into_iter()
+            .map(|c| Completion {
+                name: spelled.remove(&c.doc_key).unwrap_or(c.name),
+                files: c.files,
+            })
+            .collect();
+        SuggestResponse {
+            id: req.id,
+            completions,
         }
     }
-}
 
-// Helper to map content doc to SearchHit
-fn to_hit_content<D: Document>(
-    doc: &D,
-    fields: &content_index::ContentFields,
-    score: Score,
-) -> Option<SearchHit> {
-    let mut key = None;
-    let mut name = None;
-    let mut path = None;
-    let mut ext = None;
-    let mut size = None;
-    let mut modified = None;
-    let snippet = None; // TODO: snippet generation
+    fn name_ngram_bytes(&self) -> Option<u64> {
+        let field = self.meta.fields.name_ngram?;
+        meta_index::field_bytes(&self.meta_reader.searcher(), field)
+            .inspect_err(|err| warn!("failed to measure name trigrams: {err}"))
+            .ok()
+    }
 
-    for (field, value) in doc.iter_fields_and_values() {
-        match field {
-            f if f == fields.doc_key => {
-                if let Some(v) = value.as_u64() {
-                    key = Some(core_types::DocKey(v));
+    fn search_stream(&self, mut req: SearchRequest, sink: &mut HitSink) -> SearchResponse {
+        req.query = QueryPlanner::optimize(req.query);
+        let (mode, routed) = match req.mode {
+            SearchMode::Auto => {
+                let mode = match QueryPlanner::route(&req.query) {
+                    // Without a content index, hybrid is a metadata search.
+                    _ if self.content.is_none() => SearchMode::NameOnly,
+                    mode => mode,
+                };
+                (mode, true)
+            }
+            mode => (mode, false),
+        };
+        let page = match self.page(&req, mode) {
+            Ok(page) => page,
+            Err(err) => return error_response(&req, &err),
+        };
+        let cached = self
+            .results
+            .as_ref()
+            .and_then(|cache| Some((cache, ResultCache::key(&req, mode)?)));
+        if let Some((cache, key)) = &cached {
+            let hit = cache.get(key, page.generations());
+            record_result_cache(hit.is_some());
+            if let Some(mut resp) = hit {
+                resp.id = req.id;
+                resp.took_ms = 0;
+                let source = match mode {
+                    SearchMode::Content => HitSource::Content,
+                    _ => HitSource::Meta,
+                };
+                sink(source, &resp.hits);
+                // The cached cursor is for these same searchers.
+                if resp.next_cursor.is_some() {
+                    self.hold(&page);
                 }
+                return resp;
             }
-            f if f == fields.name => name = value.as_str().map(|s| s.to_string()),
-            f if f == fields.path => path = value.as_str().map(|s| s.to_string()),
-            f if f == fields.ext => ext = value.as_str().map(|s| s.to_string()),
-            f if f == fields.size => size = value.as_u64(),
-            f if f == fields.modified => modified = value.as_i64(),
-            // TODO: snippet from content field
-            _ => {}
         }
+        let mut fusion = None;
+        let mut resp = match mode {
+            SearchMode::NameOnly => {
+                let resp = self.search_meta(&req, &page);
+                sink(HitSource::Meta, &resp.hits);
+                resp
+            }
+            SearchMode::Content => {
+                let resp = self.search_content(&req, &page);
+                sink(HitSource::Content, &resp.hits);
+                resp
+            }
+            SearchMode::Hybrid | SearchMode::Auto => {
+                let (resp, tier) = self.search_hybrid(&req, &page, sink);
+                fusion = Some(tier);
+                resp
+            }
+        };
+        if routed && resp.served_by.is_none() {
+            resp.served_by = Some(format!("auto:{}", mode_label(mode)));
+        }
+        if page.budget.expired() {
+            // Partial results rank only what was scanned; a cursor would
+            // skip the rest, so the caller has to retry with more time.
+            warn!(timeout = ?req.timeout, "search stopped at its deadline");
+            record_search_timeout();
+            resp.truncated = true;
+        } else if resp.error.is_none() {
+            if resp.total < FEW_RESULTS && page.start == 0 {
+                resp.suggestions = self.suggestions(&req.query, mode, &page);
+            }
+            resp.next_cursor = self.next_cursor(&req, mode, &page, fusion, &resp);
+            if let Some((cache, key)) = cached {
+                cache.put(key, page.generations(), resp.clone());
+            }
+        }
+        resp
     }
-
-    key.map(|doc_key| SearchHit {
-        key: doc_key,
-        score,
-        name,
-        path,
-        ext,
-        size,
-        modified,
-        snippet,
-    })
 }
 
-static HANDLER: OnceLock<Box<dyn SearchHandler>> = OnceLock::new();
-
-pub fn set_search_handler(handler: Box<dyn SearchHandler>) {
-    tracing::info!("Global search handler installed.");
-    let _ = HANDLER.set(handler);
-}
+impl UnifiedSearchHandler {
+    /// Spelling corrections from the dictionaries `mode` searched.
+    fn suggestions(&self, query: &QueryExpr, mode: SearchMode, page: &Page) -> Vec<Suggestion> {
+        let mut sources = Vec::new();
+        if mode != SearchMode::Content {
+            sources.push((&page.meta, self.meta.fields.name));
+        }
+        if mode != SearchMode::NameOnly
+            && let (Some(searcher), Some((index, _))) = (&page.content, &self.content)
+        {
+            // Stems make poor corrections, so the prose field is left out.
+            for field in index.fields.text_fields() {
+                if field != index.fields.content_prose {
+                    sources.push((searcher, field));
+                }
+            }
+        }
+        spelling::suggest(query, &sources).unwrap_or_else(|err| {
+            warn!("spelling suggestions failed: {err:#}");
+            Vec::new()
+        })
+    }
 
-pub fn search(req: SearchRequest) -> SearchResponse {
+    /// Stored names of the files `keys` identifies.
+    fn stored_names(
+        &self,
+        searcher: &Searcher,
+        keys: &[DocKey],
+    ) -> Result<HashMap<DocKey, String>> {
+        let fields = &self.meta.fields;
+        let query = BooleanQuery::new(
+            keys.iter()
+                .map(|key| (Occur::Should, u64_equals(fields.doc_key, key.0)))
+                .collect(),
+        );
+        let mut names = HashMap::new();
+        for (_, addr) in searcher.search(&query, &TopDocs::with_limit(keys.len()))? {
+            let doc: TantivyDocument = searcher.doc(addr)?;
+            let key = doc.get_first(fields.doc_key).and_then(|v| v.as_u64());
+            let name = doc.get_first(fields.name).and_then(|v| v.as_str());
+            if let (Some(key), Some(name)) = (key, name) {
+                names.insert(DocKey(key), name.to_string());
+            }
+        }
+        Ok(names)
+    }
+
+    /// Current searchers, or the ones pinned by the request's cursor.
+    fn page(&self, req: &SearchRequest, mode: SearchMode) -> Result<Page> {
+        let Some(text) = &req.cursor else {
+            return Ok(Page {
+                meta: self.meta_reader.searcher(),
+                content: self.content.as_ref().map(|(_, reader)| reader.searcher()),
+                after: None,
+                start: req.offset as usize,
+                fusion: None,
+                budget: Budget::new(req.timeout),
+            });
+        };
+        let cursor = Cursor::decode(text, req, mode)?;
+        let expired = || anyhow!("cursor expired; run the search again");
+        let meta = self
+            .meta_leases
+            .get(cursor.meta_generation)
+            .ok_or_else(expired)?;
+        let content = cursor
+            .content_generation
+            .map(|generation| self.content_leases.get(generation).ok_or_else(expired))
+            .transpose()?;
+        Ok(Page {
+            meta,
+            content,
+            after: Some(cursor.after),
+            start: cursor.served as usize,
+            fusion: cursor.fusion,
+            budget: Budget::new(req.timeout),
+        })
+    }
+
+    /// Cursor for the page after `resp`, leasing the searchers it read.
+    fn next_cursor(
+        &self,
+        req: &SearchRequest,
+        mode: SearchMode,
+        page: &Page,
+        fusion: Option<Fusion>,
+        resp: &SearchResponse,
+    ) -> Option<String> {
+        let last = resp.hits.last()?;
+        let served = (page.start + resp.hits.len()) as u64;
+        if served >= resp.total && !resp.truncated {
+            return None;
+        }
+        self.hold(page);
+        let after = Position::of_hit(last, req.sort);
+        let cursor = Cursor::new(
+            req,
+            mode,
+            &page.meta,
+            page.content.as_ref(),
+            after,
+            served,
+            fusion,
+        );
+        Some(cursor.encode())
+    }
+
+    /// Lease the page's searchers so cursors can return to them.
+    fn hold(&self, page: &Page) {
+        self.meta_leases.hold(&page.meta);
+        if let Some(content) = &page.content {
+            self.content_leases.hold(content);
+        }
+    }
+}
+
+/// Ranked `(score, doc)` pairs, total match count and facet counts.
+type TopDocsFruit = (Vec<(Score, DocAddress)>, usize, Option<FacetCounts>);
+
+/// Combine per-index hit lists (each in rank order) into one unordered list
+/// with comparable scores.
+///
+/// BM25 scores of the two indexes are not on the same scale, so the default
+/// reciprocal rank fusion only looks at positions; `max` keeps the higher
+/// raw score.
+fn merge_hybrid(
+    meta_hits: Vec<SearchHit>,
+    content_hits: Vec<SearchHit>,
+    ranking: &SearchSection,
+) -> Vec<SearchHit> {
+    let fused = |weight: f32, rank: usize| weight / (ranking.rrf_k as f32 + rank as f32 + 1.0);
+    let mut hits_map: std::collections::HashMap<core_types::DocKey, SearchHit> =
+        std::collections::HashMap::new();
+
+    for (rank, mut hit) in meta_hits.into_iter().enumerate() {
+        if ranking.hybrid_merge == HybridMerge::Rrf {
+            hit.score = fused(ranking.meta_weight, rank);
+        }
+        hits_map.insert(hit.key, hit);
+    }
+
+    for (rank, mut hit) in content_hits.into_iter().enumerate() {
+        if ranking.hybrid_merge == HybridMerge::Rrf {
+            hit.score = fused(ranking.content_weight, rank);
+        }
+        hits_map
+            .entry(hit.key)
+            .and_modify(|e| {
+                e.score = match ranking.hybrid_merge {
+                    HybridMerge::Rrf => e.score + hit.score,
+                    HybridMerge::Max => e.score.max(hit.score),
+                };
+                e.approximate &= hit.approximate;
+                e.explain.extend(hit.explain.iter().cloned());
+                if e.snippet.is_none() {
+                    e.snippet = hit.snippet.clone();
+                    e.highlights.snippet = hit.highlights.snippet.clone();
+                }
+            })
+            .or_insert(hit);
+    }
+
+    hits_map.into_values().collect()
+}
+
+fn mode_label(mode: SearchMode) -> &'static str {
+    match mode {
+        SearchMode::NameOnly => "name",
+        SearchMode::Content => "content",
+        SearchMode::Hybrid | SearchMode::Auto => "hybrid",
+    }
+}
+
+/// Top `top_k` documents in the requested order (after the page's cursor
+/// position, if any), plus the total match count and facet counts, all from
+/// one pass within the page's time budget.
+fn top_docs(
+    searcher: &Searcher,
+    query: &dyn Query,
+    sort: Option<SortSpec>,
+    top_k: usize,
+    page: &Page,
+    facets: Option<FacetCollector>,
+) -> tantivy::Result<TopDocsFruit> {
+    let top = SortedTopDocs::new(sort, top_k).after(page.after.clone());
+    searcher.search(query, &page.budget.collector((top, Count, facets)))
+}
+
+/// Plan summary for responses to `explain` requests.
+fn explain_for(
+    req: &SearchRequest,
+    mode: SearchMode,
+    queries: Vec<IndexQuery>,
+) -> Option<SearchExplain> {
+    req.explain.then(|| SearchExplain {
+        plan: QueryPlanner::optimize(req.query.clone()),
+        mode,
+        queries,
+    })
+}
+
+fn index_query(index: &str, query: impl std::fmt::Debug) -> IndexQuery {
+    IndexQuery {
+        index: index.to_string(),
+        query: format!("{query:?}"),
+    }
+}
+
+/// Score breakdown of `addr` under `query`; empty if Tantivy cannot explain it.
+fn hit_explanation(
+    index: &str,
+    searcher: &Searcher,
+    query: &dyn Query,
+    addr: DocAddress,
+    score: Score,
+) -> Vec<HitExplanation> {
+    match query.explain(searcher, addr) {
+        Ok(explanation) => vec![HitExplanation {
+            index: index.to_string(),
+            score,
+            detail: explanation.to_pretty_json(),
+        }],
+        Err(err) => {
+            warn!(error = %err, "failed to explain hit");
+            Vec::new()
+        }
+    }
+}
+
+/// Attach rendered facet counts to a response.
+fn with_facets(
+    req: &SearchRequest,
+    (mut resp, counts): (SearchResponse, Option<FacetCounts>),
+) -> SearchResponse {
+    if let (Some(wanted), Some(counts)) = (&req.facets, counts) {
+        resp.facets = counts.into_results(wanted);
+    }
+    resp
+}
+
+/// Fill `hit.highlights` for the name and path shown in the results list.
+fn highlight_name_path(
+    hit: &mut SearchHit,
+    highlighter: &Highlighter,
+    index: &tantivy::Index,
+    name: Field,
+    path: Field,
+) {
+    if let Some(text) = &hit.name {
+        hit.highlights.name = highlighter.ranges(index, name, FieldKind::Name, text);
+    }
+    if let Some(text) = &hit.path {
+        hit.highlights.path = highlighter.ranges(index, path, FieldKind::Path, text);
+    }
+}
+
+/// Upper bound on the number of index terms a single prefix may expand to.
+const PREFIX_MAX_EXPANSIONS: u32 = 1_000;
+
+/// Prefix match on a tokenized text field.
+///
+/// The value goes through the field's analyzer so `Repo*` matches the
+/// lowercased `report` token; with several tokens the last one is the prefix
+/// of a phrase (`quarterly rep*`).
+fn prefix_query(
+    index: &tantivy::Index,
+    field: Field,
+    value: &str,
+) -> Result<Option<Box<dyn Query>>> {
+    let mut analyzer = index.tokenizer_for_field(field)?;
+    let mut stream = analyzer.token_stream(value);
+    let mut terms = Vec::new();
+    while stream.advance() {
+        terms.push(Term::from_field_text(field, &stream.token().text));
+    }
+    if terms.is_empty() {
+        return Ok(None);
+    }
+    let mut query = PhrasePrefixQuery::new(terms);
+    query.set_max_expansions(PREFIX_MAX_EXPANSIONS);
+    Ok(Some(Box::new(query)))
+}
+
+/// Exact phrase over the field's tokens (a plain term for a single token).
+///
+/// Tokens keep the analyzer's positions. Where it stacks several at one
+/// position (an identifier and its first part), the last one is used.
+fn phrase_query(
+    index: &tantivy::Index,
+    field: Field,
+    value: &str,
+) -> Result<Option<Box<dyn Query>>> {
+    let mut analyzer = index.tokenizer_for_field(field)?;
+    let mut stream = analyzer.token_stream(value);
+    let mut terms: Vec<(usize, Term)> = Vec::new();
+    while stream.advance() {
+        let token = stream.token();
+        let term = Term::from_field_text(field, &token.text);
+        match terms.last_mut() {
+            Some((position, last)) if *position == token.position => *last = term,
+            _ => terms.push((token.position, term)),
+        }
+    }
+    Ok(match terms.len() {
+        0 => None,
+        1 => Some(Box::new(TermQuery::new(
+            terms.remove(0).1,
+            IndexRecordOption::Basic,
+        ))),
+        _ => {
+            // Offsets relative to the first token.
+            let first = terms[0].0;
+            let terms = terms.into_iter().map(|(p, t)| (p - first, t)).collect();
+            Some(Box::new(PhraseQuery::new_with_offset(terms)))
+        }
+    })
+}
+
+/// Directories sharing a path phrase (`a\b` also matches `x\a\b`) that are
+/// checked for an exact path match when resolving `in:`.
+const FOLDER_CANDIDATES: usize = 64;
+
+/// Canonical form for comparing folder paths.
+fn normalize_folder(path: &str) -> String {
+    path.trim()
+        .replace('/', "\\")
+        .trim_end_matches('\\')
+        .to_lowercase()
+}
+
+/// `in:` filter: any document with `folder` in its ancestor chain.
+fn ancestor_query(field: Field, folder: DocKey) -> Box<dyn Query> {
+    let term = Term::from_field_u64(field, folder.0);
+    Box::new(TermQuery::new(term, IndexRecordOption::Basic))
+}
+
+/// Most completions one `SuggestRequest` can ask for.
+const MAX_COMPLETIONS: u32 = 50;
+
+/// Largest edit distance honoured for fuzzy terms; tantivy's Levenshtein
+/// automata stop at 2 and larger distances match almost everything anyway.
+const MAX_FUZZY_DISTANCE: u8 = 2;
+
+/// Edit-distance match on a tokenized text field; every token must match.
+fn fuzzy_query(
+    index: &tantivy::Index,
+    field: Field,
+    value: &str,
+    distance: u8,
+) -> Result<Option<Box<dyn Query>>> {
+    let distance = distance.min(MAX_FUZZY_DISTANCE);
+    let mut analyzer = index.tokenizer_for_field(field)?;
+    let mut stream = analyzer.token_stream(value);
+    let mut clauses: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+    while stream.advance() {
+        let term = Term::from_field_text(field, &stream.token().text);
+        clauses.push((
+            Occur::Must,
+            Box::new(FuzzyTermQuery::new(term, distance, true)),
+        ));
+    }
+    Ok(match clauses.len() {
+        0 => None,
+        1 => clauses.pop().map(|(_, q)| q),
+        _ => Some(Box::new(BooleanQuery::new(clauses))),
+    })
+}
+
+/// The query with every fuzzy term made exact, or `None` if it has no fuzzy
+/// terms. Hits that do not match this variant are flagged as approximate.
+fn exact_variant(expr: &QueryExpr) -> Option<QueryExpr> {
+    fn has_fuzzy(expr: &QueryExpr) -> bool {
+        match expr {
+            QueryExpr::Term(t) => matches!(t.modifier, TermModifier::Fuzzy(_)),
+            QueryExpr::Range(_) => false,
+            QueryExpr::Not(inner) => has_fuzzy(inner),
+            QueryExpr::And(items) | QueryExpr::Or(items) => items.iter().any(has_fuzzy),
+        }
+    }
+    fn make_exact(expr: &QueryExpr) -> QueryExpr {
+        match expr {
+            QueryExpr::Term(t) if matches!(t.modifier, TermModifier::Fuzzy(_)) => {
+                QueryExpr::Term(TermExpr {
+                    modifier: TermModifier::Term,
+                    ..t.clone()
+                })
+            }
+            QueryExpr::Term(_) | QueryExpr::Range(_) => expr.clone(),
+            QueryExpr::Not(inner) => QueryExpr::Not(Box::new(make_exact(inner))),
+            QueryExpr::And(items) => QueryExpr::And(items.iter().map(make_exact).collect()),
+            QueryExpr::Or(items) => QueryExpr::Or(items.iter().map(make_exact).collect()),
+        }
+    }
+    has_fuzzy(expr).then(|| make_exact(expr))
+}
+
+fn exact_weight(
+    searcher: &Searcher,
+    query: Option<Box<dyn Query>>,
+) -> Result<Option<Box<dyn Weight>>> {
+    query
+        .map(|q| q.weight(EnableScoring::disabled_from_searcher(searcher)))
+        .transpose()
+        .map_err(Into::into)
+}
+
+fn is_approximate(searcher: &Searcher, exact: Option<&dyn Weight>, addr: DocAddress) -> bool {
+    let Some(weight) = exact else {
+        return false;
+    };
+    let reader = searcher.segment_reader(addr.segment_ord);
+    match weight.scorer(reader, 1.0) {
+        Ok(mut scorer) => scorer.doc() > addr.doc_id || scorer.seek(addr.doc_id) != addr.doc_id,
+        Err(_) => false,
+    }
+}
+
+/// Regex over indexed terms of `field`, with the same search semantics as
+/// `FstIndex::search_regex`: case-insensitive and unanchored unless the
+/// pattern starts with `^` / ends with `$`.
+fn regex_query(field: Field, pattern: &str) -> Result<Box<dyn Query>> {
+    // Tantivy's term regexes always cover the whole term and reject `^`/`$`,
+    // so translate the anchors into the absence of a `.*` wrapper.
+    let (head, core) = match pattern.strip_prefix('^') {
+        Some(rest) => ("", rest),
+        None => (".*", pattern),
+    };
+    let (core, tail) = match core.strip_suffix('$') {
+        Some(rest) if !rest.ends_with('\\') => (rest, ""),
+        _ => (core, ".*"),
+    };
+    let regex = format!("(?is){head}(?:{core}){tail}");
+    Ok(Box::new(RegexQuery::from_pattern(&regex, field)?))
+}
+
+/// Names containing `value`: a phrase of its trigrams when the index has
+/// them and `value` is long enough, else a scan of `name_raw`.
+fn name_substring_query(
+    index: &tantivy::Index,
+    fields: &MetaFields,
+    value: &str,
+) -> Result<Box<dyn Query>> {
+    if let Some(field) = fields.name_ngram
+        && let Some(query) = phrase_query(index, field, value)?
+    {
+        return Ok(query);
+    }
+    substring_query(fields.name_raw, value)
+}
+
+/// Indexed terms of `field` containing `value`, case-insensitively.
+fn substring_query(field: Field, value: &str) -> Result<Box<dyn Query>> {
+    let mut regex = String::from(".*");
+    for c in value.to_lowercase().chars() {
+        if r"\.+*?()|[]{}^$#&-~".contains(c) {
+            regex.push('\\');
+        }
+        regex.push(c);
+    }
+    regex.push_str(".*");
+    Ok(Box::new(RegexQuery::from_pattern(&regex, field)?))
+}
+
+/// Match indexed terms of `field` against a shell-style glob, case-insensitively.
+fn glob_query(field: Field, pattern: &str) -> Result<Box<dyn Query>> {
+    let regex = glob_to_regex(&pattern.to_lowercase());
+    Ok(Box::new(RegexQuery::from_pattern(&regex, field)?))
+}
+
+/// Translate `*`/`?` wildcards into a regex; everything else is matched
+/// literally. Tantivy regexes always match whole terms, so no anchors needed.
+fn glob_to_regex(pattern: &str) -> String {
+    let mut out = String::with_capacity(pattern.len() * 2);
+    for c in pattern.chars() {
+        match c {
+            '*' => out.push_str(".*"),
+            '?' => out.push('.'),
+            '.' | '+' | '(' | ')' | '|' | '[' | ']' | '{' | '}' | '^' | '$' | '\\' => {
+                out.push('\\');
+                out.push(c);
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Occurrence of one conjunct: `a -b` excludes `b` from the same boolean
+/// query instead of AND-ing with a standalone (empty) negation.
+fn conjunct(expr: &QueryExpr) -> (Occur, &QueryExpr) {
+    match expr {
+        QueryExpr::Not(inner) => (Occur::MustNot, inner),
+        other => (Occur::Must, other),
+    }
+}
+
+/// Everything except `inner`; see `NotQuery` for why this is not a
+/// `MustNot` clause.
+fn negate(inner: Box<dyn Query>) -> Box<dyn Query> {
+    Box::new(NotQuery::new(inner))
+}
+
+/// AND of `clauses`. When every clause is negated, `NOT a AND NOT b` runs as
+/// `NOT (a OR b)`.
+fn conjunction(clauses: Vec<(Occur, Box<dyn Query>)>) -> Box<dyn Query> {
+    if !clauses.is_empty() && clauses.iter().all(|(occur, _)| *occur == Occur::MustNot) {
+        let any = clauses
+            .into_iter()
+            .map(|(_, q)| (Occur::Should, q))
+            .collect();
+        return negate(Box::new(BooleanQuery::new(any)));
+    }
+    Box::new(BooleanQuery::new(clauses))
+}
+
+/// Equality on a `u64` fast field.
+fn u64_equals(field: Field, value: u64) -> Box<dyn Query> {
+    let term = Term::from_field_u64(field, value);
+    Box::new(RangeQuery::new(
+        Bound::Included(term.clone()),
+        Bound::Included(term),
+    ))
+}
+
+/// `is:<attr>` filter; `is:file` is the negation of `is:dir`.
+fn flags_query(field: Field, value: &str) -> Result<Box<dyn Query>> {
+    if value.eq_ignore_ascii_case("file") {
+        return Ok(Box::new(FlagsQuery::none(
+            field,
+            FileFlags::IS_DIR.bits() as u64,
+        )));
+    }
+    let Some(flag) = FileFlags::from_query_name(value) else {
+        bail!(
+            "unknown attribute `is:{value}`; expected dir, file, hidden, system, archive, \
+             reparse, offline or temp"
+        );
+    };
+    Ok(Box::new(FlagsQuery::any(field, flag.bits() as u64)))
+}
+
+/// `kind:<kind>` filter; unknown kinds are rejected so typos do not silently
+/// return nothing.
+fn kind_query(field: Field, value: &str) -> Result<Box<dyn Query>> {
+    let kind = value.to_ascii_lowercase();
+    if !core_types::kind::is_known(&kind) {
+        bail!(
+            "unknown kind `{value}`; expected one of {} or a kind from [kinds]",
+            core_types::kind::BUILTIN_KINDS.join(", ")
+        );
+    }
+    let term = Term::from_field_text(field, &kind);
+    Ok(Box::new(TermQuery::new(term, IndexRecordOption::Basic)))
+}
+
+/// `vol:<id>` filter on the numeric volume id.
+fn volume_query(field: Field, value: &str) -> Result<Box<dyn Query>> {
+    let Ok(id) = value.parse::<u16>() else {
+        bail!("`vol:` expects a numeric volume id, got `{value}`");
+    };
+    Ok(u64_equals(field, id as u64))
+}
+
+fn uses_field(expr: &QueryExpr, kind: FieldKind) -> bool {
+    match expr {
+        QueryExpr::Term(t) => t.field == Some(kind),
+        QueryExpr::Range(r) => r.field == kind,
+        QueryExpr::Not(inner) => uses_field(inner, kind),
+        QueryExpr::And(items) | QueryExpr::Or(items) => items.iter().any(|e| uses_field(e, kind)),
+    }
+}
+
+/// Numeric fields a `RangeExpr` can target; both indexes expose the same set.
+#[derive(Clone, Copy)]
+struct RangeFields {
+    size: Field,
+    modified: Field,
+    created: Field,
+}
+
+impl RangeFields {
+    fn new(size: Field, modified: Field, created: Field) -> Self {
+        Self {
+            size,
+            modified,
+            created,
+        }
+    }
+}
+
+/// Translate a `RangeExpr` into a fast-field range query.
+fn range_query(range: &RangeExpr, fields: RangeFields) -> Result<Box<dyn Query>> {
+    let field = match range.field {
+        FieldKind::Size => fields.size,
+        FieldKind::Modified => fields.modified,
+        FieldKind::Created => fields.created,
+        other => {
+            bail!("range filters are not supported on {other:?}; use size, modified or created")
+        }
+    };
+
+    let (lower, upper) = match (range.field, &range.value) {
+        (FieldKind::Size, RangeValue::U64 { lo, hi }) => {
+            range_bounds(range.op, *lo, *hi, |v| Term::from_field_u64(field, v))?
+        }
+        (FieldKind::Modified | FieldKind::Created, RangeValue::I64 { lo, hi }) => {
+            range_bounds(range.op, *lo, *hi, |v| Term::from_field_i64(field, v))?
+        }
+        (kind, value) => bail!("range value {value:?} does not match field {kind:?}"),
+    };
+
+    Ok(Box::new(RangeQuery::new(lower, upper)))
+}
+
+fn range_bounds<T>(
+    op: RangeOp,
+    lo: T,
+    hi: Option<T>,
+    term: impl Fn(T) -> Term,
+) -> Result<(Bound<Term>, Bound<Term>)> {
+    Ok(match op {
+        RangeOp::Gt => (Bound::Excluded(term(lo)), Bound::Unbounded),
+        RangeOp::Ge => (Bound::Included(term(lo)), Bound::Unbounded),
+        RangeOp::Lt => (Bound::Unbounded, Bound::Excluded(term(lo))),
+        RangeOp::Le => (Bound::Unbounded, Bound::Included(term(lo))),
+        RangeOp::Between => {
+            let Some(hi) = hi else {
+                bail!("`between` range is missing its upper bound");
+            };
+            (Bound::Included(term(lo)), Bound::Included(term(hi)))
+        }
+    })
+}
+
+fn error_response(req: &SearchRequest, err: &anyhow::Error) -> SearchResponse {
+    SearchResponse {
+        id: req.id,
+        hits: Vec::new(),
+        total: 0,
+        truncated: false,
+        took_ms: 0,
+        served_by: None,
+        error: Some(err.to_string()),
+        facets: Vec::new(),
+        explain: None,
+        next_cursor: None,
+        suggestions: Vec::new(),
+    }
+}
+
+// Helper to map content doc to SearchHit
+fn to_hit_content<D: Document>(
+    doc: &D,
+    fields: &content_index::ContentFields,
+    score: Score,
+) -> Option<SearchHit> {
+    let mut key = None;
+    let mut name = None;
+    let mut path = None;
+    let mut ext = None;
+    let mut size = None;
+    let mut modified = None;
+    let mut created = None;
+    let snippet = None; // filled in by `search_content`
+
+    for (field, value) in doc.iter_fields_and_values() {
+        match field {
+            f if f == fields.doc_key => {
+                if let Some(v) = value.as_u64() {
+                    key = Some(core_types::DocKey(v));
+                }
+            }
+            f if f == fields.name => name = value.as_str().map(|s| s.to_string()),
+            f if f == fields.path => path = value.as_str().map(|s| s.to_string()),
+            f if f == fields.ext => ext = value.as_str().map(|s| s.to_string()),
+            f if f == fields.size => size = value.as_u64(),
+            f if f == fields.modified => modified = value.as_i64(),
+            f if f == fields.created => created = value.as_i64(),
+            _ => {}
+        }
+    }
+
+    key.map(|doc_key| SearchHit {
+        key: doc_key,
+        score,
+        name,
+        path,
+        ext,
+        size,
+        modified,
+        snippet,
+        approximate: false,
+        highlights: Default::default(),
+        created,
+        explain: Vec::new(),
+    })
+}
+
+static HANDLER: OnceLock<Box<dyn SearchHandler>> = OnceLock::new();
+
+pub fn set_search_handler(handler: Box<dyn SearchHandler>) {
+    tracing::info!("Global search handler installed.");
+    let _ = HANDLER.set(handler);
+}
+
+pub fn search(req: SearchRequest) -> SearchResponse {
     tracing::info!("Received search request id={} mode={:?}", req.id, req.mode);
     if let Some(h) = HANDLER.get() {
         h.search(req)
@@ -488,6 +1855,28 @@ pub fn search(req: SearchRequest) -> SearchResponse {
     }
 }
 
+/// Streaming counterpart of `search`; see `SearchHandler::search_stream`.
+pub fn search_stream(req: SearchRequest, sink: &mut HitSink) -> SearchResponse {
+    tracing::info!("Received streamed search id={} mode={:?}", req.id, req.mode);
+    match HANDLER.get() {
+        Some(h) => h.search_stream(req, sink),
+        None => StubSearchHandler.search_stream(req, sink),
+    }
+}
+
+/// Autocomplete counterpart of `search`; see `SearchHandler::suggest`.
+pub fn suggest(req: SuggestRequest) -> SuggestResponse {
+    match HANDLER.get() {
+        Some(h) => h.suggest(req),
+        None => StubSearchHandler.suggest(req),
+    }
+}
+
+/// See `SearchHandler::name_ngram_bytes`.
+pub fn name_ngram_bytes() -> Option<u64> {
+    HANDLER.get().and_then(|h| h.name_ngram_bytes())
+}
+
 fn to_hit<D: Document>(doc: &D, fields: &MetaFields, score: Score) -> Option<SearchHit> {
     let mut key = None;
     let mut name = None;
@@ -495,6 +1884,7 @@ fn to_hit<D: Document>(doc: &D, fields: &MetaFields, score: Score) -> Option<Sea
     let mut ext = None;
     let mut size = None;
     let mut modified = None;
+    let mut created = None;
 
     for (field, value) in doc.iter_fields_and_values() {
         match field {
@@ -528,6 +1918,11 @@ fn to_hit<D: Document>(doc: &D, fields: &MetaFields, score: Score) -> Option<Sea
                     modified = Some(v);
                 }
             }
+            f if f == fields.created => {
+                if let Some(v) = value.as_i64() {
+                    created = Some(v);
+                }
+            }
             _ => {}
         }
     }
@@ -541,5 +1936,1191 @@ fn to_hit<D: Document>(doc: &D, fields: &MetaFields, score: Score) -> Option<Sea
         size,
         modified,
         snippet: None,
+        approximate: false,
+        highlights: Default::default(),
+        created,
+        explain: Vec::new(),
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use content_index::{ContentDoc, TextKind};
+    use core_types::query::parse_query;
+    use meta_index::{MetaDoc, open_or_create_index};
+    use std::time::Duration;
+    use tempfile::TempDir;
+
+    const KIB: u64 = 1024;
+
+    /// (name, ext, size, created, modified, content)
+    const FILES: &[(&str, &str, u64, i64, i64, &str)] = &[
+        ("small.txt", "txt", 10, 1_000, 2_000, "alpha notes"),
+        ("medium.rs", "rs", 4 * KIB, 1_500, 3_000, "fn alpha() {}"),
+        (
+            "invoice-2024.pdf",
+            "pdf",
+            900 * KIB,
+            2_500,
+            4_000,
+            "alpha report",
+        ),
+    ];
+
+    /// (volume, flags) for each entry of `FILES`.
+    const ATTRS: &[(u16, FileFlags)] = &[
+        (1, FileFlags::HIDDEN),
+        (1, FileFlags::HIDDEN.union(FileFlags::SYSTEM)),
+        (2, FileFlags::empty()),
+    ];
+
+    /// Folders as (path, key, parent index); only indexed by `index_folders`.
+    const DIRS: &[(&str, DocKey, Option<usize>)] = &[
+        (r"C:\data", DocKey::from_parts(1, 0x100), None),
+        (r"C:\data\src", DocKey::from_parts(1, 0x101), Some(0)),
+        (r"D:\data", DocKey::from_parts(2, 0x100), None),
+    ];
+
+    /// Index into `DIRS` of the folder holding each entry of `FILES`.
+    const FILE_DIRS: &[usize] = &[0, 1, 2];
+
+    /// Keys of `DIRS[dir]` and its ancestors, nearest first.
+    fn dir_chain(dir: usize) -> Vec<DocKey> {
+        let mut chain = Vec::new();
+        let mut current = Some(dir);
+        while let Some(i) = current {
+            chain.push(DIRS[i].1);
+            current = DIRS[i].2;
+        }
+        chain
+    }
+
+    fn fixture() -> (TempDir, UnifiedSearchHandler) {
+        let dir = TempDir::new().unwrap();
+        let meta_path = dir.path().join("meta");
+        let content_path = dir.path().join("content");
+        std::fs::create_dir_all(&meta_path).unwrap();
+        std::fs::create_dir_all(&content_path).unwrap();
+
+        let meta = open_or_create_index(&meta_path).unwrap();
+        let mut writer = meta_index::create_writer(
+            &meta,
+            &meta_index::WriterConfig {
+                heap_size_bytes: 15_000_000,
+                num_threads: 1,
+            },
+        )
+        .unwrap();
+        let content = open_content(&content_path).unwrap();
+        let mut content_writer = content_index::create_writer(
+            &content,
+            &content_index::WriterConfig {
+                heap_size_bytes: 15_000_000,
+                num_threads: 1,
+            },
+        )
+        .unwrap();
+
+        for (i, (name, ext, size, created, modified, text)) in FILES.iter().enumerate() {
+            let (volume, flags) = ATTRS[i];
+            let key = DocKey::from_parts(volume, i as u64 + 1);
+            let path = format!("{}\\{name}", DIRS[FILE_DIRS[i]].0);
+            let ancestors = dir_chain(FILE_DIRS[i]);
+            writer
+                .add_document(meta_index::to_document(
+                    &MetaDoc {
+                        key,
+                        volume,
+                        name: name.to_string(),
+                        path: Some(path.clone()),
+                        ext: Some(ext.to_string()),
+                        size: *size,
+                        created: *created,
+                        modified: *modified,
+                        flags: flags.bits() as u64,
+                        ancestors: ancestors.clone(),
+                    },
+                    &meta.fields,
+                ))
+                .unwrap();
+            content_index::add_content_doc(
+                &mut content_writer,
+                &content.fields,
+                &ContentDoc {
+                    key,
+                    volume,
+                    name: Some(name.to_string()),
+                    path: Some(path),
+                    ext: Some(ext.to_string()),
+                    size: *size,
+                    modified: *modified,
+                    created: *created,
+                    content_lang: None,
+                    content: text.to_string(),
+                    ancestors,
+                    text_kind: TextKind::classify(Some(ext), None),
+                },
+            )
+            .unwrap();
+        }
+        writer.commit().unwrap();
+        content_writer.commit().unwrap();
+        meta_index::write_name_fst(
+            &meta.index.reader().unwrap().searcher(),
+            &meta.fields,
+            &meta_path.join(FST_FILE_NAME),
+        )
+        .unwrap();
+
+        let handler = UnifiedSearchHandler::try_new(&meta_path, &content_path).unwrap();
+        (dir, handler)
+    }
+
+    /// Add the `DIRS` folders to the metadata index.
+    fn index_folders(handler: &UnifiedSearchHandler) {
+        let mut writer = handler
+            .meta
+            .index
+            .writer_with_num_threads(1, 15_000_000)
+            .unwrap();
+        for (path, key, parent) in DIRS {
+            let name = path.rsplit('\\').next().unwrap();
+            writer
+                .add_document(meta_index::to_document(
+                    &MetaDoc {
+                        key: *key,
+                        volume: key.volume(),
+                        name: name.to_string(),
+                        path: Some(path.to_string()),
+                        ext: None,
+                        size: 0,
+                        created: 0,
+                        modified: 0,
+                        flags: FileFlags::IS_DIR.bits() as u64,
+                        ancestors: parent.map(dir_chain).unwrap_or_default(),
+                    },
+                    &handler.meta.fields,
+                ))
+                .unwrap();
+        }
+        writer.commit().unwrap();
+        handler.meta_reader.reload().unwrap();
+    }
+
+    fn run(handler: &UnifiedSearchHandler, query: &str, mode: SearchMode) -> SearchResponse {
+        handler.search(SearchRequest {
+            query: parse_query(query).unwrap(),
+            mode,
+            ..SearchRequest::default()
+        })
+    }
+
+    fn names(resp: &SearchResponse) -> Vec<String> {
+        let mut names: Vec<String> = resp.hits.iter().filter_map(|h| h.name.clone()).collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn size_ranges_filter_meta_hits() {
+        let (_dir, handler) = fixture();
+
+        let resp = run(&handler, "size:>1kb", SearchMode::NameOnly);
+        assert_eq!(resp.error, None);
+        assert_eq!(names(&resp), ["invoice-2024.pdf", "medium.rs"]);
+
+        let resp = run(&handler, "size:<=4kb", SearchMode::NameOnly);
+        assert_eq!(names(&resp), ["medium.rs", "small.txt"]);
+
+        let resp = run(&handler, "size:1kb..1mb", SearchMode::NameOnly);
+        assert_eq!(names(&resp), ["invoice-2024.pdf", "medium.rs"]);
+    }
+
+    #[test]
+    fn timestamp_ranges_filter_both_indexes() {
+        let (_dir, handler) = fixture();
+        let between = |field: &str, lo: i64, hi: i64| {
+            QueryExpr::Range(RangeExpr {
+                field: match field {
+                    "modified" => FieldKind::Modified,
+                    _ => FieldKind::Created,
+                },
+                op: RangeOp::Between,
+                value: RangeValue::I64 { lo, hi: Some(hi) },
+            })
+        };
+
+        for mode in [SearchMode::NameOnly, SearchMode::Content] {
+            let resp = handler.search(SearchRequest {
+                query: between("modified", 2_000, 3_000),
+                mode,
+                ..SearchRequest::default()
+            });
+            assert_eq!(resp.error, None);
+            assert_eq!(names(&resp), ["medium.rs", "small.txt"], "{mode:?}");
+
+            let resp = handler.search(SearchRequest {
+                query: QueryExpr::Range(RangeExpr {
+                    field: FieldKind::Created,
+                    op: RangeOp::Gt,
+                    value: RangeValue::I64 {
+                        lo: 1_500,
+                        hi: None,
+                    },
+                }),
+                mode,
+                ..SearchRequest::default()
+            });
+            assert_eq!(names(&resp), ["invoice-2024.pdf"], "{mode:?}");
+        }
+    }
+
+    #[test]
+    fn ranges_combine_with_terms() {
+        let (_dir, handler) = fixture();
+        let resp = run(&handler, "alpha size:<1kb", SearchMode::Content);
+        assert_eq!(names(&resp), ["small.txt"]);
+
+        let resp = run(&handler, "alpha size:<1kb", SearchMode::Hybrid);
+        assert_eq!(names(&resp), ["small.txt"]);
+    }
+
+    #[test]
+    fn phrases_keep_word_order() {
+        let (_dir, handler) = fixture();
+        for mode in [SearchMode::NameOnly, SearchMode::Content] {
+            assert_eq!(
+                names(&run(&handler, "name:\"invoice 2024\"", mode)),
+                ["invoice-2024.pdf"],
+                "{mode:?}"
+            );
+            assert!(
+                names(&run(&handler, "name:\"2024 invoice\"", mode)).is_empty(),
+                "{mode:?}"
+            );
+        }
+        assert_eq!(
+            names(&run(&handler, "\"alpha report\"", SearchMode::Content)),
+            ["invoice-2024.pdf"]
+        );
+        assert!(names(&run(&handler, "\"report alpha\"", SearchMode::Content)).is_empty());
+    }
+
+    #[test]
+    fn substrings_match_inside_names() {
+        let (_dir, handler) = fixture();
+        assert!(handler.name_ngram_bytes().is_some_and(|bytes| bytes > 0));
+        for mode in [SearchMode::NameOnly, SearchMode::Content] {
+            // Trigram phrases in the metadata index, `name_raw` scans in content.
+            assert_eq!(
+                names(&run(&handler, "*VOICE-2*", mode)),
+                ["invoice-2024.pdf"],
+                "{mode:?}"
+            );
+            assert_eq!(
+                names(&run(&handler, "name:*iu*", mode)),
+                ["medium.rs"],
+                "{mode:?}"
+            );
+            assert!(
+                names(&run(&handler, "*vocie*", mode)).is_empty(),
+                "{mode:?}"
+            );
+        }
+        assert_eq!(
+            names(&run(&handler, "*m* -*.rs*", SearchMode::NameOnly)),
+            ["small.txt"]
+        );
+    }
+
+    #[test]
+    fn prefix_matches_token_starts() {
+        let (_dir, handler) = fixture();
+        for mode in [SearchMode::NameOnly, SearchMode::Content] {
+            assert_eq!(
+                names(&run(&handler, "MED*", mode)),
+                ["medium.rs"],
+                "{mode:?}"
+            );
+            assert_eq!(
+                names(&run(&handler, "name:invoice-20*", mode)),
+                ["invoice-2024.pdf"],
+                "{mode:?}"
+            );
+            assert_eq!(
+                names(&run(&handler, "path:dat*", mode)),
+                ["invoice-2024.pdf", "medium.rs", "small.txt"],
+                "{mode:?}"
+            );
+            assert_eq!(names(&run(&handler, "ext:p*", mode)), ["invoice-2024.pdf"]);
+        }
+    }
+
+    #[test]
+    fn glob_matches_whole_file_name() {
+        let (_dir, handler) = fixture();
+        for mode in [
+            SearchMode::NameOnly,
+            SearchMode::Content,
+            SearchMode::Hybrid,
+        ] {
+            assert_eq!(
+                names(&run(&handler, "*.rs", mode)),
+                ["medium.rs"],
+                "{mode:?}"
+            );
+            assert_eq!(
+                names(&run(&handler, "invoice-20??.pdf", mode)),
+                ["invoice-2024.pdf"],
+                "{mode:?}"
+            );
+            assert_eq!(
+                names(&run(&handler, "Invoice-20?.pdf", mode)),
+                Vec::<String>::new()
+            );
+            assert_eq!(names(&run(&handler, "ext:t?t", mode)), ["small.txt"]);
+        }
+    }
+
+    #[test]
+    fn fuzzy_terms_flag_approximate_hits() {
+        let (_dir, handler) = fixture();
+        for mode in [
+            SearchMode::NameOnly,
+            SearchMode::Content,
+            SearchMode::Hybrid,
+        ] {
+            let resp = run(&handler, "medum~1", mode);
+            assert_eq!(names(&resp), ["medium.rs"], "{mode:?}");
+            assert!(resp.hits[0].approximate, "{mode:?}");
+
+            let resp = run(&handler, "medium~1", mode);
+            assert_eq!(names(&resp), ["medium.rs"], "{mode:?}");
+            assert!(!resp.hits[0].approximate, "{mode:?}");
+        }
+
+        let resp = run(&handler, "content:reprot~", SearchMode::Content);
+        assert_eq!(names(&resp), ["invoice-2024.pdf"]);
+        assert!(resp.hits[0].approximate);
+
+        // Distances beyond the cap are clamped instead of failing.
+        let resp = run(&handler, "smal~9", SearchMode::NameOnly);
+        assert_eq!(resp.error, None);
+        assert_eq!(names(&resp), ["small.txt"]);
+    }
+
+    #[test]
+    fn regex_searches_names() {
+        let (_dir, handler) = fixture();
+        // NameOnly goes through the FST; the other modes use tantivy regexes.
+        for mode in [
+            SearchMode::NameOnly,
+            SearchMode::Content,
+            SearchMode::Hybrid,
+        ] {
+            let resp = run(&handler, r"regex:^MED\w+\.rs$", mode);
+            assert_eq!(resp.error, None, "{mode:?}");
+            assert_eq!(names(&resp), ["medium.rs"], "{mode:?}");
+
+            let resp = run(&handler, r"regex:ice-\d{4}", mode);
+            assert_eq!(names(&resp), ["invoice-2024.pdf"], "{mode:?}");
+
+            assert!(
+                run(&handler, r"regex:^ice", mode).hits.is_empty(),
+                "{mode:?}"
+            );
+            assert!(
+                run(&handler, r"regex:(oops", mode).error.is_some(),
+                "{mode:?}"
+            );
+        }
+
+        // Every match is counted, and a cursor pages on by count.
+        let req = SearchRequest {
+            query: parse_query(r"regex:\.(rs|txt|pdf)$").unwrap(),
+            mode: SearchMode::NameOnly,
+            limit: 2,
+            ..SearchRequest::default()
+        };
+        let resp = handler.search(req.clone());
+        assert_eq!(resp.hits.len(), 2);
+        assert_eq!(resp.total, 3);
+        assert!(!resp.truncated);
+        let rest = handler.search(req.with_cursor(resp.next_cursor.unwrap()));
+        assert_eq!(names(&rest), ["small.txt"]);
+        assert!(rest.next_cursor.is_none());
+    }
+
+    #[test]
+    fn attribute_and_volume_filters() {
+        let (_dir, handler) = fixture();
+        let mode = SearchMode::NameOnly;
+        assert_eq!(
+            names(&run(&handler, "is:hidden", mode)),
+            ["medium.rs", "small.txt"]
+        );
+        assert_eq!(
+            names(&run(&handler, "is:hidden -is:system", mode)),
+            ["small.txt"]
+        );
+        assert_eq!(
+            names(&run(&handler, "is:file alpha*", mode)),
+            Vec::<String>::new()
+        );
+        assert_eq!(names(&run(&handler, "is:dir", mode)), Vec::<String>::new());
+        assert_eq!(
+            names(&run(&handler, "is:file", mode)),
+            ["invoice-2024.pdf", "medium.rs", "small.txt"]
+        );
+        assert_eq!(names(&run(&handler, "vol:2", mode)), ["invoice-2024.pdf"]);
+        assert_eq!(
+            names(&run(&handler, "vol:1 -is:system", mode)),
+            ["small.txt"]
+        );
+
+        // Volume works everywhere; attributes only live in the meta index, so
+        // hybrid answers `is:` from there and content mode reports it.
+        assert_eq!(
+            names(&run(&handler, "alpha vol:2", SearchMode::Content)),
+            ["invoice-2024.pdf"]
+        );
+        assert_eq!(
+            names(&run(&handler, "is:system", SearchMode::Hybrid)),
+            ["medium.rs"]
+        );
+        assert!(
+            run(&handler, "is:system", SearchMode::Content)
+                .error
+                .is_some()
+        );
+
+        let err = run(&handler, "is:purple", mode).error.unwrap();
+        assert!(err.contains("is:purple"), "{err}");
+        assert!(run(&handler, "vol:C", mode).error.is_some());
+    }
+
+    #[test]
+    fn kind_filters_use_the_taxonomy() {
+        let (_dir, handler) = fixture();
+        for mode in [
+            SearchMode::NameOnly,
+            SearchMode::Content,
+            SearchMode::Hybrid,
+        ] {
+            assert_eq!(names(&run(&handler, "kind:code", mode)), ["medium.rs"]);
+            assert_eq!(
+                names(&run(&handler, "kind:Document OR kind:text", mode)),
+                ["invoice-2024.pdf", "small.txt"],
+                "{mode:?}"
+            );
+            assert!(run(&handler, "kind:image", mode).hits.is_empty());
+            let err = run(&handler, "kind:spaceship", mode).error.unwrap();
+            assert!(err.contains("spaceship"), "{err}");
+        }
+    }
+
+    #[test]
+    fn unsupported_range_field_reports_error() {
+        let (_dir, handler) = fixture();
+        let query = QueryExpr::Range(RangeExpr {
+            field: FieldKind::Volume,
+            op: RangeOp::Gt,
+            value: RangeValue::U64 { lo: 1, hi: None },
+        });
+
+        for mode in [
+            SearchMode::NameOnly,
+            SearchMode::Content,
+            SearchMode::Hybrid,
+        ] {
+            let resp = handler.search(SearchRequest {
+                query: query.clone(),
+                mode,
+                ..SearchRequest::default()
+            });
+            assert!(resp.hits.is_empty());
+            let err = resp.error.expect("error for unsupported field");
+            assert!(err.contains("Volume"), "{err}");
+        }
+
+        let resp = handler.search(SearchRequest {
+            query: QueryExpr::Range(RangeExpr {
+                field: FieldKind::Size,
+                op: RangeOp::Gt,
+                value: RangeValue::I64 { lo: 1, hi: None },
+            }),
+            mode: SearchMode::NameOnly,
+            ..SearchRequest::default()
+        });
+        assert!(resp.error.is_some());
+    }
+
+    #[test]
+    fn in_folder_filters_by_ancestor() {
+        let (_dir, handler) = fixture();
+        index_folders(&handler);
+
+        assert_eq!(
+            names(&run(&handler, r"in:C:\data", SearchMode::NameOnly)),
+            ["medium.rs", "small.txt", "src"]
+        );
+        for mode in [SearchMode::NameOnly, SearchMode::Content] {
+            // Every file, in either index.
+            let all = match mode {
+                SearchMode::NameOnly => "path:data -is:dir",
+                _ => "alpha",
+            };
+            let filtered = |query: &str| names(&run(&handler, &format!("{all} {query}"), mode));
+            assert_eq!(filtered("in:c:/DATA/src/"), ["medium.rs"], "{mode:?}");
+            assert_eq!(
+                filtered(r"-in:C:\data\src"),
+                ["invoice-2024.pdf", "small.txt"],
+                "{mode:?}"
+            );
+            // DocKey literals skip path resolution.
+            assert_eq!(filtered("in:2:0x100"), ["invoice-2024.pdf"], "{mode:?}");
+            let err = run(&handler, r"in:C:\nowhere", mode).error.unwrap();
+            assert!(err.contains("folder not found"), "{err}");
+        }
+    }
+
+    #[test]
+    fn hits_carry_snippets_and_highlights() {
+        let (_dir, handler) = fixture();
+        let slice = |text: &Option<String>, r: &ipc::MatchRange| {
+            text.as_deref().unwrap()[r.start as usize..r.end as usize].to_string()
+        };
+
+        let resp = run(&handler, "invoice", SearchMode::NameOnly);
+        let hit = &resp.hits[0];
+        assert_eq!(slice(&hit.name, &hit.highlights.name[0]), "invoice");
+        assert_eq!(slice(&hit.path, &hit.highlights.path[0]), "invoice");
+        assert!(hit.snippet.is_none());
+
+        let resp = run(&handler, "repo* -notes", SearchMode::Hybrid);
+        assert_eq!(names(&resp), ["invoice-2024.pdf"]);
+        let hit = &resp.hits[0];
+        assert_eq!(hit.snippet.as_deref(), Some("alpha report"));
+        let marked: Vec<String> = hit
+            .highlights
+            .snippet
+            .iter()
+            .map(|r| slice(&hit.snippet, r))
+            .collect();
+        assert_eq!(marked, ["report"]);
+    }
+
+    #[test]
+    fn sort_spec_orders_whole_result_set() {
+        let (_dir, handler) = fixture();
+        let sorted = |query: &str, mode, sort, offset| -> Vec<String> {
+            handler
+                .search(SearchRequest {
+                    query: parse_query(query).unwrap(),
+                    mode,
+                    sort: Some(sort),
+                    offset,
+                    ..SearchRequest::default()
+                })
+                .hits
+                .into_iter()
+                .filter_map(|h| h.name)
+                .collect()
+        };
+
+        assert_eq!(
+            sorted(
+                "path:data",
+                SearchMode::NameOnly,
+                SortSpec::desc(SortField::Size),
+                0
+            ),
+            ["invoice-2024.pdf", "medium.rs", "small.txt"]
+        );
+        assert_eq!(
+            sorted(
+                "alpha",
+                SearchMode::Content,
+                SortSpec::asc(SortField::Modified),
+                0
+            ),
+            ["small.txt", "medium.rs", "invoice-2024.pdf"]
+        );
+        assert_eq!(
+            sorted(
+                "alpha",
+                SearchMode::Content,
+                SortSpec::desc(SortField::Created),
+                0
+            ),
+            ["invoice-2024.pdf", "medium.rs", "small.txt"]
+        );
+        for mode in [SearchMode::NameOnly, SearchMode::Hybrid] {
+            assert_eq!(
+                sorted("path:data", mode, SortSpec::desc(SortField::Name), 0),
+                ["small.txt", "medium.rs", "invoice-2024.pdf"],
+                "{mode:?}"
+            );
+            assert_eq!(
+                sorted("path:data", mode, SortSpec::desc(SortField::Size), 1),
+                ["medium.rs", "small.txt"],
+                "{mode:?}"
+            );
+        }
+        // Regex searches leave the FST when another order is requested.
+        assert_eq!(
+            sorted(
+                r"regex:\.(rs|txt)$",
+                SearchMode::NameOnly,
+                SortSpec::desc(SortField::Size),
+                0
+            ),
+            ["medium.rs", "small.txt"]
+        );
+    }
+
+    #[test]
+    fn facets_count_every_match_once() {
+        use ipc::{FacetField, FacetRequest};
+
+        let (_dir, handler) = fixture();
+        let faceted = |query: &str, mode| {
+            handler.search(SearchRequest {
+                query: parse_query(query).unwrap(),
+                mode,
+                limit: 1,
+                facets: Some(FacetRequest::new([
+                    FacetField::Ext,
+                    FacetField::Volume,
+                    FacetField::Size,
+                    FacetField::Modified,
+                ])),
+                ..SearchRequest::default()
+            })
+        };
+        let counts = |resp: &SearchResponse, field| -> Vec<(String, u64)> {
+            resp.facets
+                .iter()
+                .find(|f| f.field == field)
+                .unwrap()
+                .buckets
+                .iter()
+                .map(|b| (b.value.clone(), b.count))
+                .collect()
+        };
+        let pairs = |items: &[(&str, u64)]| -> Vec<(String, u64)> {
+            items.iter().map(|&(v, n)| (v.to_string(), n)).collect()
+        };
+
+        // Every file matches both indexes here; each must be counted once.
+        for mode in [
+            SearchMode::NameOnly,
+            SearchMode::Content,
+            SearchMode::Hybrid,
+        ] {
+            let resp = faceted("path:data", mode);
+            assert_eq!(resp.hits.len(), 1, "{mode:?}");
+            assert_eq!(
+                counts(&resp, FacetField::Ext),
+                pairs(&[("pdf", 1), ("rs", 1), ("txt", 1)]),
+                "{mode:?}"
+            );
+            assert_eq!(
+                counts(&resp, FacetField::Volume),
+                pairs(&[("1", 2), ("2", 1)])
+            );
+            assert_eq!(
+                counts(&resp, FacetField::Size),
+                pairs(&[("tiny", 2), ("small", 1)])
+            );
+            assert_eq!(
+                counts(&resp, FacetField::Modified),
+                pairs(&[("1970-01", 3)])
+            );
+        }
+
+        let resp = faceted("notes", SearchMode::Content);
+        assert_eq!(counts(&resp, FacetField::Ext), pairs(&[("txt", 1)]));
+
+        // Regex name searches leave the FST to count facets.
+        let resp = faceted(r"regex:\.(rs|txt)$", SearchMode::NameOnly);
+        assert_eq!(
+            counts(&resp, FacetField::Ext),
+            pairs(&[("rs", 1), ("txt", 1)])
+        );
+
+        // Bucket filters narrow the search to that bucket.
+        let resp = faceted("path:data", SearchMode::NameOnly);
+        let small = resp.facets[2].buckets.iter().find(|b| b.value == "small");
+        let narrowed = format!("path:data {}", small.unwrap().filter.as_deref().unwrap());
+        assert_eq!(
+            names(&run(&handler, &narrowed, SearchMode::NameOnly)),
+            ["invoice-2024.pdf"]
+        );
+    }
+
+    #[test]
+    fn explain_reports_plan_queries_and_scores() {
+        let (_dir, handler) = fixture();
+        let explained = |query: &str, mode| {
+            handler.search(
+                SearchRequest::with_query(parse_query(query).unwrap())
+                    .with_mode(mode)
+                    .with_explain(),
+            )
+        };
+
+        let resp = explained("path:data -(notes report)", SearchMode::Hybrid);
+        let explain = resp.explain.as_ref().unwrap();
+        assert_eq!(explain.mode, SearchMode::Hybrid);
+        assert_eq!(
+            explain.plan,
+            QueryPlanner::optimize(parse_query("path:data -(notes report)").unwrap())
+        );
+        let indexes: Vec<&str> = explain.queries.iter().map(|q| q.index.as_str()).collect();
+        assert_eq!(indexes, ["meta", "content"]);
+        assert!(
+            explain
+                .queries
+                .iter()
+                .all(|q| q.query.contains("BooleanQuery"))
+        );
+        assert!(!resp.hits.is_empty());
+        for hit in &resp.hits {
+            let mut sources: Vec<&str> = hit.explain.iter().map(|e| e.index.as_str()).collect();
+            sources.sort();
+            assert_eq!(sources, ["content", "meta"], "{:?}", hit.name);
+            assert!(hit.explain.iter().all(|e| e.detail.contains("\"value\"")));
+        }
+
+        let resp = explained(r"regex:\.rs$", SearchMode::NameOnly);
+        let explain = resp.explain.unwrap();
+        assert_eq!(explain.mode, SearchMode::NameOnly);
+        assert_eq!(explain.queries[0].index, "names");
+
+        let resp = run(&handler, "path:data", SearchMode::Hybrid);
+        assert!(resp.explain.is_none());
+        assert!(resp.hits.iter().all(|h| h.explain.is_empty()));
+    }
+
+    #[test]
+    fn auto_mode_routes_by_query_shape() {
+        let (_dir, handler) = fixture();
+        let auto = |query: &str| run(&handler, query, SearchMode::Auto);
+
+        let resp = auto("ext:pdf size:>1kb");
+        assert_eq!(resp.served_by.as_deref(), Some("auto:name"));
+        assert_eq!(names(&resp), ["invoice-2024.pdf"]);
+
+        let resp = auto("content:report");
+        assert_eq!(resp.served_by.as_deref(), Some("auto:content"));
+        assert_eq!(names(&resp), ["invoice-2024.pdf"]);
+
+        let resp = auto("alpha");
+        assert_eq!(resp.served_by.as_deref(), Some("auto:hybrid"));
+        assert_eq!(names(&resp).len(), 3);
+
+        // Explicit modes are not rerouted.
+        assert!(
+            run(&handler, "alpha", SearchMode::Hybrid)
+                .served_by
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn negations_match_against_all_documents() {
+        let (_dir, handler) = fixture();
+
+        assert_eq!(
+            names(&run(&handler, "-ext:pdf", SearchMode::NameOnly)),
+            ["medium.rs", "small.txt"]
+        );
+        assert_eq!(
+            names(&run(&handler, "NOT notes", SearchMode::Content)),
+            ["invoice-2024.pdf", "medium.rs"]
+        );
+        assert!(
+            run(&handler, "NOT alpha", SearchMode::Content)
+                .hits
+                .is_empty()
+        );
+        assert_eq!(
+            names(&run(&handler, "-is:hidden", SearchMode::NameOnly)),
+            ["invoice-2024.pdf"]
+        );
+        assert_eq!(
+            names(&run(&handler, "-ext:pdf", SearchMode::Hybrid)),
+            ["medium.rs", "small.txt"]
+        );
+
+        // De Morgan-normalized trees: NOT(a OR b) -> NOT a AND NOT b, and
+        // NOT(a AND b) -> NOT a OR NOT b.
+        for mode in [SearchMode::NameOnly, SearchMode::Content] {
+            assert_eq!(
+                names(&run(&handler, "-(ext:pdf OR ext:rs)", mode)),
+                ["small.txt"],
+                "{mode:?}"
+            );
+            assert_eq!(
+                names(&run(&handler, "-(ext:pdf size:>100kb)", mode)),
+                ["medium.rs", "small.txt"],
+                "{mode:?}"
+            );
+            assert_eq!(
+                names(&run(&handler, "-(ext:pdf -size:>100kb)", mode)),
+                ["invoice-2024.pdf", "medium.rs", "small.txt"],
+                "{mode:?}"
+            );
+        }
+        assert_eq!(
+            names(&run(
+                &handler,
+                "alpha -(notes OR report)",
+                SearchMode::Content
+            )),
+            ["medium.rs"]
+        );
+    }
+
+    #[test]
+    fn hybrid_merge_fuses_ranks_not_raw_scores() {
+        let hit = |frn: u64, score: f32| SearchHit {
+            key: DocKey::from_parts(1, frn),
+            score,
+            name: Some(format!("f{frn}")),
+            path: None,
+            ext: None,
+            size: None,
+            modified: None,
+            snippet: None,
+            approximate: false,
+            highlights: Default::default(),
+            created: None,
+            explain: Vec::new(),
+        };
+        // The exact name match (1) ranks first in meta but has a small BM25
+        // score next to the content hits.
+        let meta = || vec![hit(1, 2.0)];
+        let content = || vec![hit(2, 15.0), hit(3, 9.0), hit(1, 1.0)];
+        let order = |ranking: &SearchSection| -> Vec<u64> {
+            let mut merged = merge_hybrid(meta(), content(), ranking);
+            merged.sort_by(|a, b| compare_hits(None, a, b));
+            merged.iter().map(|h| h.key.file_id()).collect()
+        };
+
+        let rrf = SearchSection::default();
+        assert_eq!(order(&rrf), [1, 2, 3]);
+
+        let max = SearchSection {
+            hybrid_merge: HybridMerge::Max,
+            ..SearchSection::default()
+        };
+        assert_eq!(order(&max), [2, 3, 1]);
+
+        let content_only = SearchSection {
+            meta_weight: 0.0,
+            ..SearchSection::default()
+        };
+        assert_eq!(order(&content_only), [2, 3, 1]);
+    }
+
+    #[test]
+    fn cursors_page_a_pinned_snapshot() {
+        let (_dir, handler) = fixture();
+        let request = |query: &str, mode, sort| SearchRequest {
+            query: QueryPlanner::optimize(parse_query(query).unwrap()),
+            mode,
+            sort,
+            limit: 1,
+            ..SearchRequest::default()
+        };
+        let all_pages = |first: SearchRequest, between: &dyn Fn()| -> Vec<String> {
+            let mut resp = handler.search(first.clone());
+            between();
+            let mut seen = Vec::new();
+            loop {
+                assert!(resp.error.is_none(), "{:?}", resp.error);
+                seen.extend(resp.hits.iter().filter_map(|h| h.name.clone()));
+                let Some(cursor) = resp.next_cursor.take() else {
+                    break;
+                };
+                resp = handler.search(first.clone().with_cursor(cursor));
+            }
+            seen
+        };
+
+        // Folders committed after the first page do not leak into later ones.
+        let by_name = request(
+            "size:>=0",
+            SearchMode::NameOnly,
+            Some(SortSpec::asc(SortField::Name)),
+        );
+        assert_eq!(
+            all_pages(by_name.clone(), &|| index_folders(&handler)),
+            ["invoice-2024.pdf", "medium.rs", "small.txt"]
+        );
+        assert_eq!(
+            handler.search(by_name.with_limit(50)).hits.len(),
+            3 + DIRS.len()
+        );
+
+        // Hybrid pages follow the one-shot merged order.
+        let hybrid = request("alpha", SearchMode::Hybrid, None);
+        let one_shot: Vec<String> = handler
+            .search(hybrid.clone().with_limit(50))
+            .hits
+            .into_iter()
+            .filter_map(|h| h.name)
+            .collect();
+        assert_eq!(one_shot.len(), 3);
+        assert_eq!(all_pages(hybrid.clone(), &|| ()), one_shot);
+
+        // A cursor only continues the search it came from.
+        let cursor = handler.search(hybrid).next_cursor.unwrap();
+        let other = request("notes", SearchMode::Hybrid, None).with_cursor(cursor);
+        assert!(handler.search(other).error.is_some());
+        let bogus = request("alpha", SearchMode::Hybrid, None).with_cursor("not a cursor");
+        assert!(handler.search(bogus).error.is_some());
+    }
+
+    /// Handler over `(name, text)` files indexed by both indexes.
+    fn handler_for(files: &[(&str, &str)]) -> (TempDir, UnifiedSearchHandler) {
+        let dir = TempDir::new().unwrap();
+        let meta_path = dir.path().join("meta");
+        let content_path = dir.path().join("content");
+        std::fs::create_dir_all(&meta_path).unwrap();
+        std::fs::create_dir_all(&content_path).unwrap();
+        let meta = open_or_create_index(&meta_path).unwrap();
+        let content = open_content(&content_path).unwrap();
+        let mut writer = meta.index.writer_with_num_threads(1, 15_000_000).unwrap();
+        let mut content_writer = content
+            .index
+            .writer_with_num_threads(1, 15_000_000)
+            .unwrap();
+        for (i, (name, text)) in files.iter().enumerate() {
+            let key = DocKey::from_parts(1, i as u64 + 1);
+            let path = format!(r"C:\data\{name}");
+            writer
+                .add_document(meta_index::to_document(
+                    &MetaDoc {
+                        key,
+                        volume: 1,
+                        name: name.to_string(),
+                        path: Some(path.clone()),
+                        ext: Some("txt".into()),
+                        size: 1,
+                        created: 0,
+                        modified: 0,
+                        flags: 0,
+                        ancestors: Vec::new(),
+                    },
+                    &meta.fields,
+                ))
+                .unwrap();
+            content_index::add_content_doc(
+                &mut content_writer,
+                &content.fields,
+                &ContentDoc {
+                    key,
+                    volume: 1,
+                    name: Some(name.to_string()),
+                    path: Some(path),
+                    ext: Some("txt".into()),
+                    size: 1,
+                    modified: 0,
+                    created: 0,
+                    content_lang: None,
+                    content: text.to_string(),
+                    ancestors: Vec::new(),
+                    text_kind: TextKind::Plain,
+                },
+            )
+            .unwrap();
+        }
+        writer.commit().unwrap();
+        content_writer.commit().unwrap();
+        let handler = UnifiedSearchHandler::try_new(&meta_path, &content_path).unwrap();
+        (dir, handler)
+    }
+
+    #[test]
+    fn hybrid_cursors_serve_every_hit_once() {
+        let (_dir, handler) = handler_for(&[
+            ("zeta.txt", "nothing to see"),
+            ("zeta notes.txt", "zeta notes"),
+            ("zeta data plain.txt", "zeta zeta zeta zeta zeta zeta"),
+            ("zeta report draft final.txt", "words"),
+            ("other.txt", "zeta and more words around it"),
+        ]);
+        let request = |mode, limit| SearchRequest {
+            query: parse_query("zeta").unwrap(),
+            mode,
+            limit,
+            ..SearchRequest::default()
+        };
+        let ranked = |mode| -> Vec<String> {
+            let resp = handler.search(request(mode, 50));
+            resp.hits.into_iter().filter_map(|h| h.name).collect()
+        };
+        // The notes rank second in both indexes, so reading either one
+        // deeper lifts them above the first page's hit.
+        let (names, content) = (ranked(SearchMode::NameOnly), ranked(SearchMode::Content));
+        assert_ne!(names[0], content[0], "{names:?} {content:?}");
+        assert_eq!(names[1], "zeta notes.txt");
+        assert_eq!(content[1], "zeta notes.txt", "{content:?}");
+
+        let mut resp = handler.search(request(SearchMode::Hybrid, 1));
+        let mut paged = Vec::new();
+        loop {
+            assert!(resp.error.is_none(), "{:?}", resp.error);
+            paged.extend(resp.hits.iter().filter_map(|h| h.name.clone()));
+            let Some(cursor) = resp.next_cursor.take() else {
+                break;
+            };
+            resp = handler.search(request(SearchMode::Hybrid, 1).with_cursor(cursor));
+        }
+        let mut all = ranked(SearchMode::Hybrid);
+        assert_eq!(paged.len(), all.len(), "{paged:?}");
+        paged.sort();
+        all.sort();
+        assert_eq!(paged, all);
+    }
+
+    #[test]
+    fn timeouts_return_partial_results() {
+        let (_dir, handler) = fixture();
+        let timed = |timeout: Duration| {
+            handler.search(SearchRequest {
+                query: parse_query("alpha").unwrap(),
+                mode: SearchMode::Hybrid,
+                limit: 1,
+                timeout: Some(timeout),
+                ..SearchRequest::default()
+            })
+        };
+
+        // Partial results are not cached.
+        let resp = timed(Duration::ZERO);
+        assert!(resp.error.is_none());
+        assert!(resp.truncated);
+        assert!(resp.hits.is_empty());
+        assert!(resp.next_cursor.is_none());
+
+        let resp = timed(Duration::from_secs(60));
+        assert!(!resp.truncated);
+        assert!(resp.next_cursor.is_some());
+        assert_eq!(resp.total, 3);
+    }
+
+    #[test]
+    fn streams_meta_hits_before_content() {
+        let (_dir, handler) = fixture();
+        let mut batches = Vec::new();
+        let resp = handler.search_stream(
+            SearchRequest::with_query(parse_query("small OR alpha").unwrap())
+                .with_mode(SearchMode::Hybrid),
+            &mut |source, hits| batches.push((source, hits.len())),
+        );
+
+        let sources: Vec<HitSource> = batches.iter().map(|&(source, _)| source).collect();
+        assert_eq!(sources, [HitSource::Meta, HitSource::Content]);
+        assert!(batches.iter().all(|&(_, n)| n > 0));
+        assert_eq!(resp.hits.len() as u64, resp.total);
+    }
+
+    #[test]
+    fn completes_names_from_the_fst() {
+        let (_dir, handler) = fixture();
+        let complete = |prefix: &str| -> Vec<String> {
+            let req = SuggestRequest::new(prefix, 10);
+            let id = req.id;
+            let resp = handler.suggest(req);
+            assert_eq!(resp.id, id);
+            resp.completions.into_iter().map(|c| c.name).collect()
+        };
+
+        assert_eq!(complete(" In"), ["invoice-2024.pdf"]);
+        assert_eq!(complete("sm"), ["small.txt"]);
+        assert!(complete("zz").is_empty());
+        assert!(complete("  ").is_empty());
+    }
+
+    #[test]
+    fn reopens_the_fst_after_ingest_rewrites_it() {
+        let (dir, handler) = fixture();
+        let complete = |prefix: &str| -> Vec<String> {
+            let resp = handler.suggest(SuggestRequest::new(prefix, 10));
+            resp.completions.into_iter().map(|c| c.name).collect()
+        };
+        assert!(complete("report").is_empty());
+
+        let mut writer = handler
+            .meta
+            .index
+            .writer_with_num_threads(1, 15_000_000)
+            .unwrap();
+        writer
+            .add_document(meta_index::to_document(
+                &MetaDoc {
+                    key: DocKey::from_parts(1, 99),
+                    volume: 1,
+                    name: "Report.md".into(),
+                    path: Some(r"C:\data\Report.md".into()),
+                    ext: Some("md".into()),
+                    size: 1,
+                    created: 0,
+                    modified: 0,
+                    flags: 0,
+                    ancestors: Vec::new(),
+                },
+                &handler.meta.fields,
+            ))
+            .unwrap();
+        writer.commit().unwrap();
+        // The reader can see a commit before its FST is rewritten.
+        handler.meta_reader.reload().unwrap();
+        assert!(complete("report").is_empty());
+
+        meta_index::write_name_fst(
+            &handler.meta_reader.searcher(),
+            &handler.meta.fields,
+            &dir.path().join("meta").join(FST_FILE_NAME),
+        )
+        .unwrap();
+        // Completions keep the case of the stored name.
+        assert_eq!(complete("report"), ["Report.md"]);
+        let resp = run(&handler, "regex:^rep", SearchMode::NameOnly);
+        assert_eq!(names(&resp), ["Report.md"]);
+    }
+
+    #[test]
+    fn sparse_results_suggest_common_spellings() {
+        let (_dir, handler) = fixture();
+        let suggested = |query: &str, mode| -> Vec<(String, String, u8, u64)> {
+            let resp = run(&handler, query, mode);
+            resp.suggestions
+                .into_iter()
+                .map(|s| (s.term, s.suggestion, s.distance, s.doc_freq))
+                .collect()
+        };
+
+        let resp = run(&handler, "alpah", SearchMode::Hybrid);
+        assert_eq!(resp.total, 0);
+        // Only the source file counts: the other two are stemmed prose.
+        assert_eq!(
+            suggested("alpah", SearchMode::Hybrid),
+            [("alpah".into(), "alpha".into(), 1, 1)]
+        );
+        assert_eq!(
+            suggested("invoce", SearchMode::NameOnly),
+            [("invoce".into(), "invoice".into(), 1, 1)]
+        );
+        // Content words are not offered to name searches, negated and
+        // numeric words are not corrected, and enough hits need no help.
+        assert!(suggested("alpah", SearchMode::NameOnly).is_empty());
+        assert!(suggested("2025 -alpah", SearchMode::Hybrid).is_empty());
+        assert!(suggested("alpha", SearchMode::Hybrid).is_empty());
+    }
+}
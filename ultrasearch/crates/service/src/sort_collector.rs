@@ -0,0 +1,402 @@
+//! Top-N collector that orders by a fast field and breaks ties by score.
+//!
+//! Tantivy's `order_by_*` collectors drop the relevance score, so equal keys
+//! (same size, same name) come back in arbitrary order. This keeps
+//! `(key, score, doc_key)` per document instead, which makes the order total
+//! and lets a cursor resume strictly after a `Position`. Documents without a
+//! value sort last in either direction.
+
+use std::cmp::Ordering;
+
+use core_types::DocKey;
+use ipc::{SearchHit, SortDirection, SortField, SortSpec};
+use serde::{Deserialize, Serialize};
+use tantivy::collector::{Collector, SegmentCollector};
+use tantivy::columnar::StrColumn;
+use tantivy::fastfield::Column;
+use tantivy::{DocAddress, DocId, Score, SegmentOrdinal, SegmentReader, TantivyError};
+
+/// Sort key of a collected document; numbers are mapped to order-preserving
+/// `u64`s so every numeric field compares the same way.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub(crate) enum SortValue {
+    Num(u64),
+    Str(String),
+}
+
+/// A document's place in the result order.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub(crate) struct Position {
+    pub(crate) value: Option<SortValue>,
+    pub(crate) score: Score,
+    pub(crate) key: DocKey,
+}
+
+impl Position {
+    /// Position of an already-built hit, matching what the collector reads.
+    pub(crate) fn of_hit(hit: &SearchHit, sort: Option<SortSpec>) -> Self {
+        Self {
+            value: sort.and_then(|s| hit_value(hit, s.field)),
+            score: hit.score,
+            key: hit.key,
+        }
+    }
+}
+
+/// Collects the best `limit` documents under a `SortSpec`, or by relevance.
+#[derive(Debug, Clone)]
+pub(crate) struct SortedTopDocs {
+    field: Option<SortField>,
+    desc: bool,
+    limit: usize,
+    after: Option<Position>,
+}
+
+impl SortedTopDocs {
+    pub(crate) fn new(sort: Option<SortSpec>, limit: usize) -> Self {
+        Self {
+            field: sort.map(|s| s.field),
+            desc: sort.is_some_and(|s| s.direction == SortDirection::Desc),
+            limit,
+            after: None,
+        }
+    }
+
+    /// Only collect documents ordered strictly after `position`.
+    pub(crate) fn after(mut self, position: Option<Position>) -> Self {
+        self.after = position;
+        self
+    }
+}
+
+/// Fast-field column backing each sort field; both indexes use these names.
+fn column_name(field: SortField) -> &'static str {
+    match field {
+        SortField::Name => "name_raw",
+        SortField::Size => "size",
+        SortField::Modified => "modified",
+        SortField::Created => "created",
+    }
+}
+
+/// Order: key in the requested direction (missing last), then score
+/// descending, then the document key ascending for a stable result.
+fn compare<K: Ord, T: Ord>(
+    desc: bool,
+    a: (&Option<K>, Score, T),
+    b: (&Option<K>, Score, T),
+) -> Ordering {
+    let by_key = match (a.0, b.0) {
+        (Some(x), Some(y)) if desc => y.cmp(x),
+        (Some(x), Some(y)) => x.cmp(y),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    };
+    by_key
+        .then_with(|| b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal))
+        .then_with(|| a.2.cmp(&b.2))
+}
+
+enum SegmentValues {
+    None,
+    U64(Column<u64>),
+    I64(Column<i64>),
+    Str(StrColumn),
+}
+
+impl SegmentValues {
+    /// Order-preserving key; string keys are per-segment term ordinals
+    /// mapped to odd numbers, leaving room for a cursor value that falls
+    /// between two terms of this segment.
+    fn key(&self, doc: DocId) -> Option<u64> {
+        match self {
+            SegmentValues::None => None,
+            SegmentValues::U64(col) => col.first(doc),
+            SegmentValues::I64(col) => col.first(doc).map(ordered_i64),
+            SegmentValues::Str(col) => col.term_ords(doc).next().map(|ord| 2 * ord + 1),
+        }
+    }
+
+    /// `value` in the same space as `key`.
+    fn resolve(&self, value: &SortValue) -> tantivy::Result<u64> {
+        match (self, value) {
+            (SegmentValues::Str(col), SortValue::Str(s)) => {
+                let mut terms = col.dictionary().range().ge(s).into_stream()?;
+                Ok(if !terms.advance() {
+                    2 * col.dictionary().num_terms() as u64
+                } else if terms.key() == s.as_bytes() {
+                    2 * terms.term_ord() + 1
+                } else {
+                    2 * terms.term_ord()
+                })
+            }
+            (SegmentValues::U64(_) | SegmentValues::I64(_), SortValue::Num(n)) => Ok(*n),
+            _ => Err(TantivyError::InvalidArgument(
+                "cursor does not match the sort field".to_string(),
+            )),
+        }
+    }
+
+    fn value(&self, key: u64, buf: &mut String) -> tantivy::Result<SortValue> {
+        Ok(match self {
+            SegmentValues::Str(col) => {
+                buf.clear();
+                col.ord_to_str(key / 2, buf)?;
+                SortValue::Str(buf.clone())
+            }
+            _ => SortValue::Num(key),
+        })
+    }
+}
+
+type SegmentDoc = (Option<u64>, Score, u64, DocId);
+
+pub(crate) struct SortedSegmentCollector {
+    values: SegmentValues,
+    doc_keys: Column<u64>,
+    segment_ord: SegmentOrdinal,
+    desc: bool,
+    limit: usize,
+    /// Resume point, in this segment's key space.
+    after: Option<(Option<u64>, Score, u64)>,
+    docs: Vec<SegmentDoc>,
+}
+
+impl SortedSegmentCollector {
+    fn prune(&mut self) {
+        let desc = self.desc;
+        let cmp =
+            |a: &SegmentDoc, b: &SegmentDoc| compare(desc, (&a.0, a.1, a.2), (&b.0, b.1, b.2));
+        if self.docs.len() > self.limit {
+            self.docs.select_nth_unstable_by(self.limit, cmp);
+            self.docs.truncate(self.limit);
+        }
+    }
+}
+
+impl SegmentCollector for SortedSegmentCollector {
+    type Fruit = tantivy::Result<Vec<(Option<SortValue>, Score, u64, DocAddress)>>;
+
+    fn collect(&mut self, doc: DocId, score: Score) {
+        if self.limit == 0 {
+            return;
+        }
+        let key = self.values.key(doc);
+        let doc_key = self.doc_keys.first(doc).unwrap_or_default();
+        if let Some(after) = &self.after
+            && compare(
+                self.desc,
+                (&key, score, doc_key),
+                (&after.0, after.1, after.2),
+            ) != Ordering::Greater
+        {
+            return;
+        }
+        self.docs.push((key, score, doc_key, doc));
+        if self.docs.len() >= self.limit.saturating_mul(2).max(64) {
+            self.prune();
+        }
+    }
+
+    fn harvest(mut self) -> Self::Fruit {
+        self.prune();
+        let mut buf = String::new();
+        self.docs
+            .into_iter()
+            .map(|(key, score, doc_key, doc)| {
+                let value = key.map(|k| self.values.value(k, &mut buf)).transpose()?;
+                Ok((
+                    value,
+                    score,
+                    doc_key,
+                    DocAddress::new(self.segment_ord, doc),
+                ))
+            })
+            .collect()
+    }
+}
+
+impl Collector for SortedTopDocs {
+    type Fruit = Vec<(Score, DocAddress)>;
+    type Child = SortedSegmentCollector;
+
+    fn for_segment(
+        &self,
+        segment_ord: SegmentOrdinal,
+        segment: &SegmentReader,
+    ) -> tantivy::Result<Self::Child> {
+        let fast = segment.fast_fields();
+        let values = match self.field {
+            None => SegmentValues::None,
+            Some(SortField::Size) => SegmentValues::U64(fast.u64(column_name(SortField::Size))?),
+            Some(field @ (SortField::Modified | SortField::Created)) => {
+                SegmentValues::I64(fast.i64(column_name(field))?)
+            }
+            Some(SortField::Name) => {
+                let name = column_name(SortField::Name);
+                SegmentValues::Str(fast.str(name)?.ok_or_else(|| {
+                    TantivyError::SchemaError(format!(
+                        "`{name}` is not a fast field; rebuild the index to sort by name"
+                    ))
+                })?)
+            }
+        };
+        let after = match &self.after {
+            Some(pos) => Some((
+                pos.value.as_ref().map(|v| values.resolve(v)).transpose()?,
+                pos.score,
+                pos.key.0,
+            )),
+            None => None,
+        };
+        Ok(SortedSegmentCollector {
+            values,
+            doc_keys: fast.u64("doc_key")?,
+            segment_ord,
+            desc: self.desc,
+            limit: self.limit,
+            after,
+            docs: Vec::new(),
+        })
+    }
+
+    fn requires_scoring(&self) -> bool {
+        true
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<tantivy::Result<Vec<(Option<SortValue>, Score, u64, DocAddress)>>>,
+    ) -> tantivy::Result<Self::Fruit> {
+        let mut all = Vec::new();
+        for fruit in segment_fruits {
+            all.extend(fruit?);
+        }
+        all.sort_by(|a, b| compare(self.desc, (&a.0, a.1, a.2), (&b.0, b.1, b.2)));
+        all.truncate(self.limit);
+        Ok(all
+            .into_iter()
+            .map(|(_, score, _, addr)| (score, addr))
+            .collect())
+    }
+}
+
+/// `i64` → `u64` mapping that preserves order.
+const fn ordered_i64(v: i64) -> u64 {
+    (v as u64) ^ (1 << 63)
+}
+
+/// Sort key of an already-built hit, matching what the collector reads.
+fn hit_value(hit: &SearchHit, field: SortField) -> Option<SortValue> {
+    match field {
+        SortField::Name => hit.name.as_ref().map(|n| SortValue::Str(n.to_lowercase())),
+        SortField::Size => hit.size.map(SortValue::Num),
+        SortField::Modified => hit.modified.map(|v| SortValue::Num(ordered_i64(v))),
+        SortField::Created => hit.created.map(|v| SortValue::Num(ordered_i64(v))),
+    }
+}
+
+/// Order of two positions under `sort`; relevance order when `None`.
+pub(crate) fn compare_positions(sort: Option<SortSpec>, a: &Position, b: &Position) -> Ordering {
+    let desc = sort.is_some_and(|s| s.direction == SortDirection::Desc);
+    compare(desc, (&a.value, a.score, a.key), (&b.value, b.score, b.key))
+}
+
+/// Order of merged hits under `sort`; relevance order when `None`.
+pub(crate) fn compare_hits(sort: Option<SortSpec>, a: &SearchHit, b: &SearchHit) -> Ordering {
+    compare_positions(sort, &Position::of_hit(a, sort), &Position::of_hit(b, sort))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tantivy::query::AllQuery;
+    use tantivy::schema::{FAST, STORED, STRING, Schema, Value};
+    use tantivy::{Index, TantivyDocument};
+
+    fn hit(frn: u64, score: f32, size: Option<u64>) -> SearchHit {
+        SearchHit {
+            key: DocKey::from_parts(1, frn),
+            score,
+            name: None,
+            path: None,
+            ext: None,
+            size,
+            modified: None,
+            snippet: None,
+            approximate: false,
+            highlights: Default::default(),
+            created: None,
+            explain: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn hits_tie_break_on_score_and_missing_values_sort_last() {
+        let mut hits = vec![
+            hit(1, 0.5, Some(10)),
+            hit(2, 0.9, None),
+            hit(3, 0.1, Some(99)),
+            hit(4, 0.8, Some(10)),
+        ];
+        let order = |hits: &[SearchHit]| hits.iter().map(|h| h.key.file_id()).collect::<Vec<_>>();
+
+        hits.sort_by(|a, b| compare_hits(Some(SortSpec::asc(SortField::Size)), a, b));
+        assert_eq!(order(&hits), [4, 1, 3, 2]);
+        hits.sort_by(|a, b| compare_hits(Some(SortSpec::desc(SortField::Size)), a, b));
+        assert_eq!(order(&hits), [3, 4, 1, 2]);
+        hits.sort_by(|a, b| compare_hits(None, a, b));
+        assert_eq!(order(&hits), [2, 4, 1, 3]);
+    }
+
+    #[test]
+    fn resumes_after_names_missing_from_a_segment() {
+        let mut builder = Schema::builder();
+        let doc_key = builder.add_u64_field("doc_key", FAST);
+        let name = builder.add_text_field("name_raw", STRING | FAST | STORED);
+        let index = Index::create_in_ram(builder.build());
+        let mut writer = index.writer_with_num_threads(1, 15_000_000).unwrap();
+        // One segment per batch, so each has its own term ordinals.
+        for batch in [&[(2, "b"), (4, "d")][..], &[(1, "a"), (3, "c"), (5, "e")]] {
+            for &(key, value) in batch {
+                let mut doc = TantivyDocument::new();
+                doc.add_u64(doc_key, key);
+                doc.add_text(name, value);
+                writer.add_document(doc).unwrap();
+            }
+            writer.commit().unwrap();
+        }
+        let searcher = index.reader().unwrap().searcher();
+        assert_eq!(searcher.segment_readers().len(), 2);
+
+        let names = |sort: SortSpec, value: &str, key: u64| -> Vec<String> {
+            let after = Position {
+                value: Some(SortValue::Str(value.to_string())),
+                score: 1.0,
+                key: DocKey(key),
+            };
+            let top = SortedTopDocs::new(Some(sort), 10).after(Some(after));
+            searcher
+                .search(&AllQuery, &top)
+                .unwrap()
+                .into_iter()
+                .map(|(_, addr)| {
+                    let doc: TantivyDocument = searcher.doc(addr).unwrap();
+                    doc.get_first(name).unwrap().as_str().unwrap().to_string()
+                })
+                .collect()
+        };
+
+        assert_eq!(
+            names(SortSpec::asc(SortField::Name), "bb", 0),
+            ["c", "d", "e"]
+        );
+        assert_eq!(names(SortSpec::asc(SortField::Name), "c", 3), ["d", "e"]);
+        assert_eq!(names(SortSpec::desc(SortField::Name), "c", 3), ["b", "a"]);
+        assert_eq!(
+            names(SortSpec::asc(SortField::Name), "z", 0),
+            Vec::<String>::new()
+        );
+    }
+}
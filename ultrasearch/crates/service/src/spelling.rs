@@ -0,0 +1,155 @@
+//! "Did you mean" corrections drawn from the indexes' term dictionaries.
+//!
+//! Each query word is run as a Levenshtein automaton over the dictionaries,
+//! the same walk a fuzzy term query makes, so only words within reach are
+//! visited. A candidate is offered only when more documents contain it than
+//! contain the word as typed.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::OnceLock;
+
+use anyhow::Result;
+use ipc::{FieldKind, QueryExpr, Suggestion, TermModifier};
+use levenshtein_automata::{DFA, Distance, LevenshteinAutomatonBuilder, SINK_STATE};
+use tantivy::schema::Field;
+use tantivy::{Searcher, Term};
+use tantivy_fst::Automaton;
+
+/// Responses with fewer matches than this come with suggestions.
+pub(crate) const FEW_RESULTS: u64 = 3;
+/// Suggestions per response.
+const MAX_SUGGESTIONS: usize = 5;
+/// Candidates kept per query word, so one typo cannot crowd out the rest.
+const PER_WORD: usize = 2;
+/// Shorter words are left alone; nearly everything is one edit away.
+const MIN_WORD_CHARS: usize = 3;
+
+/// Corrections for the positive name/content words of `query`, looked up in
+/// `field` of each searcher, best first.
+pub(crate) fn suggest(
+    query: &QueryExpr,
+    sources: &[(&Searcher, Field)],
+) -> Result<Vec<Suggestion>> {
+    let mut words = Vec::new();
+    collect_words(query, &mut words);
+    let mut tokens = BTreeSet::new();
+    for (searcher, field) in sources {
+        let mut analyzer = searcher.index().tokenizer_for_field(*field)?;
+        for word in &words {
+            let mut stream = analyzer.token_stream(word);
+            while stream.advance() {
+                tokens.insert(stream.token().text.clone());
+            }
+        }
+    }
+
+    let mut out = Vec::new();
+    for token in tokens {
+        let chars = token.chars().count();
+        // Numbers and part codes have no spelling to correct.
+        if chars < MIN_WORD_CHARS || token.chars().any(|c| c.is_ascii_digit()) {
+            continue;
+        }
+        let distance = if chars < 5 { 1 } else { 2 };
+        let dfa = builder(distance).build_dfa(&token);
+
+        let mut typed = 0;
+        // Candidate word -> (distance, documents).
+        let mut candidates: BTreeMap<String, (u8, u64)> = BTreeMap::new();
+        for (searcher, field) in sources {
+            typed += searcher.doc_freq(&Term::from_field_text(*field, &token))?;
+            for segment in searcher.segment_readers() {
+                let inverted = segment.inverted_index(*field)?;
+                let mut terms = inverted.terms().search(Dfa(&dfa)).into_stream()?;
+                while terms.advance() {
+                    let Ok(word) = std::str::from_utf8(terms.key()) else {
+                        continue;
+                    };
+                    let Distance::Exact(d) = dfa.eval(word) else {
+                        continue;
+                    };
+                    if d == 0 {
+                        continue;
+                    }
+                    let entry = candidates.entry(word.to_string()).or_insert((d, 0));
+                    entry.1 += u64::from(terms.value().doc_freq);
+                }
+            }
+        }
+
+        let mut better: Vec<Suggestion> = candidates
+            .into_iter()
+            .filter(|&(_, (_, docs))| docs > typed)
+            .map(|(suggestion, (distance, doc_freq))| Suggestion {
+                term: token.clone(),
+                suggestion,
+                distance,
+                doc_freq,
+            })
+            .collect();
+        better.sort_by(rank);
+        better.truncate(PER_WORD);
+        out.extend(better);
+    }
+    out.sort_by(rank);
+    out.truncate(MAX_SUGGESTIONS);
+    Ok(out)
+}
+
+/// Closer first, then more common.
+fn rank(a: &Suggestion, b: &Suggestion) -> std::cmp::Ordering {
+    a.distance
+        .cmp(&b.distance)
+        .then(b.doc_freq.cmp(&a.doc_freq))
+        .then_with(|| a.suggestion.cmp(&b.suggestion))
+}
+
+/// Plain words and phrases aimed at names or content. Negated words are
+/// skipped: nobody needs help spelling what they exclude.
+fn collect_words(expr: &QueryExpr, out: &mut Vec<String>) {
+    match expr {
+        QueryExpr::Term(t)
+            if matches!(t.field, None | Some(FieldKind::Name | FieldKind::Content))
+                && matches!(t.modifier, TermModifier::Term | TermModifier::Phrase) =>
+        {
+            out.push(t.value.clone());
+        }
+        QueryExpr::And(items) | QueryExpr::Or(items) => {
+            for item in items {
+                collect_words(item, out);
+            }
+        }
+        QueryExpr::Term(_) | QueryExpr::Range(_) | QueryExpr::Not(_) => {}
+    }
+}
+
+/// Builders for edit distances 1 and 2 (with transpositions); building one
+/// takes a while, so they are shared.
+fn builder(distance: u8) -> &'static LevenshteinAutomatonBuilder {
+    static BUILDERS: OnceLock<[LevenshteinAutomatonBuilder; 2]> = OnceLock::new();
+    let builders =
+        BUILDERS.get_or_init(|| [1, 2].map(|d| LevenshteinAutomatonBuilder::new(d, true)));
+    &builders[usize::from(distance.clamp(1, 2)) - 1]
+}
+
+struct Dfa<'a>(&'a DFA);
+
+impl Automaton for Dfa<'_> {
+    type State = u32;
+
+    fn start(&self) -> u32 {
+        self.0.initial_state()
+    }
+
+    fn is_match(&self, state: &u32) -> bool {
+        matches!(self.0.distance(*state), Distance::Exact(_))
+    }
+
+    fn can_match(&self, state: &u32) -> bool {
+        *state != SINK_STATE
+    }
+
+    fn accept(&self, state: &u32, byte: u8) -> u32 {
+        self.0.transition(*state, byte)
+    }
+}
@@ -173,6 +173,9 @@ impl BasicStatusProvider {
                 active_workers: None,
                 content_enqueued: None,
                 content_dropped: None,
+                cache_hits: None,
+                cache_misses: None,
+                name_ngram_bytes: None,
             });
             snap.queue_depth = queue_depth;
             snap.active_workers = active_workers;
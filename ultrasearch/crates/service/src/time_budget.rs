@@ -0,0 +1,160 @@
+//! Deadline for the collection phase of a search.
+//!
+//! Tantivy drives a collector over every match of a segment, so a slow query
+//! (wide fuzzy or regex expansion) cannot be stopped from inside `collect`.
+//! `TimeBudget` takes over the per-segment loop instead: it checks the clock
+//! every few hundred documents, stops at the deadline and harvests what the
+//! inner collector has so far. Building the query's automata and loading the
+//! returned documents are not covered.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use tantivy::collector::{Collector, SegmentCollector};
+use tantivy::query::{Scorer, Weight};
+use tantivy::{DocSet, SegmentOrdinal, SegmentReader, TERMINATED};
+
+/// Documents collected between clock checks.
+const CHECK_EVERY: u32 = 256;
+
+/// Deadline shared by every index pass of one search.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Budget {
+    deadline: Option<Instant>,
+    expired: Arc<AtomicBool>,
+}
+
+impl Budget {
+    /// Budget ending `timeout` from now; `None` never expires.
+    pub(crate) fn new(timeout: Option<Duration>) -> Self {
+        Self {
+            deadline: timeout.map(|t| Instant::now() + t),
+            expired: Arc::default(),
+        }
+    }
+
+    /// True once any pass stopped early.
+    pub(crate) fn expired(&self) -> bool {
+        self.expired.load(Ordering::Relaxed)
+    }
+
+    /// Run `inner` within this budget.
+    pub(crate) fn collector<C: Collector>(&self, inner: C) -> TimeBudget<C> {
+        TimeBudget {
+            inner,
+            budget: self.clone(),
+        }
+    }
+
+    fn out_of_time(&self) -> bool {
+        let out = self.expired() || self.deadline.is_some_and(|d| Instant::now() >= d);
+        if out {
+            self.expired.store(true, Ordering::Relaxed);
+        }
+        out
+    }
+}
+
+pub(crate) struct TimeBudget<C> {
+    inner: C,
+    budget: Budget,
+}
+
+impl<C: Collector> Collector for TimeBudget<C> {
+    type Fruit = C::Fruit;
+    type Child = C::Child;
+
+    fn for_segment(
+        &self,
+        segment_ord: SegmentOrdinal,
+        segment: &SegmentReader,
+    ) -> tantivy::Result<Self::Child> {
+        self.inner.for_segment(segment_ord, segment)
+    }
+
+    fn requires_scoring(&self) -> bool {
+        self.inner.requires_scoring()
+    }
+
+    fn merge_fruits(
+        &self,
+        segment_fruits: Vec<<Self::Child as SegmentCollector>::Fruit>,
+    ) -> tantivy::Result<Self::Fruit> {
+        self.inner.merge_fruits(segment_fruits)
+    }
+
+    fn collect_segment(
+        &self,
+        weight: &dyn Weight,
+        segment_ord: SegmentOrdinal,
+        reader: &SegmentReader,
+    ) -> tantivy::Result<<Self::Child as SegmentCollector>::Fruit> {
+        if self.budget.deadline.is_none() {
+            return self.inner.collect_segment(weight, segment_ord, reader);
+        }
+        let mut child = self.inner.for_segment(segment_ord, reader)?;
+        if self.budget.out_of_time() {
+            return Ok(child.harvest());
+        }
+
+        let scoring = self.inner.requires_scoring();
+        let alive = reader.alive_bitset();
+        let mut scorer = weight.scorer(reader, 1.0)?;
+        let mut doc = scorer.doc();
+        let mut seen = 0u32;
+        while doc != TERMINATED {
+            if alive.is_none_or(|a| a.is_alive(doc)) {
+                let score = if scoring { scorer.score() } else { 0.0 };
+                child.collect(doc, score);
+            }
+            seen += 1;
+            if seen.is_multiple_of(CHECK_EVERY) && self.budget.out_of_time() {
+                break;
+            }
+            doc = scorer.advance();
+        }
+        Ok(child.harvest())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tantivy::collector::Count;
+    use tantivy::query::AllQuery;
+    use tantivy::schema::{FAST, Schema};
+    use tantivy::{Index, TantivyDocument};
+
+    #[test]
+    fn stops_collecting_at_the_deadline() {
+        let mut builder = Schema::builder();
+        let field = builder.add_u64_field("n", FAST);
+        let index = Index::create_in_ram(builder.build());
+        let mut writer = index.writer_with_num_threads(1, 15_000_000).unwrap();
+        for n in 0..1_000 {
+            let mut doc = TantivyDocument::new();
+            doc.add_u64(field, n);
+            writer.add_document(doc).unwrap();
+        }
+        writer.commit().unwrap();
+        let searcher = index.reader().unwrap().searcher();
+
+        let unlimited = Budget::new(None);
+        let all = searcher
+            .search(&AllQuery, &unlimited.collector(Count))
+            .unwrap();
+        assert_eq!(all, 1_000);
+        assert!(!unlimited.expired());
+
+        let roomy = Budget::new(Some(Duration::from_secs(60)));
+        let all = searcher.search(&AllQuery, &roomy.collector(Count)).unwrap();
+        assert_eq!(all, 1_000);
+        assert!(!roomy.expired());
+
+        let spent = Budget::new(Some(Duration::ZERO));
+        let none = searcher.search(&AllQuery, &spent.collector(Count)).unwrap();
+        assert_eq!(none, 0);
+        assert!(spent.expired());
+    }
+}
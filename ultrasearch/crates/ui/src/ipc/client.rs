@@ -1,7 +1,7 @@
 use anyhow::Result;
 use ipc::{
-    ReloadConfigRequest, ReloadConfigResponse, RescanRequest, RescanResponse, SearchRequest,
-    SearchResponse, StatusRequest, StatusResponse,
+    HitBatch, ReloadConfigRequest, ReloadConfigResponse, RescanRequest, RescanResponse,
+    SearchRequest, SearchResponse, StatusRequest, StatusResponse, SuggestRequest, SuggestResponse,
 };
 #[cfg(windows)]
 use std::sync::Arc;
@@ -48,10 +48,33 @@ impl IpcClient {
                 truncated: false,
                 took_ms: 0,
                 served_by: Some("ui-stub".into()),
+                error: None,
+                facets: Vec::new(),
+                explain: None,
+                next_cursor: None,
+                suggestions: Vec::new(),
             })
         }
     }
 
+    /// Streamed search: `on_batch` sees each index pass's hits as they
+    /// arrive, and the returned response carries the final ranking.
+    pub async fn search_stream(
+        &self,
+        req: SearchRequest,
+        on_batch: impl FnMut(&HitBatch),
+    ) -> Result<SearchResponse> {
+        #[cfg(windows)]
+        {
+            self.inner.search_stream(req, on_batch).await
+        }
+        #[cfg(not(windows))]
+        {
+            let _ = on_batch;
+            self.search(req).await
+        }
+    }
+
     pub async fn status(&self, req: StatusRequest) -> Result<StatusResponse> {
         #[cfg(windows)]
         {
@@ -103,6 +126,20 @@ impl IpcClient {
             })
         }
     }
+
+    pub async fn suggest(&self, req: SuggestRequest) -> Result<SuggestResponse> {
+        #[cfg(windows)]
+        {
+            self.inner.suggest(req).await
+        }
+        #[cfg(not(windows))]
+        {
+            Ok(SuggestResponse {
+                id: req.id,
+                completions: Vec::new(),
+            })
+        }
+    }
 }
 
 impl Default for IpcClient {
@@ -1,9 +1,10 @@
-use crate::background::{set_tray_status, TrayState};
+use crate::background::{TrayState, set_tray_status};
 use crate::ipc::client::IpcClient;
+use core_types::query::{ParseError, parse_query};
 use gpui::*;
 use ipc::{
-    MetricsSnapshot, QueryExpr, SearchHit, SearchMode, SearchRequest, StatusRequest, TermExpr,
-    TermModifier, VolumeStatus,
+    Completion, HitBatch, MetricsSnapshot, QueryExpr, SearchHit, SearchMode, SearchRequest,
+    StatusRequest, SuggestRequest, TermExpr, TermModifier, VolumeStatus,
 };
 use std::collections::VecDeque;
 use std::time::{Duration, Instant};
@@ -91,6 +92,17 @@ impl Default for SearchStatus {
     }
 }
 
+/// Parse the search box text; half-typed input (e.g. `ext:`) falls back to a plain term.
+fn query_expr_for(input: &str) -> QueryExpr {
+    parse_query(input).unwrap_or_else(|_| {
+        QueryExpr::Term(TermExpr {
+            field: None,
+            value: input.to_string(),
+            modifier: TermModifier::Term,
+        })
+    })
+}
+
 pub struct SearchAppModel {
     pub query: String,
     pub results: Vec<SearchHit>,
@@ -109,6 +121,11 @@ pub struct SearchAppModel {
     pub last_search: Option<Instant>,
     pub show_onboarding: bool,
     pub show_status: bool,
+    pub completions: Vec<Completion>,
+    pub suggest_task: Option<Task<()>>,
+    /// Why the query as typed does not parse; the search still runs it as a
+    /// plain term.
+    pub query_error: Option<ParseError>,
 }
 
 impl SearchAppModel {
@@ -133,6 +150,9 @@ impl SearchAppModel {
             last_search: None,
             show_onboarding: false,
             show_status: false,
+            completions: Vec::new(),
+            suggest_task: None,
+            query_error: None,
         };
 
         model.start_status_polling(cx);
@@ -245,6 +265,12 @@ impl SearchAppModel {
 
     pub fn set_query(&mut self, query: String, cx: &mut Context<SearchAppModel>) {
         self.query = query;
+        self.query_error = if self.query.trim().is_empty() {
+            None
+        } else {
+            parse_query(&self.query).err()
+        };
+        self.request_completions(cx);
 
         // Cancel previous debounce task
         if let Some(task) = self.search_debounce.take() {
@@ -280,15 +306,16 @@ impl SearchAppModel {
 
                     let req = SearchRequest {
                         id: Uuid::new_v4(),
-                        query: QueryExpr::Term(TermExpr {
-                            field: None,
-                            value: query_clone.clone(),
-                            modifier: TermModifier::Term,
-                        }),
+                        query: query_expr_for(&query_clone),
                         limit: 100,
                         mode: mode.into(),
                         timeout: Some(Duration::from_secs(5)),
                         offset: 0,
+                        sort: None,
+                        facets: None,
+                        explain: false,
+                        cursor: None,
+                        stream: true,
                     };
 
                     let start = Instant::now();
@@ -301,7 +328,35 @@ impl SearchAppModel {
                             },
                         )
                     });
-                    match client.search(req).await {
+                    // Show each pass's hits as they arrive; the final
+                    // response then replaces them with the fused ranking.
+                    let mut first_batch = true;
+                    let on_batch = |batch: &HitBatch| {
+                        let replace = std::mem::take(&mut first_batch);
+                        let _ = async_app.update(|app| {
+                            this.update(
+                                app,
+                                |model: &mut SearchAppModel, cx: &mut Context<SearchAppModel>| {
+                                    if replace {
+                                        model.results.clear();
+                                        model.page = 0;
+                                    }
+                                    for hit in &batch.hits {
+                                        if !model.results.iter().any(|h| h.key == hit.key) {
+                                            model.results.push(hit.clone());
+                                        }
+                                    }
+                                    model.status.total = model.results.len() as u64;
+                                    model.status.shown = model.current_page_results().len();
+                                    if model.selected_index.is_none() && !model.results.is_empty() {
+                                        model.selected_index = Some(0);
+                                    }
+                                    cx.notify();
+                                },
+                            )
+                        });
+                    };
+                    match client.search_stream(req, on_batch).await {
                         Ok(resp) => {
                             let latency = start.elapsed().as_millis() as u32;
                             let _ = async_app.update(|app| {
@@ -346,6 +401,45 @@ impl SearchAppModel {
         ));
     }
 
+    /// Fetch file-name completions for the query as typed. The service
+    /// answers these from the name FST, so they skip the search debounce.
+    fn request_completions(&mut self, cx: &mut Context<SearchAppModel>) {
+        const MAX_COMPLETIONS: u32 = 5;
+
+        let prefix = self.query.trim().to_string();
+        // Only a single bare word can be the start of a file name.
+        if prefix.is_empty() || prefix.contains(|c: char| c.is_whitespace() || c == ':') {
+            self.completions.clear();
+            self.suggest_task = None;
+            return;
+        }
+
+        let client = self.client.clone();
+        self.suggest_task = Some(cx.spawn(
+            move |this: WeakEntity<SearchAppModel>, cx: &mut AsyncApp| {
+                let async_app = cx.clone();
+                async move {
+                    let req = SuggestRequest::new(prefix, MAX_COMPLETIONS);
+                    match client.suggest(req).await {
+                        Ok(resp) => {
+                            let _ = async_app.update(|app| {
+                                this.update(
+                                    app,
+                                    |model: &mut SearchAppModel,
+                                     cx: &mut Context<SearchAppModel>| {
+                                        model.completions = resp.completions;
+                                        cx.notify();
+                                    },
+                                )
+                            });
+                        }
+                        Err(err) => tracing::debug!("suggest request failed: {err}"),
+                    }
+                }
+            },
+        ));
+    }
+
     pub fn set_backend_mode(&mut self, mode: BackendMode, cx: &mut Context<SearchAppModel>) {
         self.status.backend_mode = mode;
         // Re-trigger search if we have a query
@@ -423,6 +517,10 @@ impl SearchAppModel {
                 size: Some(12_345 + i as u64 * 10),
                 modified: Some(1_700_000_000 + i as i64 * 60),
                 snippet: Some("Lorem ipsum dolor sit amet, consectetur adipiscing elit.".into()),
+                approximate: false,
+                highlights: Default::default(),
+                created: Some(1_700_000_000 - i as i64 * 60),
+                explain: Vec::new(),
             });
         }
         self.page = 0;
@@ -592,5 +690,8 @@ impl Drop for SearchAppModel {
         if let Some(task) = self.search_debounce.take() {
             drop(task);
         }
+        if let Some(task) = self.suggest_task.take() {
+            drop(task);
+        }
     }
 }
@@ -82,6 +82,7 @@ impl Render for QuickBarView {
                     .gap_2()
                     .child(div().flex_shrink_0().child(self.search_view.clone()))
                     .child(self.render_history(cx))
+                    .child(self.render_completions(cx))
                     .child(
                         div()
                             .flex_1()
@@ -141,4 +142,57 @@ impl QuickBarView {
             }))
             .into_any_element()
     }
+
+    fn render_completions(&self, cx: &mut Context<Self>) -> impl IntoElement {
+        let colors = theme::active_colors(cx);
+        let model = self.model.read(cx);
+        if model.query.trim().is_empty() || model.completions.is_empty() {
+            return Empty.into_any_element();
+        }
+
+        let items = model.completions.clone();
+
+        div()
+            .bg(colors.bg)
+            .border_1()
+            .border_color(colors.border)
+            .rounded_md()
+            .p_2()
+            .flex()
+            .flex_col()
+            .gap_1()
+            .text_size(px(12.))
+            .text_color(colors.text_secondary)
+            .child(
+                div()
+                    .text_color(colors.text_primary)
+                    .font_weight(FontWeight::BOLD)
+                    .child("File names"),
+            )
+            .children(items.into_iter().map(|c| {
+                let label = if c.files > 1 {
+                    format!("{} ({} files)", c.name, c.files)
+                } else {
+                    c.name.clone()
+                };
+                let name = c.name;
+                div()
+                    .px_2()
+                    .py_1()
+                    .rounded_md()
+                    .hover(|s| s.bg(colors.divider))
+                    .cursor_pointer()
+                    .child(label)
+                    .on_mouse_down(
+                        MouseButton::Left,
+                        cx.listener(move |this, _, _, cx| {
+                            this.model.update(cx, |m, cx| {
+                                m.set_query(name.clone(), cx);
+                                m.push_history(&name);
+                            });
+                        }),
+                    )
+            }))
+            .into_any_element()
+    }
 }
@@ -6,7 +6,8 @@ use crate::theme;
 use crate::views::context_menu::{ContextMenu, ContextMenuItem};
 use gpui::prelude::*;
 use gpui::{InteractiveElement, *};
-use ipc::SearchHit;
+use ipc::{MatchRange, SearchHit};
+use std::ops::Range;
 use std::process::Command;
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
@@ -14,6 +15,31 @@ fn row_height() -> Pixels {
     px(48.)
 }
 
+/// Byte ranges to highlight: the service's match ranges when it sent any,
+/// otherwise a case-insensitive guess at the raw query text.
+fn match_spans(text: &str, ranges: &[MatchRange], query: &str) -> Vec<Range<usize>> {
+    let valid: Vec<Range<usize>> = ranges
+        .iter()
+        .map(|r| r.start as usize..r.end as usize)
+        .filter(|r| {
+            r.start < r.end && text.is_char_boundary(r.start) && text.is_char_boundary(r.end)
+        })
+        .collect();
+    if !valid.is_empty() {
+        return valid;
+    }
+
+    let needle = query.trim().to_ascii_lowercase();
+    if needle.is_empty() {
+        return Vec::new();
+    }
+    text.to_ascii_lowercase()
+        .find(&needle)
+        .map(|pos| pos..pos + needle.len())
+        .into_iter()
+        .collect()
+}
+
 pub struct ResultsView {
     model: Entity<SearchAppModel>,
     icon_cache: Entity<IconCache>,
@@ -50,93 +76,55 @@ impl ResultsView {
     fn highlight_text(
         &self,
         text: &str,
+        ranges: &[MatchRange],
         query: &str,
         is_primary: bool,
         cx: &mut Context<Self>,
     ) -> AnyElement {
         let colors = theme::active_colors(cx);
-        let needle = query.trim();
-        if needle.is_empty() {
-            return div()
-                .text_size(if is_primary { px(14.) } else { px(11.) })
-                .font_weight(if is_primary {
-                    FontWeight::MEDIUM
-                } else {
-                    FontWeight::NORMAL
-                })
-                .text_color(if is_primary {
-                    colors.text_primary
-                } else {
-                    colors.text_secondary
-                })
-                .overflow_hidden()
-                .whitespace_nowrap()
-                .text_ellipsis()
-                .child(text.to_string())
-                .into_any_element();
-        }
-
-        let lower = text.to_ascii_lowercase();
-        let needle_lower = needle.to_ascii_lowercase();
-        if let Some(pos) = lower.find(&needle_lower) {
-            let end = pos + needle_lower.len();
-            let (pre, matched, post) = (&text[..pos], &text[pos..end], &text[end..text.len()]);
-
-            div()
-                .text_size(if is_primary { px(14.) } else { px(11.) })
-                .font_weight(if is_primary {
-                    FontWeight::MEDIUM
-                } else {
-                    FontWeight::NORMAL
-                })
-                .text_color(if is_primary {
-                    colors.text_primary
-                } else {
-                    colors.text_secondary
-                })
-                .overflow_hidden()
-                .whitespace_nowrap()
-                .text_ellipsis()
-                .child(pre.to_string())
-                .child(
-                    div()
-                        .px_1()
-                        .rounded_sm()
-                        .bg(colors.match_highlight)
-                        .text_color(colors.bg)
-                        .child(matched.to_string()),
-                )
-                .child(post.to_string())
-                .into_any_element()
-        } else {
-            div()
-                .text_size(if is_primary { px(14.) } else { px(11.) })
-                .font_weight(if is_primary {
-                    FontWeight::MEDIUM
-                } else {
-                    FontWeight::NORMAL
-                })
-                .text_color(if is_primary {
-                    colors.text_primary
-                } else {
-                    colors.text_secondary
-                })
-                .overflow_hidden()
-                .whitespace_nowrap()
-                .text_ellipsis()
-                .child(text.to_string())
-                .into_any_element()
+        let mut el = div()
+            .text_size(if is_primary { px(14.) } else { px(11.) })
+            .font_weight(if is_primary {
+                FontWeight::MEDIUM
+            } else {
+                FontWeight::NORMAL
+            })
+            .text_color(if is_primary {
+                colors.text_primary
+            } else {
+                colors.text_secondary
+            })
+            .overflow_hidden()
+            .whitespace_nowrap()
+            .text_ellipsis();
+
+        let mut cursor = 0;
+        for range in match_spans(text, ranges, query) {
+            if range.start < cursor {
+                continue;
+            }
+            el = el.child(text[cursor..range.start].to_string()).child(
+                div()
+                    .px_1()
+                    .rounded_sm()
+                    .bg(colors.match_highlight)
+                    .text_color(colors.bg)
+                    .child(text[range.clone()].to_string()),
+            );
+            cursor = range.end;
         }
+        el.child(text[cursor..].to_string()).into_any_element()
     }
 
     fn render_highlighted(
         &self,
         text: &str,
+        ranges: &[MatchRange],
         query: &str,
         is_primary: bool,
         cx: &mut Context<Self>,
     ) -> AnyElement {
-        self.highlight_text(text, query, is_primary, cx)
+        self.highlight_text(text, ranges, query, is_primary, cx)
     }
 
     fn handle_click(&mut self, index: usize, cx: &mut Context<Self>) {
@@ -410,8 +398,8 @@ impl ResultsView {
                     .flex_col()
                     .gap_1()
                     .overflow_hidden()
-                    .child(self.render_highlighted(&name, query, true, cx))
-                    .child(self.render_highlighted(&path, query, false, cx)),
+                    .child(self.render_highlighted(&name, &hit.highlights.name, query, true, cx))
+                    .child(self.render_highlighted(&path, &hit.highlights.path, query, false, cx)),
             )
             // Score badge
             .when(score_pct > 0, |mut this: Div| {
@@ -1,6 +1,7 @@
 use crate::actions::{MinimizeToTray, ToggleShortcuts};
 use crate::model::state::{BackendMode, SearchAppModel};
-use crate::theme;
+use crate::theme::{self, ThemeColors};
+use core_types::query::ParseError;
 use gpui::prelude::*;
 use gpui::{InteractiveElement, *};
 
@@ -286,6 +287,52 @@ impl SearchView {
                 cx.listener(move |this, _, _, cx| this.set_mode(mode, cx)),
             )
     }
+
+    /// The parser's message under the search box, with the offending part of
+    /// the query marked.
+    fn render_query_error(query: &str, err: &ParseError, colors: &ThemeColors) -> impl IntoElement {
+        let error = hsla(0.0, 0.903, 0.661, 1.0);
+        let end = err.span.end.min(query.len());
+        let start = err.span.start.min(end);
+        let (head, marked, tail) =
+            match (query.get(..start), query.get(start..end), query.get(end..)) {
+                (Some(head), Some(marked), Some(tail)) => (head, marked, tail),
+                _ => (query, "", ""),
+            };
+        // An empty span points between characters, usually at a missing value.
+        let marked = if marked.is_empty() { "␣" } else { marked };
+
+        div()
+            .px_4()
+            .pb_2()
+            .flex()
+            .items_center()
+            .gap_3()
+            .text_size(px(12.))
+            .child(
+                div()
+                    .flex()
+                    .items_center()
+                    .font_family("monospace")
+                    .text_color(colors.text_secondary)
+                    .child(div().child(SharedString::from(head.to_string())))
+                    .child(
+                        div()
+                            .rounded_sm()
+                            .bg(colors.selection_bg)
+                            .border_b_1()
+                            .border_color(error)
+                            .text_color(error)
+                            .child(SharedString::from(marked.to_string())),
+                    )
+                    .child(div().child(SharedString::from(tail.to_string()))),
+            )
+            .child(
+                div()
+                    .text_color(error)
+                    .child(format!("{} (searching the text as typed)", err.message)),
+            )
+    }
 }
 
 impl Render for SearchView {
@@ -293,8 +340,12 @@ impl Render for SearchView {
         let model = self.model.read(cx);
         let status = model.status.clone();
         let query = model.query.clone();
-        let ipc_recovered = model.ipc_recent_reconnect;
         let colors = theme::active_colors(cx);
+        let query_error = model
+            .query_error
+            .as_ref()
+            .map(|err| Self::render_query_error(&query, err, &colors));
+        let ipc_recovered = model.ipc_recent_reconnect;
         let totals = status
             .volumes
             .iter()
@@ -650,6 +701,7 @@ impl Render for SearchView {
                             )),
                     ),
             )
+            .children(query_error)
             .child(
                 // Inline helper tips
                 div()